@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample};
+
+use super::mixer::{Bus, Mixer, Playback, VoiceId};
+use super::sound::Sound;
+
+// Mixing rate when there is no device to take it from
+pub const NULL_SAMPLE_RATE: u32 = 44100;
+
+enum Output {
+    // The sound card pulls samples from the mixer on its own thread, for as
+    // long as the stream is kept
+    Device { _stream: cpal::Stream },
+    // Nothing is listening, the mixer runs when asked to render or to keep
+    // time. Holds the fraction of a frame left over between ticks.
+    Null(f64),
+}
+
+// The game's side of the sound: loads files and starts and stops voices on
+// the mixer, which mixes wherever the output wants it
+pub struct Audio {
+    mixer: Arc<Mutex<Mixer>>,
+    output: Output,
+    // Handles by filename, so each file is decoded once
+    files: HashMap<String, usize>,
+}
+
+impl Audio {
+    // Plays through the default output device, or goes silent without one
+    pub fn open() -> Self {
+        match Self::open_device() {
+            Ok(audio) => audio,
+            Err(e) => {
+                eprintln!("Cannot open an audio device, playing without sound: {e}");
+                Self::null(NULL_SAMPLE_RATE)
+            }
+        }
+    }
+
+    // Mixes only in update and render, for running without a sound device
+    pub fn null(sample_rate: u32) -> Self {
+        Audio {
+            mixer: Arc::new(Mutex::new(Mixer::new(sample_rate))),
+            output: Output::Null(0.0),
+            files: HashMap::new(),
+        }
+    }
+
+    fn open_device() -> Result<Self, String> {
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or("no output device")?;
+        let supported = device.default_output_config().map_err(|e| e.to_string())?;
+        let config = supported.config();
+        let mixer = Arc::new(Mutex::new(Mixer::new(config.sample_rate.0)));
+
+        let stream = match supported.sample_format() {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config, mixer.clone()),
+            SampleFormat::I16 => build_stream::<i16>(&device, &config, mixer.clone()),
+            SampleFormat::U16 => build_stream::<u16>(&device, &config, mixer.clone()),
+            SampleFormat::I32 => build_stream::<i32>(&device, &config, mixer.clone()),
+            format => Err(format!("unsupported sample format {format}")),
+        }?;
+        stream.play().map_err(|e| e.to_string())?;
+
+        Ok(Audio {
+            mixer,
+            output: Output::Device { _stream: stream },
+            files: HashMap::new(),
+        })
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self.output, Output::Null(_))
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.mixer().sample_rate
+    }
+
+    // Returns a handle to play, the same one every time a file is loaded again
+    pub fn load(&mut self, filename: &str) -> usize {
+        if let Some(sound) = self.files.get(filename) {
+            return *sound;
+        }
+
+        // Decoded before taking the lock, the device keeps mixing meanwhile
+        let sound = Sound::load(filename);
+        let handle = self.mixer().add(sound);
+        self.files.insert(filename.to_string(), handle);
+        handle
+    }
+
+    pub fn play(&mut self, sound: usize, playback: Playback) -> VoiceId {
+        self.mixer().play(sound, playback)
+    }
+
+    pub fn play_music(&mut self, sound: usize, volume: f32) -> VoiceId {
+        self.mixer().play_music(sound, volume)
+    }
+
+    pub fn stop(&mut self, id: VoiceId) {
+        self.mixer().stop(id);
+    }
+
+    pub fn stop_bus(&mut self, bus: Bus) {
+        self.mixer().stop_bus(bus);
+    }
+
+    pub fn is_playing(&self, id: VoiceId) -> bool {
+        self.mixer().is_playing(id)
+    }
+
+    pub fn voice_count(&self) -> usize {
+        self.mixer().voice_count()
+    }
+
+    pub fn bus_volume(&self, bus: Bus) -> f32 {
+        self.mixer().bus_volume(bus)
+    }
+
+    pub fn set_bus_volume(&mut self, bus: Bus, volume: f32) {
+        self.mixer().set_bus_volume(bus, volume);
+    }
+
+    pub fn master_volume(&self) -> f32 {
+        self.mixer().master_volume
+    }
+
+    pub fn set_master_volume(&mut self, volume: f32) {
+        self.mixer().master_volume = volume;
+    }
+
+    // One fixed tick, dt in milliseconds. Without a device the tick's worth
+    // of sound is mixed and thrown away, so voices still end on time.
+    pub fn update(&mut self, dt: f32) {
+        let rate = self.sample_rate() as f64;
+        let Output::Null(leftover) = &mut self.output else {
+            return;
+        };
+        let frames = *leftover + dt as f64 / 1000.0 * rate;
+        *leftover = frames.fract();
+        let mut buffer = vec![0.0; 2 * frames as usize];
+        self.mixer().mix(&mut buffer);
+    }
+
+    // Mixes the next frames into interleaved left and right samples. Meant
+    // for the null output, a device takes its samples by itself.
+    pub fn render(&mut self, frames: usize) -> Vec<f32> {
+        let mut buffer = vec![0.0; 2 * frames];
+        self.mixer().mix(&mut buffer);
+        buffer
+    }
+
+    fn mixer(&self) -> MutexGuard<'_, Mixer> {
+        self.mixer.lock().unwrap()
+    }
+}
+
+// Mixes stereo and spreads it over however many channels the device has
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mixer: Arc<Mutex<Mixer>>,
+) -> Result<cpal::Stream, String>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let channels = config.channels as usize;
+    let mut stereo = Vec::new();
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _| {
+                stereo.resize(data.len() / channels * 2, 0.0);
+                mixer.lock().unwrap().mix(&mut stereo);
+                for (frame, mixed) in data.chunks_exact_mut(channels).zip(stereo.chunks_exact(2)) {
+                    for (channel, sample) in frame.iter_mut().enumerate() {
+                        let value = match (channels, channel) {
+                            (1, _) => 0.5 * (mixed[0] + mixed[1]),
+                            (_, 0 | 1) => mixed[channel],
+                            _ => 0.0,
+                        };
+                        *sample = T::from_sample(value);
+                    }
+                }
+            },
+            |e| eprintln!("Audio stream error: {e}"),
+            None,
+        )
+        .map_err(|e| e.to_string())
+}
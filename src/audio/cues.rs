@@ -0,0 +1,66 @@
+use super::audio::Audio;
+use super::mixer::Playback;
+use crate::model::animation::AnimationSet;
+use crate::model::world::World;
+
+// World pixels from the middle of the view: sounds are at full volume up to
+// HEARING_NEAR and fade out to nothing at HEARING_FAR, a little past the edge
+const HEARING_NEAR: f32 = 200.0;
+const HEARING_FAR: f32 = 480.0;
+
+// Volume of something at position for a listener at listener, from 0 to 1
+pub fn falloff(position: glm::Vec2, listener: glm::Vec2) -> f32 {
+    let distance = glm::length(position - listener);
+    ((HEARING_FAR - distance) / (HEARING_FAR - HEARING_NEAR)).clamp(0.0, 1.0)
+}
+
+// Plays the sounds the set's file ties to the events, events without one are
+// left to the gameplay code
+pub fn play_events(audio: &mut Audio, set: &AnimationSet, events: &[String], volume: f32) {
+    if volume <= 0.0 {
+        return;
+    }
+    for event in events {
+        let Some(cue) = set.sounds.get(event) else {
+            continue;
+        };
+        let sound = audio.load(&cue.path);
+        audio.play(
+            sound,
+            Playback {
+                volume: cue.volume * volume,
+                pitch_variation: cue.pitch_variation,
+                ..Default::default()
+            },
+        );
+    }
+}
+
+// One fixed tick after the world's, so each sound starts on the tick its
+// frame is first drawn
+pub fn play_world(audio: &mut Audio, world: &World) {
+    let listener = world.camera_2d.position;
+    let player = &world.player;
+    play_events(
+        audio,
+        &player.animations,
+        &player.events,
+        falloff(player.body.centre(), listener),
+    );
+    for skeleton in &world.skeletons {
+        play_events(
+            audio,
+            &world.skeleton_animations,
+            &skeleton.events,
+            falloff(skeleton.body.centre(), listener),
+        );
+    }
+    if let Some(boss) = &world.boss {
+        play_events(
+            audio,
+            &world.boss_animations,
+            &boss.events,
+            falloff(boss.body.centre(), listener),
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use super::sound::Sound;
+
+pub const BUS_COUNT: usize = 3;
+
+// Slowest a voice plays, so a zero or negative pitch can't leave a one-shot
+// stuck on its first frame for good
+const LOWEST_PITCH: f32 = 0.05;
+
+// Every voice goes through one bus, so each kind of sound has its own volume
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bus {
+    Music,
+    Effects,
+    Ui,
+}
+
+// How a sound is played
+#[derive(Clone, Copy, Debug)]
+pub struct Playback {
+    pub bus: Bus,
+    pub volume: f32,
+    // Speed the sound is played at, 2 is an octave up
+    pub pitch: f32,
+    // Each time it is played the pitch is moved up to this fraction either
+    // way at random, so repeated effects don't all sound the same
+    pub pitch_variation: f32,
+    // Starts over at the end until it is stopped
+    pub looping: bool,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Playback {
+            bus: Bus::Effects,
+            volume: 1.0,
+            pitch: 1.0,
+            pitch_variation: 0.0,
+            looping: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VoiceId(u64);
+
+// A sound being played
+struct Voice {
+    id: VoiceId,
+    sound: usize,
+    bus: Bus,
+    volume: f32,
+    pitch: f32,
+    looping: bool,
+    // In the sound's frames, between them when the rates or pitch differ
+    position: f64,
+}
+
+// Adds up every voice playing into stereo output at one sample rate
+pub struct Mixer {
+    pub sample_rate: u32,
+    pub master_volume: f32,
+    bus_volumes: [f32; BUS_COUNT],
+    sounds: Vec<Sound>,
+    voices: Vec<Voice>,
+    next_id: u64,
+    // Xorshift state for the pitch variation
+    random: u32,
+}
+
+impl Mixer {
+    pub fn new(sample_rate: u32) -> Self {
+        Mixer {
+            sample_rate,
+            master_volume: 1.0,
+            bus_volumes: [1.0; BUS_COUNT],
+            sounds: Vec::new(),
+            voices: Vec::new(),
+            next_id: 0,
+            random: 0x2545_f491,
+        }
+    }
+
+    // Returns the handle to play the sound with
+    pub fn add(&mut self, sound: Sound) -> usize {
+        self.sounds.push(sound);
+        self.sounds.len() - 1
+    }
+
+    pub fn sound(&self, sound: usize) -> &Sound {
+        &self.sounds[sound]
+    }
+
+    pub fn bus_volume(&self, bus: Bus) -> f32 {
+        self.bus_volumes[bus as usize]
+    }
+
+    pub fn set_bus_volume(&mut self, bus: Bus, volume: f32) {
+        self.bus_volumes[bus as usize] = volume;
+    }
+
+    pub fn play(&mut self, sound: usize, playback: Playback) -> VoiceId {
+        let variation = playback.pitch_variation * (2.0 * self.next_random() - 1.0);
+        let id = VoiceId(self.next_id);
+        self.next_id += 1;
+        self.voices.push(Voice {
+            id,
+            sound,
+            bus: playback.bus,
+            volume: playback.volume,
+            pitch: (playback.pitch * (1.0 + variation)).max(LOWEST_PITCH),
+            looping: playback.looping,
+            position: 0.0,
+        });
+        id
+    }
+
+    // Loops the sound on the music bus in place of whatever played there,
+    // carrying on if it is already playing
+    pub fn play_music(&mut self, sound: usize, volume: f32) -> VoiceId {
+        if let Some(voice) = self
+            .voices
+            .iter_mut()
+            .find(|voice| voice.bus == Bus::Music && voice.sound == sound)
+        {
+            voice.volume = volume;
+            return voice.id;
+        }
+
+        self.stop_bus(Bus::Music);
+        self.play(
+            sound,
+            Playback {
+                bus: Bus::Music,
+                volume,
+                looping: true,
+                ..Default::default()
+            },
+        )
+    }
+
+    pub fn stop(&mut self, id: VoiceId) {
+        self.voices.retain(|voice| voice.id != id);
+    }
+
+    pub fn stop_bus(&mut self, bus: Bus) {
+        self.voices.retain(|voice| voice.bus != bus);
+    }
+
+    pub fn is_playing(&self, id: VoiceId) -> bool {
+        self.voices.iter().any(|voice| voice.id == id)
+    }
+
+    pub fn voice_count(&self) -> usize {
+        self.voices.len()
+    }
+
+    // Fills interleaved left and right samples, dropping the voices that end.
+    // Silent buses still move their voices along, so muted music stays in time.
+    pub fn mix(&mut self, output: &mut [f32]) {
+        output.fill(0.0);
+        let rate = self.sample_rate as f64;
+
+        for voice in &mut self.voices {
+            let sound = &self.sounds[voice.sound];
+            let length = sound.frames.len();
+            if length == 0 {
+                voice.looping = false;
+                voice.position = 0.0;
+                continue;
+            }
+            let gain = voice.volume * self.bus_volumes[voice.bus as usize] * self.master_volume;
+            let step = voice.pitch as f64 * sound.sample_rate as f64 / rate;
+
+            for frame in output.chunks_exact_mut(2) {
+                if voice.position >= length as f64 {
+                    if !voice.looping {
+                        break;
+                    }
+                    voice.position %= length as f64;
+                }
+
+                // Straight line between the two nearest frames
+                let index = voice.position as usize;
+                let fraction = (voice.position - index as f64) as f32;
+                let next = if index + 1 < length {
+                    sound.frames[index + 1]
+                } else if voice.looping {
+                    sound.frames[0]
+                } else {
+                    [0.0, 0.0]
+                };
+                let current = sound.frames[index];
+                for side in 0..2 {
+                    let sample = current[side] + (next[side] - current[side]) * fraction;
+                    frame[side] += sample * gain;
+                }
+                voice.position += step;
+            }
+        }
+
+        self.voices.retain(|voice| {
+            voice.looping || voice.position < self.sounds[voice.sound].frames.len() as f64
+        });
+        for sample in output {
+            *sample = sample.clamp(-1.0, 1.0);
+        }
+    }
+
+    // From 0 to 1
+    fn next_random(&mut self) -> f32 {
+        self.random ^= self.random << 13;
+        self.random ^= self.random >> 17;
+        self.random ^= self.random << 5;
+        self.random as f32 / u32::MAX as f32
+    }
+}
@@ -0,0 +1,5 @@
+#[allow(clippy::module_inception)]
+pub mod audio;
+pub mod cues;
+pub mod mixer;
+pub mod sound;
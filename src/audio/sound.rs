@@ -0,0 +1,88 @@
+use std::io::{Cursor, Read, Seek};
+use std::path::Path;
+
+use lewton::inside_ogg::OggStreamReader;
+
+// A whole decoded file as stereo frames at the file's own sample rate, mono
+// files play the same on both sides and channels past the second are dropped
+pub struct Sound {
+    pub sample_rate: u32,
+    pub frames: Vec<[f32; 2]>,
+}
+
+impl Sound {
+    // Picks the decoder by extension, .wav or .ogg
+    pub fn load(filename: &str) -> Self {
+        let bytes =
+            std::fs::read(filename).unwrap_or_else(|e| panic!("Cannot read sound {filename}: {e}"));
+        let extension = Path::new(filename)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        let sound = match extension.as_str() {
+            "wav" => Self::from_wav(Cursor::new(bytes)),
+            "ogg" => Self::from_ogg(Cursor::new(bytes)),
+            _ => Err(format!("unknown extension .{extension}")),
+        };
+        sound.unwrap_or_else(|e| panic!("Cannot decode sound {filename}: {e}"))
+    }
+
+    pub fn from_wav<R: Read>(reader: R) -> Result<Self, String> {
+        let mut reader = hound::WavReader::new(reader).map_err(|e| e.to_string())?;
+        let spec = reader.spec();
+        let samples: Result<Vec<f32>, _> = match spec.sample_format {
+            hound::SampleFormat::Float => reader.samples::<f32>().collect(),
+            hound::SampleFormat::Int => {
+                // Full scale of the signed samples hound hands back
+                let scale = (1_i64 << (spec.bits_per_sample - 1)) as f32;
+                reader
+                    .samples::<i32>()
+                    .map(|sample| sample.map(|sample| sample as f32 / scale))
+                    .collect()
+            }
+        };
+        let samples = samples.map_err(|e| e.to_string())?;
+        Ok(Self::from_interleaved(
+            &samples,
+            spec.channels as usize,
+            spec.sample_rate,
+        ))
+    }
+
+    pub fn from_ogg<R: Read + Seek>(reader: R) -> Result<Self, String> {
+        let mut reader = OggStreamReader::new(reader).map_err(|e| e.to_string())?;
+        let channels = reader.ident_hdr.audio_channels as usize;
+        let sample_rate = reader.ident_hdr.audio_sample_rate;
+
+        let mut samples = Vec::new();
+        while let Some(packet) = reader.read_dec_packet_itl().map_err(|e| e.to_string())? {
+            samples.extend(
+                packet
+                    .into_iter()
+                    .map(|sample| sample as f32 / i16::MAX as f32),
+            );
+        }
+        Ok(Self::from_interleaved(&samples, channels, sample_rate))
+    }
+
+    pub fn from_interleaved(samples: &[f32], channels: usize, sample_rate: u32) -> Self {
+        let frames = samples
+            .chunks_exact(channels.max(1))
+            .map(|frame| match frame {
+                [mono] => [*mono, *mono],
+                [left, right, ..] => [*left, *right],
+                [] => [0.0, 0.0],
+            })
+            .collect();
+        Sound {
+            sample_rate,
+            frames,
+        }
+    }
+
+    // Seconds
+    pub fn duration(&self) -> f32 {
+        self.frames.len() as f32 / self.sample_rate as f32
+    }
+}
@@ -1 +1,45 @@
-pub const MOVEMENT_SPEED: f32 = 0.5;
+// Simulation rate, independent of the display refresh rate
+pub const TICKS_PER_SECOND: u32 = 60;
+// Ticks run at most per frame before the game slows down instead
+pub const MAX_TICKS_PER_FRAME: u32 = 5;
+
+// How many screen pixels a world pixel covers
+pub const CAMERA_ZOOM: f32 = 2.0;
+
+// Knight collision box while standing, in world pixels
+pub const KNIGHT_WIDTH: f32 = 20.0;
+pub const KNIGHT_HEIGHT: f32 = 44.0;
+pub const KNIGHT_HEALTH: i32 = 100;
+// Seconds the knight cannot be hurt again after a hit
+pub const KNIGHT_RECOVERY: f32 = 1.0;
+// Touching a hazard tile, knockback in pixels per second away from the tile and upwards
+pub const HAZARD_DAMAGE: i32 = 10;
+pub const HAZARD_KNOCKBACK_X: f32 = 120.0;
+pub const HAZARD_KNOCKBACK_Y: f32 = -260.0;
+// How far above the head the hands reach when hanging from a ledge
+pub const LEDGE_GRAB_HEIGHT: f32 = 12.0;
+// How far from the hands a ledge can be and still be grabbed
+pub const LEDGE_REACH: f32 = 8.0;
+
+// Skeleton collision box, in world pixels
+pub const SKELETON_WIDTH: f32 = 18.0;
+pub const SKELETON_HEIGHT: f32 = 56.0;
+pub const SKELETON_HEALTH: i32 = 30;
+pub const SKELETON_RECOVERY: f32 = 0.3;
+// Pixels per second
+pub const SKELETON_PATROL_SPEED: f32 = 40.0;
+pub const SKELETON_CHASE_SPEED: f32 = 80.0;
+// How far away the player can be noticed, and how far they are followed once noticed
+pub const SKELETON_SIGHT: f32 = 160.0;
+pub const SKELETON_PURSUIT: f32 = 240.0;
+// Seconds the player can be out of sight before the skeleton goes back to patrolling
+pub const SKELETON_MEMORY: f32 = 2.0;
+// Distance between the middles of the skeleton and the player close enough to swing at
+pub const SKELETON_ATTACK_RANGE: f32 = 48.0;
+// Seconds between the end of one attack and the start of the next
+pub const SKELETON_ATTACK_COOLDOWN: f32 = 0.8;
+// Deceleration while knocked back, pixels per second per second
+pub const SKELETON_FRICTION: f32 = 400.0;
+
+// Seconds the boss takes to fade away once defeated
+pub const BOSS_FADE_TIME: f32 = 2.0;
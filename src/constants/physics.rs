@@ -0,0 +1,8 @@
+// Distances are in world pixels, times in seconds
+pub const GRAVITY: f32 = 1800.0;
+pub const MAX_FALL_SPEED: f32 = 900.0;
+
+// Gap kept between a body and the surface it rests against
+pub const CONTACT_EPSILON: f32 = 0.01;
+// How far a grounded body is pulled down to stay on a slope going downhill
+pub const SLOPE_SNAP: f32 = 8.0;
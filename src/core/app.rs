@@ -1,76 +1,152 @@
-use crate::model::world::World;
+use crate::audio::audio::Audio;
+use crate::constants::gameplay;
+use crate::core::controls_menu::ControlsMenu;
+use crate::core::main_menu::MainMenu;
+use crate::core::save::SaveSlots;
+use crate::core::scene::{Context, SceneStack};
+use crate::core::settings::Settings;
+use crate::core::timestep::FixedTimestep;
+use crate::model::game_object::{Camera, OrthoCamera};
+use crate::platform::config;
+use crate::platform::game_window::GameWindow;
+use crate::platform::gamepad::GlfwGamepads;
+use crate::platform::input::{Input, InputMap};
 use crate::renderer::renderer::State;
-use crate::{model::game_object::Object, platform::game_window::GameWindow};
 use glfw::{Action, Key};
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub async fn run() {
     let mut game_window = GameWindow::new("Rusty Knight");
     let mut graphics_state = State::new(&mut game_window.window).await;
 
-    graphics_state.window.set_framebuffer_size_polling(true);
-    graphics_state.window.set_key_polling(true);
-    graphics_state.window.set_mouse_button_polling(true);
-    graphics_state.window.set_pos_polling(true);
-    graphics_state
-        .window
-        .set_cursor_mode(glfw::CursorMode::Hidden);
+    game_window.window.set_framebuffer_size_polling(true);
+    game_window.window.set_key_polling(true);
+    game_window.window.set_mouse_button_polling(true);
+    game_window.window.set_pos_polling(true);
+    game_window.window.set_cursor_mode(glfw::CursorMode::Hidden);
 
     graphics_state.load_assets();
 
-    let mut world = World::new();
-    world.quads.push(Object {
-        position: glm::Vec3::new(0.5, 0.0, -1.5),
-        angle: 0.0,
-    });
-    world.tris.push(Object {
-        position: glm::Vec3::new(0.0, 0.0, -1.0),
-        angle: 0.0,
-    });
-    graphics_state.build_ubos_for_objects(2);
-    world.keys.insert(glfw::Key::W, false);
-    world.keys.insert(glfw::Key::A, false);
-    world.keys.insert(glfw::Key::S, false);
-    world.keys.insert(glfw::Key::D, false);
-
-    while !graphics_state.window.should_close() {
+    let mut scenes = SceneStack::new(Box::new(MainMenu::new(&mut graphics_state)));
+    // Rendered with when no scene has a world, as in the menus
+    let no_camera = Camera::new();
+    let no_camera_2d = OrthoCamera::new();
+
+    let bindings_path = config::config_path("bindings.cfg");
+    let mut input = Input::new(InputMap::load(&bindings_path));
+    input.set_gamepad_source(Box::new(GlfwGamepads::new(&game_window.glfw)));
+    let mut controls_menu = ControlsMenu::new(&mut graphics_state);
+    let mut settings = Settings::default();
+    let mut audio = Audio::open();
+    let mut saves = SaveSlots::new(config::config_path("saves"));
+
+    let mut timestep =
+        FixedTimestep::new(gameplay::TICKS_PER_SECOND, gameplay::MAX_TICKS_PER_FRAME);
+
+    while !game_window.window.should_close() {
         game_window.glfw.poll_events();
-        world.update(16.67, graphics_state.window);
 
         for (_, event) in glfw::flush_messages(&game_window.events) {
+            if controls_menu.handle_event(&event, &mut input.map) {
+                continue;
+            }
+            input.handle_event(&event);
             match event {
-                glfw::WindowEvent::Key(Key::Escape, _, Action::Press, _) => {
-                    graphics_state.window.set_should_close(true);
+                glfw::WindowEvent::Key(Key::F1, _, Action::Press, _) => {
+                    controls_menu.toggle();
                 }
 
-                // Movement
-                glfw::WindowEvent::Key(key, _, Action::Press, _) => {
-                    world.set_key(key, true);
-                }
-                glfw::WindowEvent::Key(key, _, Action::Release, _) => {
-                    world.keys.insert(key, false);
+                // Screenshot
+                glfw::WindowEvent::Key(Key::F12, _, Action::Press, _) => {
+                    graphics_state.request_capture();
                 }
 
                 // Window was moved
                 glfw::WindowEvent::Pos(..) => {
-                    graphics_state.update_surface();
+                    graphics_state.update_surface(&mut game_window.window);
                     graphics_state.resize(graphics_state.size);
                 }
                 // Window was resized
                 glfw::WindowEvent::FramebufferSize(width, height) => {
-                    graphics_state.update_surface();
+                    graphics_state.update_surface(&mut game_window.window);
                     graphics_state.resize((width, height));
                 }
                 _ => {} //e => println!("Action: {e:?}"),
             }
         }
 
-        match graphics_state.render(&world.quads, &world.tris, &world.camera) {
+        if controls_menu.take_changes()
+            && let Err(e) = input.map.save(&bindings_path)
+        {
+            eprintln!("Cannot save {}: {e}", bindings_path.display());
+        }
+
+        input.poll_cursor(&mut game_window.window);
+        let mut ticks = timestep.begin_frame();
+        // The game stands still while the menu is open
+        if controls_menu.open {
+            input.flush();
+            ticks = 0;
+        }
+        for _ in 0..ticks {
+            let actions = input.tick();
+            settings.apply(&mut audio);
+            let mut context = Context {
+                state: &mut graphics_state,
+                controls_menu: &mut controls_menu,
+                settings: &mut settings,
+                audio: &mut audio,
+                saves: &mut saves,
+            };
+            scenes.update(timestep.step_milliseconds(), actions, &mut context);
+            audio.update(timestep.step_milliseconds());
+        }
+        if scenes.is_empty() {
+            break;
+        }
+
+        scenes.draw(&mut graphics_state, timestep.alpha());
+        controls_menu.draw(&input.map, &mut graphics_state);
+
+        let result = match scenes.world() {
+            Some(world) => graphics_state.render(
+                &world.quads,
+                &world.tris,
+                &world.camera,
+                &world.camera_2d,
+                timestep.alpha(),
+            ),
+            None => graphics_state.render(&[], &[], &no_camera, &no_camera_2d, timestep.alpha()),
+        };
+        match result {
             Ok(_) => {}
             Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
-                graphics_state.update_surface();
+                graphics_state.update_surface(&mut game_window.window);
                 graphics_state.resize(graphics_state.size);
             }
             Err(e) => eprintln!("{e:?}"),
         }
+
+        if let Some(frame) = graphics_state.take_capture() {
+            save_screenshot(&frame);
+        }
+    }
+}
+
+fn save_screenshot(frame: &image::RgbaImage) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis();
+
+    if let Err(e) = fs::create_dir_all("screenshots") {
+        eprintln!("Cannot make the screenshots folder: {e}");
+        return;
+    }
+    let filename = format!("screenshots/screenshot_{timestamp}.png");
+    match frame.save(&filename) {
+        Ok(_) => eprintln!("Saved {filename}"),
+        Err(e) => eprintln!("Cannot save {filename}: {e}"),
     }
 }
@@ -0,0 +1,277 @@
+use crate::model::geometry::Rect;
+use crate::platform::input::{Action, Binding, InputMap};
+use crate::renderer::renderer::State;
+use crate::renderer::sprite_batch::Sprite;
+use glfw::{Key, WindowEvent};
+
+const LETTERS_SHEET: &str = "assets/gui/Keyboard_Letters_and_Symbols.png";
+const EXTRAS_SHEET: &str = "assets/gui/Keyboard_Extras.png";
+const PANEL_SHEET: &str = "assets/gui/background.png";
+
+// Both keyboard sheets have the plain glyphs on top and the highlighted ones below
+const LETTER_SIZE: f32 = 16.0;
+const LETTER_COLUMNS: u32 = 8;
+const LETTER_ROWS: u32 = 7;
+const EXTRA_WIDTH: f32 = 32.0;
+const EXTRA_HEIGHT: f32 = 16.0;
+const EXTRA_COLUMNS: u32 = 4;
+const EXTRA_ROWS: u32 = 4;
+
+const LETTER_A: u32 = 16;
+const LETTER_F1: u32 = 4;
+const LETTER_QUESTION: u32 = 44;
+
+const SCALE: f32 = 2.0;
+const ROW_HEIGHT: f32 = 48.0;
+const FIRST_ROW: f32 = 176.0;
+const LABEL_X: f32 = 288.0;
+const BINDINGS_X: f32 = 768.0;
+const LAYER: i32 = 100;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum GlyphSheet {
+    Letters,
+    Extras,
+}
+
+// Position of a key in the keyboard sheets, None for keys without a glyph
+pub fn key_glyph(key: Key) -> Option<(GlyphSheet, u32)> {
+    let letters = |index| Some((GlyphSheet::Letters, index));
+    let extras = |index| Some((GlyphSheet::Extras, index));
+
+    let code = key as i32;
+    if (Key::A as i32..=Key::Z as i32).contains(&code) {
+        return letters(LETTER_A + (code - Key::A as i32) as u32);
+    }
+    if (Key::F1 as i32..=Key::F12 as i32).contains(&code) {
+        return letters(LETTER_F1 + (code - Key::F1 as i32) as u32);
+    }
+
+    match key {
+        Key::Up => letters(0),
+        Key::Down => letters(1),
+        Key::Left => letters(2),
+        Key::Right => letters(3),
+        Key::Period => letters(42),
+        Key::Comma => letters(43),
+        Key::Slash => letters(45),
+        Key::Backslash => letters(46),
+        Key::Semicolon => letters(47),
+        Key::Apostrophe => letters(48),
+        Key::LeftBracket => letters(49),
+        Key::RightBracket => letters(50),
+        Key::Equal => letters(51),
+        Key::Minus => letters(52),
+        Key::GraveAccent => letters(53),
+        Key::Tab => extras(0),
+        Key::Escape => extras(1),
+        Key::PrintScreen => extras(2),
+        Key::Backspace => extras(3),
+        Key::LeftShift | Key::RightShift => extras(4),
+        Key::PageUp => extras(5),
+        Key::PageDown => extras(6),
+        Key::Enter | Key::KpEnter => extras(7),
+        Key::LeftControl | Key::RightControl => extras(8),
+        Key::LeftAlt | Key::RightAlt => extras(9),
+        Key::Space => extras(10),
+        Key::Insert => extras(11),
+        Key::Delete => extras(12),
+        Key::End => extras(13),
+        Key::Home => extras(14),
+        Key::Pause => extras(15),
+        _ => None,
+    }
+}
+
+pub fn glyph_source(sheet: GlyphSheet, index: u32, highlighted: bool) -> Rect {
+    match sheet {
+        GlyphSheet::Letters => {
+            let row = index / LETTER_COLUMNS + if highlighted { LETTER_ROWS } else { 0 };
+            Rect::new(
+                (index % LETTER_COLUMNS) as f32 * LETTER_SIZE,
+                row as f32 * LETTER_SIZE,
+                LETTER_SIZE,
+                LETTER_SIZE,
+            )
+        }
+        GlyphSheet::Extras => {
+            let row = index / EXTRA_COLUMNS + if highlighted { EXTRA_ROWS } else { 0 };
+            Rect::new(
+                (index % EXTRA_COLUMNS) as f32 * EXTRA_WIDTH,
+                row as f32 * EXTRA_HEIGHT,
+                EXTRA_WIDTH,
+                EXTRA_HEIGHT,
+            )
+        }
+    }
+}
+
+// Lists every action with its bound keys. Enter picks an action to rebind,
+// the next key pressed becomes its binding and Escape backs out.
+pub struct ControlsMenu {
+    pub open: bool,
+    selected: usize,
+    waiting_for_key: bool,
+    changed: bool,
+    letters: usize,
+    extras: usize,
+    panel: usize,
+}
+
+impl ControlsMenu {
+    pub fn new(state: &mut State) -> Self {
+        ControlsMenu {
+            open: false,
+            selected: 0,
+            waiting_for_key: false,
+            changed: false,
+            letters: state.load_sprite_texture(LETTERS_SHEET),
+            extras: state.load_sprite_texture(EXTRAS_SHEET),
+            panel: state.load_sprite_texture(PANEL_SHEET),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.waiting_for_key = false;
+    }
+
+    // True when the bindings were edited since the last call
+    pub fn take_changes(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    // Returns whether the menu used the event, only presses are ever used
+    pub fn handle_event(&mut self, event: &WindowEvent, map: &mut InputMap) -> bool {
+        if !self.open {
+            return false;
+        }
+
+        match event {
+            WindowEvent::Key(key, _, glfw::Action::Press, _) if self.waiting_for_key => {
+                let binding = Binding::Key(*key);
+                if *key == Key::Escape {
+                    self.waiting_for_key = false;
+                } else if binding.name().is_some() {
+                    map.rebind(Action::ALL[self.selected], binding);
+                    self.changed = true;
+                    self.waiting_for_key = false;
+                }
+                true
+            }
+            WindowEvent::MouseButton(button, glfw::Action::Press, _) if self.waiting_for_key => {
+                let binding = Binding::Mouse(*button);
+                if binding.name().is_some() {
+                    map.rebind(Action::ALL[self.selected], binding);
+                    self.changed = true;
+                    self.waiting_for_key = false;
+                }
+                true
+            }
+            WindowEvent::Key(key, _, glfw::Action::Press | glfw::Action::Repeat, _) => {
+                let count = Action::ALL.len();
+                match key {
+                    Key::Up | Key::W => self.selected = (self.selected + count - 1) % count,
+                    Key::Down | Key::S => self.selected = (self.selected + 1) % count,
+                    Key::Enter | Key::Space => self.waiting_for_key = true,
+                    Key::Escape | Key::F1 => self.toggle(),
+                    _ => {}
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn draw(&self, map: &InputMap, state: &mut State) {
+        if !self.open {
+            return;
+        }
+
+        // Darkens the game behind the menu, stretching a plain patch of the panel sheet
+        let mut shade = Sprite::new(
+            self.panel,
+            Rect::new(84.0, 24.0, 4.0, 4.0),
+            glm::Vec2::new(0.0, 0.0),
+        );
+        shade.scale = glm::Vec2::new(320.0, 180.0);
+        shade.tint = glm::Vec4::new(0.0, 0.0, 0.0, 0.75);
+        shade.layer = LAYER;
+        state.draw_ui_sprite(shade);
+
+        self.draw_word("CONTROLS", glm::Vec2::new(LABEL_X, 80.0), 3.0, true, state);
+
+        for (i, action) in Action::ALL.iter().enumerate() {
+            let selected = i == self.selected;
+            let y = FIRST_ROW + i as f32 * ROW_HEIGHT;
+            self.draw_word(
+                action.label(),
+                glm::Vec2::new(LABEL_X, y),
+                SCALE,
+                selected,
+                state,
+            );
+
+            if selected && self.waiting_for_key {
+                let source = glyph_source(GlyphSheet::Letters, LETTER_QUESTION, true);
+                self.draw_glyph(
+                    GlyphSheet::Letters,
+                    source,
+                    glm::Vec2::new(BINDINGS_X, y),
+                    state,
+                );
+                continue;
+            }
+
+            let mut x = BINDINGS_X;
+            for binding in map.bindings_for(*action) {
+                // Mouse buttons have no glyphs
+                let Binding::Key(key) = binding else {
+                    continue;
+                };
+                let (sheet, index) =
+                    key_glyph(key).unwrap_or((GlyphSheet::Letters, LETTER_QUESTION));
+                let source = glyph_source(sheet, index, selected);
+                self.draw_glyph(sheet, source, glm::Vec2::new(x, y), state);
+                x += (source.w + 4.0) * SCALE;
+            }
+        }
+    }
+
+    // Spells a word in capitals out of the letter keys
+    fn draw_word(
+        &self,
+        word: &str,
+        position: glm::Vec2,
+        scale: f32,
+        highlighted: bool,
+        state: &mut State,
+    ) {
+        let mut x = position.x;
+        for c in word.chars() {
+            if c.is_ascii_uppercase() {
+                let index = LETTER_A + (c as u32 - 'A' as u32);
+                let mut sprite = Sprite::new(
+                    self.letters,
+                    glyph_source(GlyphSheet::Letters, index, highlighted),
+                    glm::Vec2::new(x, position.y),
+                );
+                sprite.scale = glm::Vec2::new(scale, scale);
+                sprite.layer = LAYER + 1;
+                state.draw_ui_sprite(sprite);
+            }
+            x += LETTER_SIZE * scale;
+        }
+    }
+
+    fn draw_glyph(&self, sheet: GlyphSheet, source: Rect, position: glm::Vec2, state: &mut State) {
+        let texture = match sheet {
+            GlyphSheet::Letters => self.letters,
+            GlyphSheet::Extras => self.extras,
+        };
+        let mut sprite = Sprite::new(texture, source, position);
+        sprite.scale = glm::Vec2::new(SCALE, SCALE);
+        sprite.layer = LAYER + 1;
+        state.draw_ui_sprite(sprite);
+    }
+}
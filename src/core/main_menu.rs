@@ -0,0 +1,151 @@
+use crate::constants::graphics;
+use crate::core::playing::Playing;
+use crate::core::save::{SLOT_COUNT, SaveGame, SaveSlots};
+use crate::core::scene::{Context, Scene, Transition};
+use crate::model::geometry::Rect;
+use crate::platform::input::ActionState;
+use crate::renderer::renderer::State;
+use crate::renderer::text::{Align, Font, TextStyle};
+use crate::ui::hud;
+use crate::ui::widgets::{self, Label, Ui};
+
+const LAYER: i32 = 1;
+const BUTTON_GAP: f32 = 16.0;
+const MUSIC: &str = "assets/music/dungeon.wav";
+const MUSIC_VOLUME: f32 = 0.6;
+// The slot row is as wide as two buttons and half as tall as one
+const SLOT_HEIGHT: f32 = 0.6;
+const SLOT_PANEL_SCALE: f32 = 2.0;
+const TEXT_SCALE: f32 = 3.0;
+// Play comes after the slot row, and has the focus first
+const PLAY: usize = 1;
+
+// Where the game starts: pick a save slot with left and right, then play it
+// from where it was left, change the controls or quit. Slots that can't be
+// read can't be played either.
+pub struct MainMenu {
+    pub ui: Ui,
+    font: Font,
+    // What the slot row says, and for which slot
+    slot_text: String,
+    shown_slot: Option<usize>,
+}
+
+impl MainMenu {
+    pub fn new(state: &mut State) -> Self {
+        let mut ui = Ui::new(state);
+        ui.layer = LAYER;
+        ui.set_focus(PLAY);
+        let mut font = Font::load(hud::FONT);
+        font.load_textures(state);
+        MainMenu {
+            ui,
+            font,
+            slot_text: String::new(),
+            shown_slot: None,
+        }
+    }
+
+    fn slot_rect() -> Rect {
+        let size = widgets::label_size(1.0);
+        let width = 2.0 * size.x;
+        Rect::new(
+            widgets::centred_x(width),
+            Self::top(),
+            width,
+            SLOT_HEIGHT * size.y,
+        )
+    }
+
+    fn top() -> f32 {
+        let size = widgets::label_size(1.0);
+        let height = (3.0 + SLOT_HEIGHT) * size.y + 3.0 * BUTTON_GAP;
+        0.5 * (graphics::MAP_HEIGHT_PIXELS as f32 - height)
+    }
+}
+
+// "Slot 2  Level 3", or whether it is empty or can't be read
+pub fn slot_summary(saves: &SaveSlots, slot: usize) -> String {
+    let state = match saves.load(slot) {
+        Ok(Some(game)) => format!("Level {}", game.level),
+        Ok(None) => "Empty".to_string(),
+        Err(e) => {
+            eprintln!("Cannot read {}: {e}", saves.path(slot).display());
+            "Damaged".to_string()
+        }
+    };
+    format!("< Slot {}  {state} >", slot + 1)
+}
+
+impl Scene for MainMenu {
+    fn update(&mut self, _dt: f32, actions: &ActionState, context: &mut Context) -> Transition {
+        let size = widgets::label_size(self.ui.scale);
+        let top = Self::top() + SLOT_HEIGHT * size.y + BUTTON_GAP;
+        let position = |row: f32| {
+            glm::Vec2::new(
+                widgets::centred_x(size.x),
+                top + row * (size.y + BUTTON_GAP),
+            )
+        };
+
+        let music = context.audio.load(MUSIC);
+        context.audio.play_music(music, MUSIC_VOLUME);
+        self.ui.begin(actions);
+        self.ui.play_sounds(context.audio);
+        self.ui.shade(0.6);
+        let saves = &mut context.saves;
+        self.ui.selector(
+            &mut saves.current,
+            SLOT_COUNT,
+            Self::slot_rect(),
+            SLOT_PANEL_SCALE,
+        );
+        if self.shown_slot != Some(saves.current) {
+            self.slot_text = slot_summary(saves, saves.current);
+            self.shown_slot = Some(saves.current);
+        }
+        if self.ui.button(Label::Play, position(0.0)) {
+            // Empty slots start a new game. Damaged ones are left alone rather
+            // than written over, they may only need a newer version of the game.
+            let playing = match saves.load(saves.current) {
+                Ok(Some(game)) => {
+                    *context.settings = game.settings;
+                    Playing::resume(context.state, &game)
+                }
+                Ok(None) => {
+                    saves.save(&SaveGame {
+                        settings: *context.settings,
+                        ..Default::default()
+                    });
+                    Playing::new(context.state, 1)
+                }
+                Err(e) => {
+                    eprintln!("Cannot play {}: {e}", saves.path(saves.current).display());
+                    return Transition::Stay;
+                }
+            };
+            return Transition::Replace(Box::new(playing));
+        }
+        if self.ui.button(Label::Options, position(1.0)) {
+            context.controls_menu.toggle();
+        }
+        if self.ui.button(Label::Quit, position(2.0)) {
+            return Transition::Quit;
+        }
+        Transition::Stay
+    }
+
+    fn draw(&self, state: &mut State, _alpha: f32) {
+        self.ui.draw(state);
+        let rect = Self::slot_rect();
+        let style = TextStyle {
+            scale: TEXT_SCALE,
+            align: Align::Centre,
+            layer: LAYER + 2,
+            ..Default::default()
+        };
+        let height = self.font.measure(&self.slot_text, &style).y;
+        let middle = glm::Vec2::new(rect.x + 0.5 * rect.w, rect.y + 0.5 * (rect.h - height));
+        self.font.draw(state, &self.slot_text, middle, &style);
+    }
+}
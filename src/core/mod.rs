@@ -1 +1,10 @@
 pub mod app;
+pub mod controls_menu;
+pub mod main_menu;
+pub mod outcome;
+pub mod pause_menu;
+pub mod playing;
+pub mod save;
+pub mod scene;
+pub mod settings;
+pub mod timestep;
@@ -0,0 +1,133 @@
+use crate::core::main_menu::MainMenu;
+use crate::core::playing::Playing;
+use crate::core::save::SaveGame;
+use crate::core::scene::{Context, Scene, Transition};
+use crate::model::geometry::Rect;
+use crate::model::level::Level;
+use crate::platform::input::ActionState;
+use crate::renderer::renderer::State;
+use crate::renderer::sprite_batch::Sprite;
+use crate::ui::widgets::{self, Icon, Ui};
+
+const VICTORY_SHEET: &str = "assets/gui/victory.png";
+const DEFEAT_SHEET: &str = "assets/gui/defeat.png";
+
+const LAYER: i32 = 50;
+const BANNER_TOP: f32 = 240.0;
+const BANNER_SCALE: f32 = 4.0;
+const BUTTON_GAP: f32 = 24.0;
+// Seconds the banner fades in for, the buttons show up after that
+const FADE_TIME: f32 = 1.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutcomeKind {
+    Victory,
+    Defeat,
+}
+
+// Shown over the level once it is won or lost, with buttons to go on to the
+// next level or retry this one, or to go back to the main menu
+pub struct Outcome {
+    pub kind: OutcomeKind,
+    pub level: u32,
+    pub ui: Ui,
+    // Seconds since it was shown
+    time: f32,
+    banner: usize,
+}
+
+impl Outcome {
+    pub fn victory(state: &mut State, level: u32) -> Self {
+        Self::new(state, OutcomeKind::Victory, level, VICTORY_SHEET)
+    }
+
+    pub fn defeat(state: &mut State, level: u32) -> Self {
+        Self::new(state, OutcomeKind::Defeat, level, DEFEAT_SHEET)
+    }
+
+    fn new(state: &mut State, kind: OutcomeKind, level: u32, sheet: &str) -> Self {
+        let mut ui = Ui::new(state);
+        ui.layer = LAYER;
+        Outcome {
+            kind,
+            level,
+            ui,
+            time: 0.0,
+            banner: state.load_sprite_texture(sheet),
+        }
+    }
+}
+
+impl Scene for Outcome {
+    fn update(&mut self, dt: f32, actions: &ActionState, context: &mut Context) -> Transition {
+        self.time += dt / 1000.0;
+        let faded_in = glm::min(self.time / FADE_TIME, 1.0);
+
+        let (width, height) = context.state.sprite_texture_size(self.banner);
+        let mut banner = Sprite::new(
+            self.banner,
+            Rect::new(0.0, 0.0, width as f32, height as f32),
+            glm::Vec2::new(widgets::centred_x(width as f32 * BANNER_SCALE), BANNER_TOP),
+        );
+        banner.scale = glm::Vec2::new(BANNER_SCALE, BANNER_SCALE);
+        banner.tint.w = faded_in;
+
+        self.ui.begin(actions);
+        self.ui.play_sounds(context.audio);
+        self.ui.shade(0.6 * faded_in);
+        self.ui.image(banner);
+        if self.time < FADE_TIME {
+            return Transition::Stay;
+        }
+
+        let (icon, next) = match self.kind {
+            OutcomeKind::Victory => (Icon::Play, self.level + 1),
+            OutcomeKind::Defeat => (Icon::Restart, self.level),
+        };
+        let size = widgets::icon_size(1.0);
+        let top = BANNER_TOP + height as f32 * BANNER_SCALE + BUTTON_GAP;
+        let left = widgets::centred_x(2.0 * size.x + BUTTON_GAP);
+        if self.ui.icon_button(icon, glm::Vec2::new(left, top)) {
+            // Past the last level there is nowhere to go on to
+            if !Level::exists(next) {
+                return Transition::Restart(Box::new(MainMenu::new(context.state)));
+            }
+            let saved = context.saves.load(context.saves.current).ok().flatten();
+            let game = match self.kind {
+                // The next level starts at full health from its spawn point
+                OutcomeKind::Victory => {
+                    let game = SaveGame {
+                        level: next,
+                        abilities: saved.map(|game| game.abilities).unwrap_or_default(),
+                        settings: *context.settings,
+                        ..Default::default()
+                    };
+                    context.saves.save(&game);
+                    Some(game)
+                }
+                // Back to the last checkpoint saved in this level
+                OutcomeKind::Defeat => saved.filter(|game| game.level == next),
+            };
+            let playing = match game {
+                Some(game) => Playing::resume(context.state, &game),
+                None => Playing::new(context.state, next),
+            };
+            return Transition::Restart(Box::new(playing));
+        }
+        if self
+            .ui
+            .icon_button(Icon::Home, glm::Vec2::new(left + size.x + BUTTON_GAP, top))
+        {
+            return Transition::Restart(Box::new(MainMenu::new(context.state)));
+        }
+        Transition::Stay
+    }
+
+    fn draw(&self, state: &mut State, _alpha: f32) {
+        self.ui.draw(state);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
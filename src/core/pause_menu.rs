@@ -0,0 +1,117 @@
+use crate::core::main_menu::MainMenu;
+use crate::core::scene::{Context, Scene, Transition};
+use crate::model::geometry::Rect;
+use crate::platform::input::{Action, ActionState};
+use crate::renderer::renderer::State;
+use crate::renderer::sprite_batch::Sprite;
+use crate::ui::widgets::{self, Icon, Ui};
+
+const PAUSE_SHEET: &str = "assets/gui/pause.png";
+
+const LAYER: i32 = 50;
+const PANEL_TOP: f32 = 40.0;
+const PANEL_SCALE: f32 = 3.0;
+// Where things go on the pause sheet, in its own pixels
+const MUSIC_ROW: f32 = 26.0;
+const SFX_ROW: f32 = 56.0;
+const TOGGLE_X: f32 = 84.0;
+const SLIDER_ROW: f32 = 112.0;
+// The sound buttons are drawn smaller to fit next to the labels
+const TOGGLE_SCALE: f32 = 0.5;
+const BUTTON_GAP: f32 = 24.0;
+// Resume comes after the two toggles and the slider, and has the focus first
+const RESUME: usize = 3;
+
+// Freezes the level underneath with the sound options on show, until it is
+// resumed or left for the main menu
+pub struct PauseMenu {
+    pub ui: Ui,
+    pause_texture: usize,
+}
+
+impl PauseMenu {
+    pub fn new(state: &mut State) -> Self {
+        let mut ui = Ui::new(state);
+        ui.layer = LAYER;
+        ui.set_focus(RESUME);
+        PauseMenu {
+            ui,
+            pause_texture: state.load_sprite_texture(PAUSE_SHEET),
+        }
+    }
+}
+
+impl Scene for PauseMenu {
+    fn update(&mut self, _dt: f32, actions: &ActionState, context: &mut Context) -> Transition {
+        if actions.pressed(Action::Pause) {
+            context.saves.save_settings(context.settings);
+            return Transition::Pop;
+        }
+
+        let (width, height) = context.state.sprite_texture_size(self.pause_texture);
+        let panel = Rect::new(
+            widgets::centred_x(width as f32 * PANEL_SCALE),
+            PANEL_TOP,
+            width as f32 * PANEL_SCALE,
+            height as f32 * PANEL_SCALE,
+        );
+        let on_panel =
+            |x: f32, y: f32| glm::Vec2::new(panel.x, panel.y) + glm::Vec2::new(x, y) * PANEL_SCALE;
+
+        self.ui.begin(actions);
+        self.ui.play_sounds(context.audio);
+        self.ui.shade(0.5);
+        let mut sprite = Sprite::new(
+            self.pause_texture,
+            Rect::new(0.0, 0.0, width as f32, height as f32),
+            glm::Vec2::new(panel.x, panel.y),
+        );
+        sprite.scale = glm::Vec2::new(PANEL_SCALE, PANEL_SCALE);
+        self.ui.image(sprite);
+
+        let settings = &mut context.settings;
+        self.ui.scale = TOGGLE_SCALE;
+        let toggle = widgets::icon_size(TOGGLE_SCALE);
+        self.ui.toggle(
+            &mut settings.music,
+            on_panel(TOGGLE_X, MUSIC_ROW) - glm::Vec2::new(0.0, 0.5 * toggle.y),
+        );
+        self.ui.toggle(
+            &mut settings.sound,
+            on_panel(TOGGLE_X, SFX_ROW) - glm::Vec2::new(0.0, 0.5 * toggle.y),
+        );
+        self.ui.scale = 1.0;
+        let slider = widgets::slider_size(1.0);
+        self.ui.slider(
+            &mut settings.volume,
+            glm::Vec2::new(
+                widgets::centred_x(slider.x),
+                on_panel(0.0, SLIDER_ROW).y - 0.5 * slider.y,
+            ),
+        );
+
+        let icon = widgets::icon_size(1.0);
+        let top = panel.y + panel.h + BUTTON_GAP;
+        let left = widgets::centred_x(2.0 * icon.x + BUTTON_GAP);
+        if self.ui.icon_button(Icon::Play, glm::Vec2::new(left, top)) {
+            context.saves.save_settings(context.settings);
+            return Transition::Pop;
+        }
+        if self
+            .ui
+            .icon_button(Icon::Home, glm::Vec2::new(left + icon.x + BUTTON_GAP, top))
+        {
+            context.saves.save_settings(context.settings);
+            return Transition::Restart(Box::new(MainMenu::new(context.state)));
+        }
+        Transition::Stay
+    }
+
+    fn draw(&self, state: &mut State, _alpha: f32) {
+        self.ui.draw(state);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,157 @@
+use crate::audio::cues;
+use crate::core::outcome::Outcome;
+use crate::core::pause_menu::PauseMenu;
+use crate::core::save::SaveGame;
+use crate::core::scene::{Context, Scene, Transition};
+use crate::model::animation::{AnimationSet, Pose};
+use crate::model::game_object::Object;
+use crate::model::geometry::Rect;
+use crate::model::level::Level;
+use crate::model::world::World;
+use crate::platform::input::{Action, ActionState};
+use crate::renderer::renderer::State;
+use crate::renderer::sprite_batch::Sprite;
+use crate::ui::hud::Hud;
+
+// Closed arena gates are drawn with a plain patch of the GUI panel sheet
+const GATE_SHEET: &str = "assets/gui/background.png";
+const MUSIC: &str = "assets/music/dungeon.wav";
+const MUSIC_VOLUME: f32 = 0.6;
+
+// A level being played, until the player pauses, wins or dies
+pub struct Playing {
+    pub world: World,
+    pub hud: Hud,
+    background: usize,
+    gate_texture: usize,
+}
+
+impl Playing {
+    pub fn new(state: &mut State, level: u32) -> Self {
+        Self::with_world(state, World::new(Level::load(level)))
+    }
+
+    // Carries on from a save's checkpoint
+    pub fn resume(state: &mut State, game: &SaveGame) -> Self {
+        let mut world = World::resume(Level::load(game.level), game.checkpoint);
+        let health = &mut world.player.fighter.health;
+        health.current = game.health.clamp(1, health.max);
+        world.player.abilities = game.abilities.clone();
+        Self::with_world(state, world)
+    }
+
+    fn with_world(state: &mut State, mut world: World) -> Self {
+        world.player.animations.load_textures(state);
+        world.skeleton_animations.load_textures(state);
+        world.boss_animations.load_textures(state);
+
+        world.quads.push(Object {
+            position: glm::Vec3::new(0.5, 0.0, -1.5),
+            angle: 0.0,
+        });
+        world.tris.push(Object {
+            position: glm::Vec3::new(0.0, 0.0, -1.0),
+            angle: 0.0,
+        });
+
+        let mut hud = Hud::new(&world);
+        hud.load_textures(state);
+        // The design image is the background, loaded by name so replaying
+        // the level uses the same texture
+        let background = state.load_sprite_texture(&Level::design_path(world.level.number));
+
+        Playing {
+            world,
+            hud,
+            background,
+            gate_texture: state.load_sprite_texture(GATE_SHEET),
+        }
+    }
+}
+
+impl Scene for Playing {
+    fn update(&mut self, dt: f32, actions: &ActionState, context: &mut Context) -> Transition {
+        // Carries on from the menu when it is the same tune
+        let music = context.audio.load(MUSIC);
+        context.audio.play_music(music, MUSIC_VOLUME);
+
+        if actions.pressed(Action::Pause) {
+            return Transition::Push(Box::new(PauseMenu::new(context.state)));
+        }
+
+        self.world.update(dt, actions);
+        cues::play_world(context.audio, &self.world);
+        if self.world.checkpoint_reached {
+            context
+                .saves
+                .save(&SaveGame::capture(&self.world, context.settings));
+        }
+        self.hud.update(dt / 1000.0, &self.world);
+
+        let level = self.world.level.number;
+        if self.world.is_lost() {
+            Transition::Push(Box::new(Outcome::defeat(context.state, level)))
+        } else if self.world.is_won() {
+            Transition::Push(Box::new(Outcome::victory(context.state, level)))
+        } else {
+            Transition::Stay
+        }
+    }
+
+    fn draw(&self, state: &mut State, alpha: f32) {
+        let world = &self.world;
+
+        let mut background = Sprite::new(
+            self.background,
+            Rect::new(
+                0.0,
+                0.0,
+                world.level.background.width() as f32,
+                world.level.background.height() as f32,
+            ),
+            glm::Vec2::new(0.0, 0.0),
+        );
+        background.layer = -1;
+        state.draw_sprite(background);
+
+        for skeleton in &world.skeletons {
+            let pose = skeleton.pose(&world.skeleton_animations, alpha);
+            state.draw_sprite(sprite(&world.skeleton_animations, pose));
+        }
+        if let Some(boss) = &world.boss {
+            let pose = boss.pose(&world.boss_animations, alpha);
+            state.draw_sprite(sprite(&world.boss_animations, pose));
+            if boss.arena.is_locked() {
+                for rect in boss.arena.gate_rects() {
+                    let mut gate = Sprite::new(
+                        self.gate_texture,
+                        Rect::new(84.0, 24.0, 4.0, 4.0),
+                        glm::Vec2::new(rect.x, rect.y),
+                    );
+                    gate.scale = glm::Vec2::new(rect.w / 4.0, rect.h / 4.0);
+                    gate.tint = glm::Vec4::new(0.3, 0.3, 0.35, 1.0);
+                    state.draw_sprite(gate);
+                }
+            }
+        }
+        let pose = world.player.pose(alpha);
+        state.draw_sprite(sprite(&world.player.animations, pose));
+        for projectile in &world.projectiles {
+            let pose = projectile.pose(&world.boss_animations, alpha);
+            state.draw_sprite(sprite(&world.boss_animations, pose));
+        }
+        self.hud.draw(state);
+    }
+
+    fn world(&self) -> Option<&World> {
+        Some(&self.world)
+    }
+}
+
+// A character's pose as a sprite, needs the set's textures loaded
+fn sprite(animations: &AnimationSet, pose: Pose) -> Sprite {
+    let mut sprite = Sprite::new(animations.textures[pose.image], pose.source, pose.position);
+    sprite.flip_x = pose.flip_x;
+    sprite.tint.w = pose.opacity;
+    sprite
+}
@@ -0,0 +1,230 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use crate::constants::{gameplay, graphics};
+use crate::core::settings::Settings;
+use crate::model::level::Level;
+use crate::model::world::World;
+use crate::utility::string::split;
+
+// Saves are text, one field per line after the version:
+//
+//   version <number>                  always the first line
+//   level <number>
+//   checkpoint <x> <y>                where the knight comes back, the middle of
+//                                     its feet in world pixels; the level's
+//                                     spawn point when there is none
+//   health <points>
+//   ability <name>                    one line per ability unlocked
+//   music on|off
+//   sound on|off
+//   volume <0 to 1>
+//
+// Version 1 gave the checkpoint as the tile it is in. Older files are brought
+// up to date as they are read, and written back in the current version.
+pub const VERSION: u32 = 2;
+pub const SLOT_COUNT: usize = 3;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SaveGame {
+    pub level: u32,
+    pub checkpoint: Option<glm::Vec2>,
+    pub health: i32,
+    pub abilities: Vec<String>,
+    pub settings: Settings,
+}
+
+impl Default for SaveGame {
+    // The start of a new game
+    fn default() -> Self {
+        SaveGame {
+            level: 1,
+            checkpoint: None,
+            health: gameplay::KNIGHT_HEALTH,
+            abilities: Vec::new(),
+            settings: Settings::default(),
+        }
+    }
+}
+
+impl SaveGame {
+    // Where the world has got to, to carry on from its checkpoint
+    pub fn capture(world: &World, settings: &Settings) -> Self {
+        SaveGame {
+            level: world.level.number,
+            checkpoint: world.checkpoint,
+            health: world.player.fighter.health.current,
+            abilities: world.player.abilities.clone(),
+            settings: *settings,
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let on = |on: bool| if on { "on" } else { "off" };
+        let mut text = String::from("# Rusty Knight save\n");
+        text.push_str(&format!("version {VERSION}\n"));
+        text.push_str(&format!("level {}\n", self.level));
+        if let Some(checkpoint) = self.checkpoint {
+            text.push_str(&format!("checkpoint {} {}\n", checkpoint.x, checkpoint.y));
+        }
+        text.push_str(&format!("health {}\n", self.health));
+        for ability in &self.abilities {
+            text.push_str(&format!("ability {ability}\n"));
+        }
+        text.push_str(&format!("music {}\n", on(self.settings.music)));
+        text.push_str(&format!("sound {}\n", on(self.settings.sound)));
+        text.push_str(&format!("volume {}\n", self.settings.volume));
+        text
+    }
+
+    // Fails without a version line or for versions newer than this game
+    // knows, anything else it cannot read keeps its new game value
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut lines: Vec<(usize, Vec<String>)> = text
+            .lines()
+            .enumerate()
+            .map(|(number, line)| (number + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(number, line)| {
+                let words = split(line, " ")
+                    .into_iter()
+                    .filter(|word| !word.is_empty())
+                    .collect();
+                (number, words)
+            })
+            .collect();
+
+        let version = match lines.first() {
+            Some((_, words)) if words.len() == 2 && words[0] == "version" => words[1]
+                .parse::<u32>()
+                .map_err(|_| format!("bad version {}", words[1]))?,
+            _ => return Err("no version line".to_string()),
+        };
+        if version == 0 || version > VERSION {
+            return Err(format!("version {version} is not one this game can read"));
+        }
+        lines.remove(0);
+        migrate(version, &mut lines);
+
+        let mut game = SaveGame::default();
+        for (number, words) in lines {
+            if game.parse_line(&words).is_none() {
+                eprintln!("Ignoring save line {number}: {}", words.join(" "));
+            }
+        }
+        Ok(game)
+    }
+
+    fn parse_line(&mut self, words: &[String]) -> Option<()> {
+        // NaN and infinity parse as numbers too, but nothing saved is ever one
+        let number = |word: &String| word.parse::<f32>().ok().filter(|n| n.is_finite());
+        let on = |word: &String| match word.as_str() {
+            "on" => Some(true),
+            "off" => Some(false),
+            _ => None,
+        };
+
+        match words {
+            [command, level] if command == "level" => {
+                self.level = level.parse().ok().filter(|level| *level > 0)?;
+            }
+            [command, x, y] if command == "checkpoint" => {
+                self.checkpoint = Some(glm::Vec2::new(number(x)?, number(y)?));
+            }
+            [command, health] if command == "health" => {
+                self.health = health.parse().ok()?;
+            }
+            [command, name] if command == "ability" => {
+                if !self.abilities.contains(name) {
+                    self.abilities.push(name.clone());
+                }
+            }
+            [command, value] if command == "music" => self.settings.music = on(value)?,
+            [command, value] if command == "sound" => self.settings.sound = on(value)?,
+            [command, volume] if command == "volume" => {
+                self.settings.volume = number(volume)?.clamp(0.0, 1.0);
+            }
+            _ => return None,
+        }
+
+        Some(())
+    }
+}
+
+// Rewrites the lines of an older version into the current one, a version
+// at a time
+fn migrate(version: u32, lines: &mut [(usize, Vec<String>)]) {
+    if version < 2 {
+        // Tiles to the middle of the tile's bottom edge, where feet go
+        let tile = graphics::TILE_PIXELS as f32;
+        for (_, words) in lines.iter_mut() {
+            if let [command, x, y] = words.as_mut_slice()
+                && command == "checkpoint"
+                && let (Ok(tile_x), Ok(tile_y)) = (x.parse::<i32>(), y.parse::<i32>())
+            {
+                *x = ((tile_x as f32 + 0.5) * tile).to_string();
+                *y = ((tile_y + 1) as f32 * tile).to_string();
+            }
+        }
+    }
+}
+
+// The save files, one per slot, and which slot is being played
+pub struct SaveSlots {
+    pub dir: PathBuf,
+    // From 0
+    pub current: usize,
+}
+
+impl SaveSlots {
+    pub fn new(dir: PathBuf) -> Self {
+        SaveSlots { dir, current: 0 }
+    }
+
+    pub fn path(&self, slot: usize) -> PathBuf {
+        self.dir.join(format!("slot_{}.sav", slot + 1))
+    }
+
+    // None for an empty slot, an error when the file is there but can't be
+    // read or is in a level this game doesn't have
+    pub fn load(&self, slot: usize) -> Result<Option<SaveGame>, String> {
+        let game = match fs::read_to_string(self.path(slot)) {
+            Ok(text) => SaveGame::from_text(&text)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.to_string()),
+        };
+        if !Level::exists(game.level) {
+            return Err(format!("there is no level {}", game.level));
+        }
+        Ok(Some(game))
+    }
+
+    // Writes the whole save beside the slot first and then renames it over
+    // the slot, so a crash part way through leaves the old save in one piece
+    pub fn write(&self, slot: usize, game: &SaveGame) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(slot);
+        let partial = path.with_extension("sav.tmp");
+        let mut file = File::create(&partial)?;
+        file.write_all(game.to_text().as_bytes())?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&partial, &path)
+    }
+
+    // Into the slot being played, carrying on without it when it fails
+    pub fn save(&self, game: &SaveGame) {
+        if let Err(e) = self.write(self.current, game) {
+            eprintln!("Cannot save {}: {e}", self.path(self.current).display());
+        }
+    }
+
+    // Keeps the settings in the slot being played, if it has a save
+    pub fn save_settings(&self, settings: &Settings) {
+        if let Ok(Some(mut game)) = self.load(self.current) {
+            game.settings = *settings;
+            self.save(&game);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use crate::audio::audio::Audio;
+use crate::core::controls_menu::ControlsMenu;
+use crate::core::save::SaveSlots;
+use crate::core::settings::Settings;
+use crate::model::world::World;
+use crate::platform::input::ActionState;
+use crate::renderer::renderer::State;
+
+// What the scene on top of the stack wants to happen after its tick
+pub enum Transition {
+    Stay,
+    // Covers the current scene, which stops updating until it is on top again
+    Push(Box<dyn Scene>),
+    // Back to the scene underneath
+    Pop,
+    // Swaps the top scene for another
+    Replace(Box<dyn Scene>),
+    // Drops every scene and starts over from this one
+    Restart(Box<dyn Scene>),
+    // Empties the stack, which ends the game
+    Quit,
+}
+
+// The parts of the app a scene may use while it updates
+pub struct Context<'a> {
+    pub state: &'a mut State,
+    pub controls_menu: &'a mut ControlsMenu,
+    pub settings: &'a mut Settings,
+    pub audio: &'a mut Audio,
+    pub saves: &'a mut SaveSlots,
+}
+
+pub trait Scene {
+    // One fixed tick, dt in milliseconds. Only the top scene is updated.
+    fn update(&mut self, dt: f32, actions: &ActionState, context: &mut Context) -> Transition;
+
+    // Queues the scene's sprites, alpha is how far between ticks to draw
+    fn draw(&self, state: &mut State, alpha: f32);
+
+    // Overlays are drawn on top of the scenes underneath instead of hiding them
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    // The world to render behind the sprites, if the scene has one
+    fn world(&self) -> Option<&World> {
+        None
+    }
+}
+
+#[derive(Default)]
+pub struct SceneStack {
+    scenes: Vec<Box<dyn Scene>>,
+}
+
+impl SceneStack {
+    pub fn new(first: Box<dyn Scene>) -> Self {
+        SceneStack {
+            scenes: vec![first],
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scenes.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.scenes.len()
+    }
+
+    pub fn top(&self) -> Option<&dyn Scene> {
+        self.scenes.last().map(|scene| scene.as_ref())
+    }
+
+    pub fn apply(&mut self, transition: Transition) {
+        match transition {
+            Transition::Stay => {}
+            Transition::Push(scene) => self.scenes.push(scene),
+            Transition::Pop => {
+                self.scenes.pop();
+            }
+            Transition::Replace(scene) => {
+                self.scenes.pop();
+                self.scenes.push(scene);
+            }
+            Transition::Restart(scene) => {
+                self.scenes.clear();
+                self.scenes.push(scene);
+            }
+            Transition::Quit => self.scenes.clear(),
+        }
+    }
+
+    pub fn update(&mut self, dt: f32, actions: &ActionState, context: &mut Context) {
+        if let Some(scene) = self.scenes.last_mut() {
+            let transition = scene.update(dt, actions, context);
+            self.apply(transition);
+        }
+    }
+
+    // Draws the top scene over every overlay beneath it, down to the first
+    // scene that hides the rest
+    pub fn draw(&self, state: &mut State, alpha: f32) {
+        for scene in &self.scenes[self.first_visible()..] {
+            scene.draw(state, alpha);
+        }
+    }
+
+    // The world of the topmost visible scene that has one
+    pub fn world(&self) -> Option<&World> {
+        self.scenes[self.first_visible()..]
+            .iter()
+            .rev()
+            .find_map(|scene| scene.world())
+    }
+
+    fn first_visible(&self) -> usize {
+        self.scenes
+            .iter()
+            .rposition(|scene| !scene.is_overlay())
+            .unwrap_or(0)
+    }
+}
@@ -0,0 +1,32 @@
+use crate::audio::audio::Audio;
+use crate::audio::mixer::Bus;
+
+// Sound options picked in the pause menu, kept for as long as the game runs
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settings {
+    pub music: bool,
+    pub sound: bool,
+    // From 0 to 1
+    pub volume: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            music: true,
+            sound: true,
+            volume: 1.0,
+        }
+    }
+}
+
+impl Settings {
+    // Sets the mixer's volumes to match
+    pub fn apply(&self, audio: &mut Audio) {
+        let on = |on: bool| if on { 1.0 } else { 0.0 };
+        audio.set_master_volume(self.volume);
+        audio.set_bus_volume(Bus::Music, on(self.music));
+        audio.set_bus_volume(Bus::Effects, on(self.sound));
+        audio.set_bus_volume(Bus::Ui, on(self.sound));
+    }
+}
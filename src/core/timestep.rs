@@ -0,0 +1,67 @@
+use std::time::{Duration, Instant};
+
+// Runs the simulation in fixed ticks however long frames take. Real time
+// goes into an accumulator and is paid out one tick at a time; what is left
+// over becomes the interpolation alpha for rendering between two ticks.
+pub struct FixedTimestep {
+    step: Duration,
+    max_steps: u32,
+    accumulator: Duration,
+    last_frame: Option<Instant>,
+}
+
+impl FixedTimestep {
+    pub fn new(ticks_per_second: u32, max_steps: u32) -> Self {
+        FixedTimestep {
+            step: Duration::from_secs(1) / ticks_per_second.max(1),
+            max_steps: max_steps.max(1),
+            accumulator: Duration::ZERO,
+            last_frame: None,
+        }
+    }
+
+    // Measures the time since the last call and returns how many ticks to run
+    pub fn begin_frame(&mut self) -> u32 {
+        let now = Instant::now();
+        let elapsed = match self.last_frame {
+            Some(last_frame) => now - last_frame,
+            None => Duration::ZERO,
+        };
+        self.last_frame = Some(now);
+
+        self.advance(elapsed)
+    }
+
+    pub fn advance(&mut self, elapsed: Duration) -> u32 {
+        self.accumulator += elapsed;
+
+        let mut steps = 0;
+        while self.accumulator >= self.step && steps < self.max_steps {
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+
+        // After a long stall (dragging the window, a breakpoint) drop the time
+        // we cannot catch up on rather than running slower and slower
+        if self.accumulator >= self.step {
+            let step = self.step.as_nanos();
+            let leftover = self.accumulator.as_nanos() % step;
+            self.accumulator = Duration::from_nanos(leftover as u64);
+        }
+
+        steps
+    }
+
+    // How far between the last tick and the next one we are, from 0 to 1
+    pub fn alpha(&self) -> f32 {
+        self.accumulator.as_secs_f32() / self.step.as_secs_f32()
+    }
+
+    pub fn step_seconds(&self) -> f32 {
+        self.step.as_secs_f32()
+    }
+
+    pub fn step_milliseconds(&self) -> f32 {
+        1000.0 * self.step.as_secs_f32()
+    }
+}
@@ -1,6 +1,8 @@
+pub mod audio;
 pub mod constants;
 pub mod core;
 pub mod model;
 pub mod platform;
 pub mod renderer;
+pub mod ui;
 pub mod utility;
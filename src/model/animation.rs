@@ -0,0 +1,530 @@
+use std::collections::HashMap;
+
+use super::combat::Hitbox;
+use super::geometry::Rect;
+use crate::utility::string::split;
+
+// Clips are described in text files, one command per line:
+//
+//   anchor <x> <y>                                point of every frame placed on the
+//                                                 character's feet, for one clip when
+//                                                 it comes after the clip line
+//   clip <name>                                   starts a new clip
+//   mode loop|once|ping-pong                      how it plays, loop by default
+//   duration <seconds>                            time spent on every frame
+//   sheet <path> <width> <height> <columns>       grid of equally sized frames
+//   frames <first> <count>                        frames from the sheet, in reading order
+//   rect <x> <y> <width> <height>                 one frame from the sheet by hand
+//   files <pattern> <first> <last> <width> <height>  numbered files, {} is the number
+//   hold <frame> <seconds>                        overrides the duration of one frame
+//   event <frame> <name>                          reported when the frame comes up
+//   sound <event> <path> <volume> <pitch variation>  played whenever the event is
+//                                                 reported, by any clip of the set
+//   hitbox <frames> <x> <y> <width> <height> <damage> <knockback x> <knockback y>
+//   hurtbox <frames> <x> <y> <width> <height>     where the character can be hit,
+//                                                 its body when a frame has none
+//
+// Frame numbers count from 0 within the clip. Frames for hitboxes and hurtboxes
+// can also be a range like 2-5, or * for all of them. Boxes are in frame pixels.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlayMode {
+    Loop,
+    Once,
+    PingPong,
+}
+
+impl PlayMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "loop" => Some(PlayMode::Loop),
+            "once" => Some(PlayMode::Once),
+            "ping-pong" => Some(PlayMode::PingPong),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AnimationFrame {
+    // Index into the set's images
+    pub image: usize,
+    pub source: Rect,
+    // Seconds
+    pub duration: f32,
+    pub events: Vec<String>,
+    pub hitboxes: Vec<Hitbox>,
+    pub hurtboxes: Vec<Rect>,
+}
+
+impl AnimationFrame {
+    // Moves a rect in frame pixels to the world, for a frame drawn at position
+    pub fn place(&self, rect: Rect, position: glm::Vec2, flip_x: bool) -> Rect {
+        let x = if flip_x {
+            self.source.w - rect.x - rect.w
+        } else {
+            rect.x
+        };
+        Rect::new(position.x + x, position.y + rect.y, rect.w, rect.h)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Clip {
+    pub name: String,
+    pub mode: PlayMode,
+    // Overrides the set's anchor
+    pub anchor: Option<glm::Vec2>,
+    pub frames: Vec<AnimationFrame>,
+}
+
+impl Clip {
+    pub fn length(&self) -> f32 {
+        self.frames.iter().map(|frame| frame.duration).sum()
+    }
+}
+
+// A sound file tied to an event name
+#[derive(Clone, Debug, PartialEq)]
+pub struct SoundCue {
+    pub path: String,
+    pub volume: f32,
+    // Fraction the pitch is moved either way at random
+    pub pitch_variation: f32,
+}
+
+// What a character looks like this tick, turned into a sprite by the renderer
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pose {
+    // Index into the set's images
+    pub image: usize,
+    pub source: Rect,
+    // Top left corner in world pixels
+    pub position: glm::Vec2,
+    pub flip_x: bool,
+    // 1 is fully opaque
+    pub opacity: f32,
+}
+
+// Everything one character can play, with the images the frames come from
+pub struct AnimationSet {
+    pub anchor: glm::Vec2,
+    pub images: Vec<String>,
+    pub clips: Vec<Clip>,
+    // By event name
+    pub sounds: HashMap<String, SoundCue>,
+    // Sprite textures for the images once they are loaded
+    pub textures: Vec<usize>,
+}
+
+struct ClipBuilder {
+    name: String,
+    mode: PlayMode,
+    anchor: Option<glm::Vec2>,
+    duration: f32,
+    sheet: Option<(usize, f32, f32, u32)>,
+    frames: Vec<(usize, Rect)>,
+    holds: Vec<(usize, f32)>,
+    events: Vec<(usize, String)>,
+    hitboxes: Vec<(FrameRange, Hitbox)>,
+    hurtboxes: Vec<(FrameRange, Rect)>,
+}
+
+#[derive(Clone, Copy)]
+enum FrameRange {
+    All,
+    Between(usize, usize),
+}
+
+impl FrameRange {
+    fn from_name(name: &str) -> Option<Self> {
+        if name == "*" {
+            return Some(FrameRange::All);
+        }
+        let (first, last) = name.split_once('-').unwrap_or((name, name));
+        Some(FrameRange::Between(first.parse().ok()?, last.parse().ok()?))
+    }
+
+    fn indices(self, count: usize) -> std::ops::RangeInclusive<usize> {
+        match self {
+            FrameRange::All => 0..=count - 1,
+            FrameRange::Between(first, last) => first..=last,
+        }
+    }
+}
+
+impl ClipBuilder {
+    fn new(name: &str) -> Self {
+        ClipBuilder {
+            name: name.to_string(),
+            mode: PlayMode::Loop,
+            anchor: None,
+            duration: 0.1,
+            sheet: None,
+            frames: Vec::new(),
+            holds: Vec::new(),
+            events: Vec::new(),
+            hitboxes: Vec::new(),
+            hurtboxes: Vec::new(),
+        }
+    }
+
+    // Clips without frames are dropped, there is nothing to show
+    fn build(self) -> Option<Clip> {
+        if self.frames.is_empty() {
+            eprintln!("Clip {} has no frames", self.name);
+            return None;
+        }
+
+        let mut frames: Vec<AnimationFrame> = self
+            .frames
+            .into_iter()
+            .map(|(image, source)| AnimationFrame {
+                image,
+                source,
+                duration: self.duration,
+                events: Vec::new(),
+                hitboxes: Vec::new(),
+                hurtboxes: Vec::new(),
+            })
+            .collect();
+
+        for (index, duration) in self.holds {
+            match frames.get_mut(index) {
+                Some(frame) => frame.duration = duration,
+                None => eprintln!("Clip {} has no frame {index} to hold", self.name),
+            }
+        }
+        for (index, event) in self.events {
+            match frames.get_mut(index) {
+                Some(frame) => frame.events.push(event),
+                None => eprintln!("Clip {} has no frame {index} for {event}", self.name),
+            }
+        }
+        let count = frames.len();
+        for (range, hitbox) in self.hitboxes {
+            for index in range.indices(count) {
+                match frames.get_mut(index) {
+                    Some(frame) => frame.hitboxes.push(hitbox),
+                    None => eprintln!("Clip {} has no frame {index} for a hitbox", self.name),
+                }
+            }
+        }
+        for (range, hurtbox) in self.hurtboxes {
+            for index in range.indices(count) {
+                match frames.get_mut(index) {
+                    Some(frame) => frame.hurtboxes.push(hurtbox),
+                    None => eprintln!("Clip {} has no frame {index} for a hurtbox", self.name),
+                }
+            }
+        }
+
+        Some(Clip {
+            name: self.name,
+            mode: self.mode,
+            anchor: self.anchor,
+            frames,
+        })
+    }
+}
+
+impl AnimationSet {
+    pub fn load(filename: &str) -> Self {
+        let text = std::fs::read_to_string(filename)
+            .unwrap_or_else(|e| panic!("Cannot read animations {filename}: {e}"));
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let mut set = AnimationSet {
+            anchor: glm::Vec2::new(0.0, 0.0),
+            images: Vec::new(),
+            clips: Vec::new(),
+            sounds: HashMap::new(),
+            textures: Vec::new(),
+        };
+        let mut clip: Option<ClipBuilder> = None;
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let words: Vec<String> = split(line, " ")
+                .into_iter()
+                .filter(|word| !word.is_empty())
+                .collect();
+            if let [command, name] = words.as_slice()
+                && command == "clip"
+            {
+                set.clips.extend(clip.take().and_then(ClipBuilder::build));
+                clip = Some(ClipBuilder::new(name));
+                continue;
+            }
+            if let [command, x, y] = words.as_slice()
+                && command == "anchor"
+                && let (Ok(x), Ok(y)) = (x.parse(), y.parse())
+            {
+                match clip.as_mut() {
+                    Some(clip) => clip.anchor = Some(glm::Vec2::new(x, y)),
+                    None => set.anchor = glm::Vec2::new(x, y),
+                }
+                continue;
+            }
+            if let [command, event, path, volume, variation] = words.as_slice()
+                && command == "sound"
+                && let (Ok(volume), Ok(pitch_variation)) = (volume.parse(), variation.parse())
+            {
+                let cue = SoundCue {
+                    path: path.clone(),
+                    volume,
+                    pitch_variation,
+                };
+                set.sounds.insert(event.clone(), cue);
+                continue;
+            }
+
+            let parsed = match clip.as_mut() {
+                Some(clip) => set.parse_clip_line(clip, &words),
+                None => None,
+            };
+            if parsed.is_none() {
+                eprintln!("Ignoring animation line {}: {line}", number + 1);
+            }
+        }
+        set.clips.extend(clip.and_then(ClipBuilder::build));
+
+        set
+    }
+
+    fn parse_clip_line(&mut self, clip: &mut ClipBuilder, words: &[String]) -> Option<()> {
+        let number = |word: &String| word.parse::<f32>().ok();
+        let index = |word: &String| word.parse::<u32>().ok();
+
+        match words {
+            [command, mode] if command == "mode" => {
+                clip.mode = PlayMode::from_name(mode)?;
+            }
+            [command, seconds] if command == "duration" => {
+                clip.duration = number(seconds).filter(|seconds| *seconds > 0.0)?;
+            }
+            [command, path, width, height, columns] if command == "sheet" => {
+                let image = self.image(path);
+                clip.sheet = Some((
+                    image,
+                    number(width)?,
+                    number(height)?,
+                    index(columns).filter(|columns| *columns > 0)?,
+                ));
+            }
+            [command, first, count] if command == "frames" => {
+                let (image, width, height, columns) = clip.sheet?;
+                let first = index(first)?;
+                for i in first..first + index(count)? {
+                    let x = (i % columns) as f32 * width;
+                    let y = (i / columns) as f32 * height;
+                    clip.frames.push((image, Rect::new(x, y, width, height)));
+                }
+            }
+            [command, x, y, width, height] if command == "rect" => {
+                let (image, ..) = clip.sheet?;
+                let source = Rect::new(number(x)?, number(y)?, number(width)?, number(height)?);
+                clip.frames.push((image, source));
+            }
+            [command, pattern, first, last, width, height] if command == "files" => {
+                let source = Rect::new(0.0, 0.0, number(width)?, number(height)?);
+                for i in index(first)?..=index(last)? {
+                    let image = self.image(&pattern.replace("{}", &i.to_string()));
+                    clip.frames.push((image, source));
+                }
+            }
+            [command, frame, seconds] if command == "hold" => {
+                let seconds = number(seconds).filter(|seconds| *seconds > 0.0)?;
+                clip.holds.push((index(frame)? as usize, seconds));
+            }
+            [command, frame, name] if command == "event" => {
+                clip.events.push((index(frame)? as usize, name.clone()));
+            }
+            [
+                command,
+                frames,
+                x,
+                y,
+                width,
+                height,
+                damage,
+                knockback_x,
+                knockback_y,
+            ] if command == "hitbox" => {
+                let hitbox = Hitbox {
+                    rect: Rect::new(number(x)?, number(y)?, number(width)?, number(height)?),
+                    damage: damage.parse().ok()?,
+                    knockback: glm::Vec2::new(number(knockback_x)?, number(knockback_y)?),
+                };
+                clip.hitboxes.push((FrameRange::from_name(frames)?, hitbox));
+            }
+            [command, frames, x, y, width, height] if command == "hurtbox" => {
+                let hurtbox = Rect::new(number(x)?, number(y)?, number(width)?, number(height)?);
+                clip.hurtboxes
+                    .push((FrameRange::from_name(frames)?, hurtbox));
+            }
+            _ => return None,
+        }
+
+        Some(())
+    }
+
+    // Index of an image, shared between every clip that uses it
+    fn image(&mut self, path: &str) -> usize {
+        match self.images.iter().position(|image| image == path) {
+            Some(index) => index,
+            None => {
+                self.images.push(path.to_string());
+                self.images.len() - 1
+            }
+        }
+    }
+
+    pub fn clip(&self, name: &str) -> Option<usize> {
+        self.clips.iter().position(|clip| clip.name == name)
+    }
+}
+
+// Plays one clip of a set at a time
+pub struct Animator {
+    clip: usize,
+    frame: usize,
+    // Seconds spent on the current frame
+    time: f32,
+    forwards: bool,
+    finished: bool,
+    // The first frame's events have not been reported yet
+    started: bool,
+}
+
+impl Animator {
+    pub fn new(set: &AnimationSet, clip: &str) -> Self {
+        let mut animator = Animator {
+            clip: 0,
+            frame: 0,
+            time: 0.0,
+            forwards: true,
+            finished: false,
+            started: false,
+        };
+        animator.restart(set, clip);
+        animator
+    }
+
+    // Switches clip, carrying on where it is when the clip is already playing
+    pub fn play(&mut self, set: &AnimationSet, clip: &str) {
+        if set.clips[self.clip].name != clip {
+            self.restart(set, clip);
+        }
+    }
+
+    pub fn restart(&mut self, set: &AnimationSet, clip: &str) {
+        self.clip = set
+            .clip(clip)
+            .unwrap_or_else(|| panic!("No animation clip called {clip}"));
+        self.frame = 0;
+        self.time = 0.0;
+        self.forwards = true;
+        self.finished = false;
+        self.started = true;
+    }
+
+    // Moves the clip on by dt seconds and returns the events of every frame it reached
+    pub fn advance(&mut self, set: &AnimationSet, dt: f32) -> Vec<String> {
+        let clip = &set.clips[self.clip];
+        let mut events = Vec::new();
+        if std::mem::take(&mut self.started) {
+            events.extend(clip.frames[self.frame].events.iter().cloned());
+        }
+        if self.finished {
+            return events;
+        }
+
+        self.time += dt;
+        while self.time >= clip.frames[self.frame].duration {
+            let Some(next) = self.next_frame(clip) else {
+                self.finished = true;
+                self.time = clip.frames[self.frame].duration;
+                break;
+            };
+            self.time -= clip.frames[self.frame].duration;
+            self.frame = next;
+            events.extend(clip.frames[self.frame].events.iter().cloned());
+        }
+
+        events
+    }
+
+    fn next_frame(&mut self, clip: &Clip) -> Option<usize> {
+        let last = clip.frames.len() - 1;
+        match clip.mode {
+            PlayMode::Loop => Some(if self.frame == last {
+                0
+            } else {
+                self.frame + 1
+            }),
+            PlayMode::Once => (self.frame < last).then_some(self.frame + 1),
+            PlayMode::PingPong => {
+                if last == 0 {
+                    return Some(0);
+                }
+                if self.frame == last {
+                    self.forwards = false;
+                } else if self.frame == 0 {
+                    self.forwards = true;
+                }
+                Some(if self.forwards {
+                    self.frame + 1
+                } else {
+                    self.frame - 1
+                })
+            }
+        }
+    }
+
+    pub fn clip_name<'a>(&self, set: &'a AnimationSet) -> &'a str {
+        &set.clips[self.clip].name
+    }
+
+    pub fn frame_index(&self) -> usize {
+        self.frame
+    }
+
+    pub fn frame<'a>(&self, set: &'a AnimationSet) -> &'a AnimationFrame {
+        &set.clips[self.clip].frames[self.frame]
+    }
+
+    // Once clips are finished after their last frame has been shown in full
+    pub fn finished(&self) -> bool {
+        self.finished
+    }
+
+    // The current frame with its top left corner at position
+    pub fn pose(&self, set: &AnimationSet, position: glm::Vec2) -> Pose {
+        let frame = self.frame(set);
+        Pose {
+            image: frame.image,
+            source: frame.source,
+            position,
+            flip_x: false,
+            opacity: 1.0,
+        }
+    }
+
+    // Where the current frame's top left corner goes to put the anchor on the feet
+    pub fn origin(&self, set: &AnimationSet, feet: glm::Vec2, flip_x: bool) -> glm::Vec2 {
+        let anchor = set.clips[self.clip].anchor.unwrap_or(set.anchor);
+        let x = if flip_x {
+            self.frame(set).source.w - anchor.x
+        } else {
+            anchor.x
+        };
+        feet - glm::Vec2::new(x, anchor.y)
+    }
+}
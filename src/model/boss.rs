@@ -0,0 +1,424 @@
+use super::animation::{AnimationSet, Animator, Pose};
+use super::combat::{Combatant, DamageEvent, Fighter, Health, Hitbox, Team};
+use super::fight::{Fight, FightAction};
+use super::geometry::Rect;
+use super::level::{CollisionGrid, TileKind};
+use super::physics::{self, Body};
+use crate::constants::{gameplay, graphics};
+
+pub const ANIMATIONS: &str = "assets/animations/nightmare.anim";
+pub const FIGHT: &str = "assets/animations/nightmare.fight";
+
+// Projectile body size, in world pixels
+const PROJECTILE_SIZE: f32 = 12.0;
+
+// Walls that close in around the boss once the player walks in, and open
+// again when it is defeated
+pub struct Arena {
+    // Tiles turned solid while locked, with what they were before
+    gates: Vec<(i32, i32, TileKind)>,
+    // Inner edges of the gates, in world pixels
+    left: f32,
+    right: f32,
+    locked: bool,
+}
+
+impl Arena {
+    // Gates stand half_width tiles either side of the given tile, height tiles
+    // tall on whatever floor is below them
+    pub fn new(grid: &CollisionGrid, centre: (i32, i32), half_width: i32, height: i32) -> Self {
+        let mut gates = Vec::new();
+        for x in [centre.0 - half_width, centre.0 + half_width] {
+            let floor = (centre.1..grid.height as i32)
+                .find(|y| grid.is_solid(x, *y))
+                .unwrap_or(grid.height as i32);
+            for y in floor - height..floor {
+                gates.push((x, y, grid.get(x, y)));
+            }
+        }
+        let tile = graphics::TILE_PIXELS as f32;
+
+        Arena {
+            gates,
+            left: (centre.0 - half_width + 1) as f32 * tile,
+            right: (centre.0 + half_width) as f32 * tile,
+            locked: false,
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    // Entirely between the gates, where closing them cannot trap it inside a wall
+    pub fn surrounds(&self, body: &Body) -> bool {
+        body.position.x >= self.left && body.right() <= self.right
+    }
+
+    pub fn lock(&mut self, grid: &mut CollisionGrid) {
+        for (x, y, _) in &self.gates {
+            grid.set(*x, *y, TileKind::Solid);
+        }
+        self.locked = true;
+    }
+
+    pub fn unlock(&mut self, grid: &mut CollisionGrid) {
+        for (x, y, kind) in &self.gates {
+            grid.set(*x, *y, *kind);
+        }
+        self.locked = false;
+    }
+
+    // One rect per gate tile, in world pixels, for drawing the closed gates
+    pub fn gate_rects(&self) -> Vec<Rect> {
+        let tile = graphics::TILE_PIXELS as f32;
+        self.gates
+            .iter()
+            .map(|(x, y, _)| Rect::new(*x as f32 * tile, *y as f32 * tile, tile, tile))
+            .collect()
+    }
+}
+
+// The Nightmare, playing the timeline of whichever phase its health is in.
+// Its sprites face left.
+pub struct Boss {
+    pub body: Body,
+    pub facing_left: bool,
+    pub animator: Animator,
+    pub fighter: Fighter,
+    // Animation events reached during the last tick
+    pub events: Vec<String>,
+    pub arena: Arena,
+    fight: Fight,
+    // The player has come into the arena
+    awake: bool,
+    phase: usize,
+    // Seconds into the phase's timeline
+    time: f32,
+    // Next step of the timeline
+    step: usize,
+    // Gallop speed while charging, 0 otherwise
+    charge: f32,
+    // Seconds since it was defeated
+    fading: Option<f32>,
+    // The current frame's hitboxes in world pixels, placed at the end of each tick
+    hitboxes: Vec<Hitbox>,
+}
+
+impl Boss {
+    // Asleep until the player comes into the arena, standing with the middle
+    // of its feet on the given point
+    pub fn new(
+        fight: Fight,
+        animations: &AnimationSet,
+        grid: &CollisionGrid,
+        feet: glm::Vec2,
+    ) -> Self {
+        let size = fight.size;
+        let arena = Arena::new(
+            grid,
+            grid.tile_at(feet),
+            fight.arena_width,
+            fight.arena_height,
+        );
+
+        Boss {
+            body: Body::new(feet - glm::Vec2::new(0.5 * size.x, size.y), size),
+            facing_left: true,
+            animator: Animator::new(animations, "idle"),
+            fighter: Fighter::new(Team::Monsters, Health::new(fight.health, fight.recovery)),
+            events: Vec::new(),
+            arena,
+            fight,
+            awake: false,
+            phase: 0,
+            time: 0.0,
+            step: 0,
+            charge: 0.0,
+            fading: None,
+            hitboxes: Vec::new(),
+        }
+    }
+
+    pub fn is_awake(&self) -> bool {
+        self.awake
+    }
+
+    pub fn phase_name(&self) -> &str {
+        &self.fight.phases[self.phase].name
+    }
+
+    // Faded out after being defeated, it can be removed from the world
+    pub fn despawned(&self) -> bool {
+        self.fading
+            .is_some_and(|seconds| seconds >= gameplay::BOSS_FADE_TIME)
+    }
+
+    fn facing(&self) -> f32 {
+        if self.facing_left { -1.0 } else { 1.0 }
+    }
+
+    // One fixed tick, dt in seconds. Returns the projectiles it spat.
+    pub fn update(
+        &mut self,
+        dt: f32,
+        animations: &AnimationSet,
+        grid: &mut CollisionGrid,
+        player: &Body,
+    ) -> Vec<Projectile> {
+        let mut spat = Vec::new();
+
+        if self.fighter.health.is_dead() {
+            if self.fading.is_none() {
+                self.arena.unlock(grid);
+                self.charge = 0.0;
+                self.animator.play(animations, "idle");
+            }
+            self.fading = Some(self.fading.unwrap_or(0.0) + dt);
+        } else if !self.awake {
+            if self.arena.surrounds(player) {
+                self.arena.lock(grid);
+                self.awake = true;
+            }
+        } else {
+            let health = self.fighter.health.current as f32 / self.fighter.health.max as f32;
+            let phase = self.fight.phase_for(health);
+            if phase != self.phase {
+                self.phase = phase;
+                self.time = 0.0;
+                self.step = 0;
+            }
+            spat = self.run_timeline(animations, player);
+            self.time += dt;
+        }
+
+        // A charge ends against the first wall it meets
+        let wall = if self.facing_left {
+            self.body.contacts.wall_left
+        } else {
+            self.body.contacts.wall_right
+        };
+        if self.charge > 0.0 && wall {
+            self.charge = 0.0;
+            self.animator.play(animations, "idle");
+        }
+        self.body.velocity.x = self.facing() * self.charge;
+
+        physics::step(&mut self.body, grid, dt);
+        self.events = self.animator.advance(animations, dt);
+        self.fighter.health.tick(dt);
+        self.hitboxes = self.place_hitboxes(animations);
+
+        spat
+    }
+
+    // Runs every step that has come up
+    fn run_timeline(&mut self, animations: &AnimationSet, player: &Body) -> Vec<Projectile> {
+        let mut spat = Vec::new();
+
+        while let Some(step) = self.fight.phases[self.phase].steps.get(self.step)
+            && step.time <= self.time
+        {
+            self.step += 1;
+            match step.action.clone() {
+                FightAction::Idle => {
+                    self.charge = 0.0;
+                    self.animator.play(animations, "idle");
+                }
+                FightAction::Face => {
+                    self.facing_left = player.centre().x < self.body.centre().x;
+                }
+                FightAction::Charge(speed) => {
+                    self.charge = speed;
+                    self.animator.play(animations, "gallop");
+                }
+                FightAction::Breath {
+                    clip,
+                    velocity,
+                    gravity,
+                } => {
+                    let feet = self.body.position
+                        + glm::Vec2::new(0.5 * self.body.size.x, self.body.size.y);
+                    let mouth = feet
+                        + glm::Vec2::new(-self.facing() * self.fight.mouth.x, self.fight.mouth.y);
+                    let velocity = glm::Vec2::new(self.facing() * velocity.x, velocity.y);
+                    spat.push(Projectile::new(animations, &clip, mouth, velocity, gravity));
+                }
+                FightAction::Repeat => {
+                    self.time = 0.0;
+                    self.step = 0;
+                    break;
+                }
+            }
+        }
+
+        spat
+    }
+
+    // Where the current frame's top left corner goes for a body at position
+    fn frame_position(&self, animations: &AnimationSet, position: glm::Vec2) -> glm::Vec2 {
+        let feet = position + glm::Vec2::new(0.5 * self.body.size.x, self.body.size.y);
+        self.animator.origin(animations, feet, !self.facing_left)
+    }
+
+    fn place_hitboxes(&self, animations: &AnimationSet) -> Vec<Hitbox> {
+        let frame = self.animator.frame(animations);
+        let position = self.frame_position(animations, self.body.position);
+        frame
+            .hitboxes
+            .iter()
+            .map(|hitbox| Hitbox {
+                rect: frame.place(hitbox.rect, position, !self.facing_left),
+                ..*hitbox
+            })
+            .collect()
+    }
+
+    // Current frame at the interpolated position
+    pub fn pose(&self, animations: &AnimationSet, alpha: f32) -> Pose {
+        let position = self.frame_position(animations, self.body.interpolated_position(alpha));
+
+        let mut pose = self.animator.pose(animations, position);
+        pose.flip_x = !self.facing_left;
+        match self.fading {
+            Some(seconds) => pose.opacity = 1.0 - seconds / gameplay::BOSS_FADE_TIME,
+            None if self.fighter.health.is_invulnerable() => pose.opacity = 0.5,
+            None => {}
+        }
+        pose
+    }
+}
+
+impl Combatant for Boss {
+    fn fighter(&self) -> &Fighter {
+        &self.fighter
+    }
+
+    fn fighter_mut(&mut self) -> &mut Fighter {
+        &mut self.fighter
+    }
+
+    fn hitboxes(&self) -> Vec<Hitbox> {
+        self.hitboxes.clone()
+    }
+
+    fn hurtboxes(&self) -> Vec<Rect> {
+        vec![Rect::new(
+            self.body.position.x,
+            self.body.position.y,
+            self.body.size.x,
+            self.body.size.y,
+        )]
+    }
+
+    // Too heavy to be knocked back, it carries on with its timeline
+    fn take_hit(&mut self, _hit: &DamageEvent) {}
+}
+
+// Breath spat by the boss. It flies until it hits something, then burns
+// where it landed until its burst clip ends.
+pub struct Projectile {
+    pub body: Body,
+    pub facing_left: bool,
+    pub animator: Animator,
+    pub fighter: Fighter,
+    burst: String,
+    landed: bool,
+    hitboxes: Vec<Hitbox>,
+}
+
+impl Projectile {
+    // Centred on the given point, its clip's frames face left
+    pub fn new(
+        animations: &AnimationSet,
+        clip: &str,
+        centre: glm::Vec2,
+        velocity: glm::Vec2,
+        gravity: f32,
+    ) -> Self {
+        let size = glm::Vec2::new(PROJECTILE_SIZE, PROJECTILE_SIZE);
+        let mut body = Body::new(centre - size * 0.5, size);
+        body.velocity = velocity;
+        body.gravity_scale = gravity;
+
+        Projectile {
+            body,
+            facing_left: velocity.x < 0.0,
+            animator: Animator::new(animations, clip),
+            fighter: Fighter::new(Team::Monsters, Health::new(1, 0.0)),
+            burst: format!("{clip}_burst"),
+            landed: false,
+            hitboxes: Vec::new(),
+        }
+    }
+
+    pub fn has_landed(&self) -> bool {
+        self.landed
+    }
+
+    // The burst has burnt out
+    pub fn gone(&self) -> bool {
+        self.landed && self.animator.finished()
+    }
+
+    // One fixed tick, dt in seconds
+    pub fn update(&mut self, dt: f32, animations: &AnimationSet, grid: &CollisionGrid) {
+        let contacts = self.body.contacts;
+        if !self.landed
+            && (contacts.ground || contacts.ceiling || contacts.wall_left || contacts.wall_right)
+        {
+            self.landed = true;
+            self.body.velocity = glm::Vec2::new(0.0, 0.0);
+            self.body.gravity_scale = 0.0;
+            self.animator.restart(animations, &self.burst);
+        }
+
+        physics::step(&mut self.body, grid, dt);
+        self.animator.advance(animations, dt);
+
+        let frame = self.animator.frame(animations);
+        let position = self.frame_position(animations, self.body.position);
+        self.hitboxes = frame
+            .hitboxes
+            .iter()
+            .map(|hitbox| Hitbox {
+                rect: frame.place(hitbox.rect, position, !self.facing_left),
+                ..*hitbox
+            })
+            .collect();
+    }
+
+    fn frame_position(&self, animations: &AnimationSet, position: glm::Vec2) -> glm::Vec2 {
+        let feet = position + glm::Vec2::new(0.5 * self.body.size.x, self.body.size.y);
+        self.animator.origin(animations, feet, !self.facing_left)
+    }
+
+    pub fn pose(&self, animations: &AnimationSet, alpha: f32) -> Pose {
+        let position = self.frame_position(animations, self.body.interpolated_position(alpha));
+
+        let mut pose = self.animator.pose(animations, position);
+        pose.flip_x = !self.facing_left;
+        pose
+    }
+}
+
+impl Combatant for Projectile {
+    fn fighter(&self) -> &Fighter {
+        &self.fighter
+    }
+
+    fn fighter_mut(&mut self) -> &mut Fighter {
+        &mut self.fighter
+    }
+
+    fn hitboxes(&self) -> Vec<Hitbox> {
+        self.hitboxes.clone()
+    }
+
+    // Cannot be hit back
+    fn hurtboxes(&self) -> Vec<Rect> {
+        Vec::new()
+    }
+
+    fn take_hit(&mut self, _hit: &DamageEvent) {}
+}
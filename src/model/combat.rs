@@ -0,0 +1,166 @@
+use super::geometry::Rect;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Team {
+    Knight,
+    Monsters,
+}
+
+// Part of an attack, in world pixels once it is placed in the world
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hitbox {
+    pub rect: Rect,
+    pub damage: i32,
+    // Speed given to whoever is hit, x pointing away from the hitbox
+    pub knockback: glm::Vec2,
+}
+
+pub struct Health {
+    pub current: i32,
+    pub max: i32,
+    // Seconds of invulnerability after each hit
+    pub recovery: f32,
+    invulnerable: f32,
+}
+
+impl Health {
+    pub fn new(max: i32, recovery: f32) -> Self {
+        Health {
+            current: max,
+            max,
+            recovery,
+            invulnerable: 0.0,
+        }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable > 0.0
+    }
+
+    pub fn can_be_hurt(&self) -> bool {
+        !self.is_dead() && !self.is_invulnerable()
+    }
+
+    pub fn damage(&mut self, amount: i32) {
+        self.current = (self.current - amount).max(0);
+        self.invulnerable = self.recovery;
+    }
+
+    pub fn heal(&mut self, amount: i32) {
+        if !self.is_dead() {
+            self.current = (self.current + amount).min(self.max);
+        }
+    }
+
+    // dt in seconds
+    pub fn tick(&mut self, dt: f32) {
+        self.invulnerable = glm::max(self.invulnerable - dt, 0.0);
+    }
+}
+
+pub struct Fighter {
+    pub team: Team,
+    pub health: Health,
+    // Combatants already hit by the current attack, each is only hit once per swing
+    struck: Vec<usize>,
+}
+
+impl Fighter {
+    pub fn new(team: Team, health: Health) -> Self {
+        Fighter {
+            team,
+            health,
+            struck: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DamageEvent {
+    // Indices into the combatants passed to exchange_blows. The attacker is
+    // the target itself when it was hurt by the level.
+    pub attacker: usize,
+    pub target: usize,
+    pub damage: i32,
+    pub knockback: glm::Vec2,
+    // Middle of the overlap between hitbox and hurtbox
+    pub position: glm::Vec2,
+}
+
+// Anything that can hit or be hit
+pub trait Combatant {
+    fn fighter(&self) -> &Fighter;
+    fn fighter_mut(&mut self) -> &mut Fighter;
+    // Active this tick, in world pixels
+    fn hitboxes(&self) -> Vec<Hitbox>;
+    fn hurtboxes(&self) -> Vec<Rect>;
+    // Called after the damage has been taken off its health
+    fn take_hit(&mut self, hit: &DamageEvent);
+}
+
+// Checks every attack against everyone on the other team. Each attack hits
+// a target once, and hit targets are invulnerable for their recovery time.
+pub fn exchange_blows(combatants: &mut [&mut dyn Combatant]) -> Vec<DamageEvent> {
+    let hitboxes: Vec<Vec<Hitbox>> = combatants.iter().map(|c| c.hitboxes()).collect();
+    let hurtboxes: Vec<Vec<Rect>> = combatants.iter().map(|c| c.hurtboxes()).collect();
+    let mut events = Vec::new();
+
+    for attacker in 0..combatants.len() {
+        // The swing is over once the hitboxes are gone
+        if hitboxes[attacker].is_empty() {
+            combatants[attacker].fighter_mut().struck.clear();
+            continue;
+        }
+        if combatants[attacker].fighter().health.is_dead() {
+            continue;
+        }
+
+        for target in 0..combatants.len() {
+            let attacking = combatants[attacker].fighter();
+            let defending = combatants[target].fighter();
+            if attacking.team == defending.team
+                || attacking.struck.contains(&target)
+                || !defending.health.can_be_hurt()
+            {
+                continue;
+            }
+
+            // The strongest hitbox touching any hurtbox lands
+            let landed = hitboxes[attacker]
+                .iter()
+                .flat_map(|hitbox| {
+                    hurtboxes[target].iter().filter_map(move |hurtbox| {
+                        hitbox.rect.overlap(hurtbox).map(|o| (hitbox, hurtbox, o))
+                    })
+                })
+                .max_by_key(|(hitbox, ..)| hitbox.damage);
+            let Some((hitbox, hurtbox, overlap)) = landed else {
+                continue;
+            };
+
+            let away = if hurtbox.centre().x < hitbox.rect.centre().x {
+                -1.0
+            } else {
+                1.0
+            };
+            let event = DamageEvent {
+                attacker,
+                target,
+                damage: hitbox.damage,
+                knockback: glm::Vec2::new(away * hitbox.knockback.x.abs(), hitbox.knockback.y),
+                position: overlap.centre(),
+            };
+
+            combatants[target].fighter_mut().health.damage(event.damage);
+            combatants[target].take_hit(&event);
+            combatants[attacker].fighter_mut().struck.push(target);
+            events.push(event);
+        }
+    }
+
+    events
+}
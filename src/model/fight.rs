@@ -0,0 +1,186 @@
+use crate::utility::string::split;
+
+// Boss fights are scripted in text files so they can be rebalanced without
+// rebuilding:
+//
+//   health <max>
+//   recovery <seconds>             invulnerable this long after each hit
+//   size <width> <height>          body in world pixels
+//   mouth <x> <y>                  where breath comes out, from the middle of the
+//                                  feet while facing left
+//   arena <half width> <height>    tiles from the spawn to each gate, and how tall
+//                                  the gates are
+//   phase <name> [below <fraction>]
+//                                  starts a timeline, played once health falls to
+//                                  the fraction of its max. The first phase plays
+//                                  until then.
+//   at <seconds> <action>          step of the phase's timeline
+//
+// Actions:
+//   idle                           stands still
+//   face                           turns towards the player
+//   charge <speed>                 gallops forwards until it runs into a wall
+//   breath <clip> <speed x> <speed y> <gravity>
+//                                  spits a projectile forwards, which flies playing
+//                                  <clip> and leaves <clip>_burst where it lands
+//   repeat                         starts the phase's timeline over
+//
+// Steps are run in order of time, whatever order they are written in.
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FightAction {
+    Idle,
+    Face,
+    Charge(f32),
+    Breath {
+        clip: String,
+        velocity: glm::Vec2,
+        gravity: f32,
+    },
+    Repeat,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+    // Seconds from the start of the phase
+    pub time: f32,
+    pub action: FightAction,
+}
+
+#[derive(Clone, Debug)]
+pub struct Phase {
+    pub name: String,
+    // Fraction of health at or below which the phase plays
+    pub below: f32,
+    pub steps: Vec<Step>,
+}
+
+impl Phase {
+    fn new(name: &str, below: f32) -> Self {
+        Phase {
+            name: name.to_string(),
+            below,
+            steps: Vec::new(),
+        }
+    }
+}
+
+pub struct Fight {
+    pub health: i32,
+    pub recovery: f32,
+    pub size: glm::Vec2,
+    pub mouth: glm::Vec2,
+    pub arena_width: i32,
+    pub arena_height: i32,
+    // Never empty
+    pub phases: Vec<Phase>,
+}
+
+impl Fight {
+    pub fn load(filename: &str) -> Self {
+        let text = std::fs::read_to_string(filename)
+            .unwrap_or_else(|e| panic!("Cannot read fight {filename}: {e}"));
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let mut fight = Fight {
+            health: 100,
+            recovery: 0.5,
+            size: glm::Vec2::new(32.0, 32.0),
+            mouth: glm::Vec2::new(0.0, 0.0),
+            arena_width: 10,
+            arena_height: 8,
+            phases: Vec::new(),
+        };
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let words: Vec<String> = split(line, " ")
+                .into_iter()
+                .filter(|word| !word.is_empty())
+                .collect();
+            if fight.parse_line(&words).is_none() {
+                eprintln!("Ignoring fight line {}: {line}", number + 1);
+            }
+        }
+
+        if fight.phases.is_empty() {
+            eprintln!("Fight has no phases, the boss will stand still");
+            fight.phases.push(Phase::new("idle", 1.0));
+        }
+        for phase in &mut fight.phases {
+            phase.steps.sort_by(|a, b| a.time.total_cmp(&b.time));
+        }
+
+        fight
+    }
+
+    fn parse_line(&mut self, words: &[String]) -> Option<()> {
+        let number = |word: &String| word.parse::<f32>().ok();
+        let tiles = |word: &String| word.parse::<i32>().ok().filter(|tiles| *tiles > 0);
+
+        match words {
+            [command, health] if command == "health" => {
+                self.health = health.parse().ok().filter(|health| *health > 0)?;
+            }
+            [command, seconds] if command == "recovery" => self.recovery = number(seconds)?,
+            [command, width, height] if command == "size" => {
+                self.size = glm::Vec2::new(number(width)?, number(height)?);
+            }
+            [command, x, y] if command == "mouth" => {
+                self.mouth = glm::Vec2::new(number(x)?, number(y)?);
+            }
+            [command, width, height] if command == "arena" => {
+                self.arena_width = tiles(width)?;
+                self.arena_height = tiles(height)?;
+            }
+            [command, name] if command == "phase" => self.phases.push(Phase::new(name, 1.0)),
+            [command, name, below, fraction] if command == "phase" && below == "below" => {
+                self.phases.push(Phase::new(name, number(fraction)?));
+            }
+            [command, time, action @ ..] if command == "at" => {
+                let step = Step {
+                    time: number(time)?,
+                    action: Self::parse_action(action)?,
+                };
+                self.phases.last_mut()?.steps.push(step);
+            }
+            _ => return None,
+        }
+
+        Some(())
+    }
+
+    fn parse_action(words: &[String]) -> Option<FightAction> {
+        let number = |word: &String| word.parse::<f32>().ok();
+
+        match words {
+            [action] if action == "idle" => Some(FightAction::Idle),
+            [action] if action == "face" => Some(FightAction::Face),
+            [action] if action == "repeat" => Some(FightAction::Repeat),
+            [action, speed] if action == "charge" => Some(FightAction::Charge(number(speed)?)),
+            [action, clip, x, y, gravity] if action == "breath" => Some(FightAction::Breath {
+                clip: clip.clone(),
+                velocity: glm::Vec2::new(number(x)?, number(y)?),
+                gravity: number(gravity)?,
+            }),
+            _ => None,
+        }
+    }
+
+    // The phase with the lowest threshold health has fallen to, the first
+    // phase while health is above every threshold
+    pub fn phase_for(&self, health: f32) -> usize {
+        self.phases
+            .iter()
+            .enumerate()
+            .filter(|(_, phase)| health <= phase.below)
+            .min_by(|(_, a), (_, b)| a.below.total_cmp(&b.below))
+            .map_or(0, |(index, _)| index)
+    }
+}
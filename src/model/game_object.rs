@@ -1,3 +1,5 @@
+use crate::constants::graphics;
+
 pub struct Object {
     pub position: glm::Vec3,
     pub angle: f32,
@@ -12,6 +14,12 @@ pub struct Camera {
     pitch: f32,
 }
 
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Camera {
     pub fn new() -> Self {
         let position = glm::Vec3::new(-5.0, 0.0, 2.0);
@@ -68,3 +76,105 @@ impl Camera {
         self.position.z = z;
     }
 }
+
+// 2D camera measured in world pixels, y pointing down
+pub struct OrthoCamera {
+    // Centre of the view
+    pub position: glm::Vec2,
+    // Position at the start of the current tick, for interpolated rendering
+    pub previous_position: glm::Vec2,
+    pub zoom: f32,
+    // How quickly follow() catches up with its target, per second; 0 snaps instantly
+    pub follow_speed: f32,
+    bounds: Option<(glm::Vec2, glm::Vec2)>,
+}
+
+impl Default for OrthoCamera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrthoCamera {
+    pub fn new() -> Self {
+        let width = graphics::MAP_WDITH_PIXELS as f32;
+        let height = graphics::MAP_HEIGHT_PIXELS as f32;
+
+        let position = glm::Vec2::new(0.5 * width, 0.5 * height);
+
+        OrthoCamera {
+            position,
+            previous_position: position,
+            zoom: 1.0,
+            follow_speed: 0.0,
+            bounds: None,
+        }
+    }
+
+    // Area of the world visible at the current zoom
+    pub fn view_size(&self) -> glm::Vec2 {
+        glm::Vec2::new(
+            graphics::MAP_WDITH_PIXELS as f32 / self.zoom,
+            graphics::MAP_HEIGHT_PIXELS as f32 / self.zoom,
+        )
+    }
+
+    pub fn top_left(&self) -> glm::Vec2 {
+        self.position - self.view_size() * 0.5
+    }
+
+    // alpha 0 is the start of the tick, 1 the current position
+    pub fn interpolated_position(&self, alpha: f32) -> glm::Vec2 {
+        self.previous_position + (self.position - self.previous_position) * alpha
+    }
+
+    pub fn interpolated_top_left(&self, alpha: f32) -> glm::Vec2 {
+        self.interpolated_position(alpha) - self.view_size() * 0.5
+    }
+
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = glm::max(zoom, 0.01);
+        self.clamp_to_bounds();
+    }
+
+    // Keeps the view inside the rectangle from min to max, e.g. the level
+    pub fn set_bounds(&mut self, min: glm::Vec2, max: glm::Vec2) {
+        self.bounds = Some((min, max));
+        self.clamp_to_bounds();
+    }
+
+    pub fn clear_bounds(&mut self) {
+        self.bounds = None;
+    }
+
+    // dt in milliseconds, like World::update
+    pub fn follow(&mut self, target: glm::Vec2, dt: f32) {
+        if self.follow_speed <= 0.0 {
+            self.position = target;
+        } else {
+            let t = glm::min(1.0, self.follow_speed * dt / 1000.0);
+            self.position = self.position + (target - self.position) * t;
+        }
+
+        self.clamp_to_bounds();
+    }
+
+    pub fn clamp_to_bounds(&mut self) {
+        let Some((min, max)) = self.bounds else {
+            return;
+        };
+        let half = self.view_size() * 0.5;
+
+        // Centre on any axis where the bounds are smaller than the view
+        self.position.x = if max.x - min.x < 2.0 * half.x {
+            0.5 * (min.x + max.x)
+        } else {
+            glm::clamp(self.position.x, min.x + half.x, max.x - half.x)
+        };
+        self.position.y = if max.y - min.y < 2.0 * half.y {
+            0.5 * (min.y + max.y)
+        } else {
+            glm::clamp(self.position.y, min.y + half.y, max.y - half.y)
+        };
+    }
+}
@@ -0,0 +1,26 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, w: f32, h: f32) -> Self {
+        Rect { x, y, w, h }
+    }
+
+    pub fn centre(&self) -> glm::Vec2 {
+        glm::Vec2::new(self.x + 0.5 * self.w, self.y + 0.5 * self.h)
+    }
+
+    // Shared area of two rects, None when they only touch or are apart
+    pub fn overlap(&self, other: &Rect) -> Option<Rect> {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = (self.x + self.w).min(other.x + other.w);
+        let bottom = (self.y + self.h).min(other.y + other.h);
+        (right > left && bottom > top).then(|| Rect::new(left, top, right - left, bottom - top))
+    }
+}
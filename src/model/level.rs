@@ -0,0 +1,295 @@
+use crate::constants::graphics;
+use std::env::current_dir;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TileKind {
+    Empty,
+    Solid,
+    // Only blocks from above, can be jumped through
+    OneWay,
+    // 45 degree floors, named after the side that is higher
+    SlopeRight,
+    SlopeLeft,
+    Hazard,
+    // Where the player starts
+    Spawn,
+    EnemySpawn,
+    BossSpawn,
+    Exit,
+}
+
+impl TileKind {
+    pub fn is_solid(self) -> bool {
+        self == TileKind::Solid
+    }
+
+    pub fn is_slope(self) -> bool {
+        matches!(self, TileKind::SlopeRight | TileKind::SlopeLeft)
+    }
+
+    // Markers say where things go but are empty space for collision
+    pub fn is_marker(self) -> bool {
+        matches!(
+            self,
+            TileKind::Spawn | TileKind::EnemySpawn | TileKind::BossSpawn | TileKind::Exit
+        )
+    }
+}
+
+// Colours painted into the hitbox masks. The masks are hand painted and
+// slightly noisy, so pixels are matched to the nearest entry.
+const PALETTE: [([u8; 3], TileKind); 11] = [
+    ([255, 255, 255], TileKind::Empty),
+    ([0, 0, 255], TileKind::Solid),
+    ([0, 0, 0], TileKind::Solid),
+    ([0, 255, 255], TileKind::OneWay),
+    ([255, 128, 0], TileKind::SlopeRight),
+    ([128, 0, 255], TileKind::SlopeLeft),
+    ([255, 0, 255], TileKind::Hazard),
+    ([255, 255, 0], TileKind::Spawn),
+    ([0, 255, 0], TileKind::EnemySpawn),
+    ([255, 0, 0], TileKind::BossSpawn),
+    ([128, 128, 128], TileKind::Exit),
+];
+
+pub fn classify(pixel: [u8; 3]) -> TileKind {
+    let distance = |color: &[u8; 3]| -> i32 {
+        (0..3)
+            .map(|i| {
+                let d = pixel[i] as i32 - color[i] as i32;
+                d * d
+            })
+            .sum()
+    };
+
+    PALETTE
+        .iter()
+        .min_by_key(|(color, _)| distance(color))
+        .map(|(_, kind)| *kind)
+        .unwrap()
+}
+
+// Level geometry in tiles of graphics::TILE_PIXELS, row major, y pointing down
+pub struct CollisionGrid {
+    pub width: u32,
+    pub height: u32,
+    tiles: Vec<TileKind>,
+}
+
+impl CollisionGrid {
+    pub fn new(width: u32, height: u32) -> Self {
+        CollisionGrid {
+            width,
+            height,
+            tiles: vec![TileKind::Empty; (width * height) as usize],
+        }
+    }
+
+    // Builds a width x height grid from a mask with either one pixel per tile
+    // or any whole number of pixels per tile, by majority vote over each block.
+    // A marker anywhere in a block wins, they are often a single pixel.
+    pub fn from_mask(mask: &image::RgbaImage, width: u32, height: u32) -> Self {
+        let mut grid = CollisionGrid::new(width, height);
+        let block_w = (mask.width() / width).max(1);
+        let block_h = (mask.height() / height).max(1);
+
+        for ty in 0..height {
+            for tx in 0..width {
+                let mut counts = [0u32; PALETTE.len()];
+                let mut marker: Option<TileKind> = None;
+                for py in ty * block_h..((ty + 1) * block_h).min(mask.height()) {
+                    for px in tx * block_w..((tx + 1) * block_w).min(mask.width()) {
+                        let pixel = mask.get_pixel(px, py).0;
+                        let kind = classify([pixel[0], pixel[1], pixel[2]]);
+                        if kind.is_marker() {
+                            marker = Some(kind);
+                        }
+                        let index = PALETTE.iter().position(|(_, k)| *k == kind).unwrap();
+                        counts[index] += 1;
+                    }
+                }
+
+                let majority = (0..PALETTE.len())
+                    .max_by_key(|i| counts[*i])
+                    .map(|i| PALETTE[i].1)
+                    .unwrap();
+                grid.set(tx as i32, ty as i32, marker.unwrap_or(majority));
+            }
+        }
+
+        grid
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return None;
+        }
+        Some((y as u32 * self.width + x as u32) as usize)
+    }
+
+    // Outside the level counts as solid so nothing falls off the map
+    pub fn get(&self, x: i32, y: i32) -> TileKind {
+        match self.index(x, y) {
+            Some(i) => self.tiles[i],
+            None => TileKind::Solid,
+        }
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, kind: TileKind) {
+        if let Some(i) = self.index(x, y) {
+            self.tiles[i] = kind;
+        }
+    }
+
+    pub fn is_solid(&self, x: i32, y: i32) -> bool {
+        self.get(x, y).is_solid()
+    }
+
+    pub fn pixel_size(&self) -> glm::Vec2 {
+        glm::Vec2::new(
+            (self.width * graphics::TILE_PIXELS) as f32,
+            (self.height * graphics::TILE_PIXELS) as f32,
+        )
+    }
+
+    // Tile containing a point given in world pixels
+    pub fn tile_at(&self, point: glm::Vec2) -> (i32, i32) {
+        let tile = graphics::TILE_PIXELS as f32;
+        (
+            (point.x / tile).floor() as i32,
+            (point.y / tile).floor() as i32,
+        )
+    }
+
+    // Top left corner of a tile in world pixels
+    pub fn tile_position(&self, x: i32, y: i32) -> glm::Vec2 {
+        let tile = graphics::TILE_PIXELS as f32;
+        glm::Vec2::new(x as f32 * tile, y as f32 * tile)
+    }
+
+    // Every tile touched by the box from min to max, in world pixels
+    pub fn tiles_overlapping(&self, min: glm::Vec2, max: glm::Vec2) -> Vec<(i32, i32, TileKind)> {
+        let (x0, y0) = self.tile_at(min);
+        // Boxes ending exactly on a tile edge do not reach into the next tile
+        let (x1, y1) = self.tile_at(max - glm::Vec2::new(0.001, 0.001));
+
+        let mut tiles = Vec::new();
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                tiles.push((x, y, self.get(x, y)));
+            }
+        }
+        tiles
+    }
+
+    pub fn any_overlapping(&self, min: glm::Vec2, max: glm::Vec2, kind: TileKind) -> bool {
+        self.tiles_overlapping(min, max)
+            .iter()
+            .any(|(_, _, k)| *k == kind)
+    }
+
+    // Whether nothing solid lies on the straight line between two points in world pixels
+    pub fn line_of_sight(&self, from: glm::Vec2, to: glm::Vec2) -> bool {
+        // Sampled a quarter tile apart so no tile can be skipped over
+        let spacing = graphics::TILE_PIXELS as f32 * 0.25;
+        let steps = (glm::distance(from, to) / spacing).ceil().max(1.0) as i32;
+        (0..=steps).all(|i| {
+            let (x, y) = self.tile_at(from + (to - from) * (i as f32 / steps as f32));
+            !self.is_solid(x, y)
+        })
+    }
+
+    pub fn find_all(&self, kind: TileKind) -> Vec<(i32, i32)> {
+        let mut found = Vec::new();
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                if self.get(x, y) == kind {
+                    found.push((x, y));
+                }
+            }
+        }
+        found
+    }
+}
+
+pub struct Level {
+    pub number: u32,
+    pub collision: CollisionGrid,
+    pub background: image::RgbaImage,
+}
+
+impl Level {
+    // Reads assets/levels/level_N_design.png and level_N_hitBox.png
+    pub fn load(number: u32) -> Self {
+        let design = Self::load_image(&Self::design_path(number));
+        let mask = Self::load_image(&format!("assets/levels/level_{number}_hitBox.png"));
+
+        Self::from_images(number, design, &mask)
+    }
+
+    // The design image doubles as the background texture
+    pub fn design_path(number: u32) -> String {
+        format!("assets/levels/level_{number}_design.png")
+    }
+
+    pub fn exists(number: u32) -> bool {
+        current_dir()
+            .unwrap()
+            .join(Self::design_path(number))
+            .exists()
+    }
+
+    // The design image decides the level size, in whole tiles
+    pub fn from_images(number: u32, design: image::RgbaImage, mask: &image::RgbaImage) -> Self {
+        let width = (design.width() / graphics::TILE_PIXELS).max(1);
+        let height = (design.height() / graphics::TILE_PIXELS).max(1);
+        let collision = CollisionGrid::from_mask(mask, width, height);
+
+        Level {
+            number,
+            collision,
+            background: design,
+        }
+    }
+
+    fn load_image(filename: &str) -> image::RgbaImage {
+        let mut filepath = current_dir().unwrap();
+        filepath.push(filename);
+
+        let bytes = std::fs::read(&filepath)
+            .unwrap_or_else(|e| panic!("Cannot read level image {}: {e}", filepath.display()));
+        image::load_from_memory(&bytes).unwrap().to_rgba8()
+    }
+
+    // Centre of the player spawn tile, or the middle of the level if none was painted
+    pub fn spawn_point(&self) -> glm::Vec2 {
+        match self.collision.find_all(TileKind::Spawn).first() {
+            Some((x, y)) => self.tile_centre(*x, *y),
+            None => self.collision.pixel_size() * 0.5,
+        }
+    }
+
+    // Centres of the enemy spawn tiles, one skeleton each
+    pub fn enemy_spawns(&self) -> Vec<glm::Vec2> {
+        self.collision
+            .find_all(TileKind::EnemySpawn)
+            .into_iter()
+            .map(|(x, y)| self.tile_centre(x, y))
+            .collect()
+    }
+
+    // Centre of the boss spawn tile, if the level has a boss
+    pub fn boss_spawn(&self) -> Option<glm::Vec2> {
+        let (x, y) = *self.collision.find_all(TileKind::BossSpawn).first()?;
+        Some(self.tile_centre(x, y))
+    }
+
+    fn tile_centre(&self, x: i32, y: i32) -> glm::Vec2 {
+        let tile = graphics::TILE_PIXELS as f32;
+        self.collision.tile_position(x, y) + glm::Vec2::new(0.5 * tile, 0.5 * tile)
+    }
+
+    pub fn pixel_size(&self) -> glm::Vec2 {
+        self.collision.pixel_size()
+    }
+}
@@ -1,2 +1,12 @@
+pub mod animation;
+pub mod boss;
+pub mod combat;
+pub mod fight;
 pub mod game_object;
+pub mod geometry;
+pub mod level;
+pub mod physics;
+pub mod player;
+pub mod skeleton;
+pub mod state_machine;
 pub mod world;
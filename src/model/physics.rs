@@ -0,0 +1,245 @@
+use super::level::{CollisionGrid, TileKind};
+use crate::constants::{graphics, physics};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Contacts {
+    pub ground: bool,
+    pub ceiling: bool,
+    pub wall_left: bool,
+    pub wall_right: bool,
+    pub on_slope: bool,
+}
+
+// Axis aligned box moved through the tile grid, y pointing down
+pub struct Body {
+    // Top left corner in world pixels
+    pub position: glm::Vec2,
+    // Position before the last step, for interpolated rendering
+    pub previous_position: glm::Vec2,
+    pub size: glm::Vec2,
+    // Pixels per second
+    pub velocity: glm::Vec2,
+    pub gravity_scale: f32,
+    // Fall through one-way platforms on the next step
+    pub drop_through: bool,
+    pub contacts: Contacts,
+}
+
+impl Body {
+    pub fn new(position: glm::Vec2, size: glm::Vec2) -> Self {
+        Body {
+            position,
+            previous_position: position,
+            size,
+            velocity: glm::Vec2::new(0.0, 0.0),
+            gravity_scale: 1.0,
+            drop_through: false,
+            contacts: Contacts::default(),
+        }
+    }
+
+    pub fn bottom(&self) -> f32 {
+        self.position.y + self.size.y
+    }
+
+    pub fn right(&self) -> f32 {
+        self.position.x + self.size.x
+    }
+
+    pub fn centre(&self) -> glm::Vec2 {
+        self.position + self.size * 0.5
+    }
+
+    pub fn interpolated_position(&self, alpha: f32) -> glm::Vec2 {
+        self.previous_position + (self.position - self.previous_position) * alpha
+    }
+}
+
+// Advances a body by dt seconds: gravity, then x and y moved separately,
+// each swept across every tile on the way so fast bodies cannot tunnel.
+// Only depends on its inputs, so the same steps always give the same result.
+pub fn step(body: &mut Body, grid: &CollisionGrid, dt: f32) {
+    body.previous_position = body.position;
+    let was_grounded = body.contacts.ground;
+    let was_on_slope = body.contacts.on_slope;
+    body.contacts = Contacts::default();
+
+    body.velocity.y += physics::GRAVITY * body.gravity_scale * dt;
+    body.velocity.y = glm::min(body.velocity.y, physics::MAX_FALL_SPEED);
+
+    move_x(body, grid, body.velocity.x * dt);
+    move_y(body, grid, body.velocity.y * dt);
+    resolve_slopes(body, grid, was_grounded, was_on_slope);
+
+    body.drop_through = false;
+}
+
+fn tile_size() -> f32 {
+    graphics::TILE_PIXELS as f32
+}
+
+// Tiles covering the half open span [start, end)
+fn tile_span(start: f32, end: f32) -> (i32, i32) {
+    let tile = tile_size();
+    let first = (start / tile).floor() as i32;
+    let last = ((end - physics::CONTACT_EPSILON) / tile).floor() as i32;
+    (first, last.max(first))
+}
+
+fn move_x(body: &mut Body, grid: &CollisionGrid, dx: f32) {
+    if dx == 0.0 {
+        return;
+    }
+    let tile = tile_size();
+    let (row_first, mut row_last) = tile_span(body.position.y, body.bottom());
+    // On a slope the feet sink into the row below the surface, which would
+    // otherwise stop the body at the foot of every step up
+    if is_on_slope(body, grid) {
+        row_last = (row_last - 1).max(row_first);
+    }
+
+    if dx > 0.0 {
+        let (first, last) = tile_span(body.right(), body.right() + dx);
+        for column in first..=last {
+            if blocks_horizontally(grid, column, row_first, row_last) {
+                let limit = column as f32 * tile - body.size.x;
+                if body.position.x + dx > limit {
+                    body.position.x = glm::max(body.position.x, limit);
+                    body.velocity.x = 0.0;
+                    body.contacts.wall_right = true;
+                    return;
+                }
+            }
+        }
+    } else {
+        let left = body.position.x;
+        let first = ((left - physics::CONTACT_EPSILON) / tile).floor() as i32;
+        let last = ((left + dx) / tile).floor() as i32;
+        for column in (last..=first).rev() {
+            if blocks_horizontally(grid, column, row_first, row_last) {
+                let limit = (column + 1) as f32 * tile;
+                if body.position.x + dx < limit {
+                    body.position.x = glm::min(body.position.x, limit);
+                    body.velocity.x = 0.0;
+                    body.contacts.wall_left = true;
+                    return;
+                }
+            }
+        }
+    }
+
+    body.position.x += dx;
+}
+
+fn blocks_horizontally(grid: &CollisionGrid, column: i32, row_first: i32, row_last: i32) -> bool {
+    (row_first..=row_last).any(|row| grid.is_solid(column, row))
+}
+
+fn move_y(body: &mut Body, grid: &CollisionGrid, dy: f32) {
+    let tile = tile_size();
+    let (column_first, column_last) = tile_span(body.position.x, body.right());
+
+    if dy > 0.0 {
+        let bottom = body.bottom();
+        let first = (bottom / tile).floor() as i32;
+        let last = ((bottom + dy) / tile).floor() as i32;
+        for row in first..=last {
+            let top = row as f32 * tile;
+            // Anything already overlapped is left to the slope pass
+            if top < bottom - physics::CONTACT_EPSILON {
+                continue;
+            }
+            let lands = (column_first..=column_last).any(|column| match grid.get(column, row) {
+                TileKind::Solid => true,
+                TileKind::OneWay => !body.drop_through,
+                _ => false,
+            });
+            if lands && bottom + dy > top {
+                body.position.y = top - body.size.y;
+                body.velocity.y = 0.0;
+                body.contacts.ground = true;
+                return;
+            }
+        }
+    } else if dy < 0.0 {
+        let top = body.position.y;
+        let first = ((top - physics::CONTACT_EPSILON) / tile).floor() as i32;
+        let last = ((top + dy) / tile).floor() as i32;
+        for row in (last..=first).rev() {
+            let bottom = (row + 1) as f32 * tile;
+            let bumps = (column_first..=column_last).any(|column| grid.is_solid(column, row));
+            if bumps && top + dy < bottom {
+                body.position.y = bottom;
+                body.velocity.y = 0.0;
+                body.contacts.ceiling = true;
+                return;
+            }
+        }
+    }
+
+    body.position.y += dy;
+}
+
+// Height of the floor inside a tile at world x. Solid tiles only count when
+// asked for and when they have open space above them.
+fn floor_surface(
+    grid: &CollisionGrid,
+    column: i32,
+    row: i32,
+    x: f32,
+    include_solid: bool,
+) -> Option<(f32, bool)> {
+    let tile = tile_size();
+    let top = row as f32 * tile;
+    let local_x = glm::clamp(x - column as f32 * tile, 0.0, tile);
+    match grid.get(column, row) {
+        TileKind::SlopeRight => Some((top + tile - local_x, true)),
+        TileKind::SlopeLeft => Some((top + local_x, true)),
+        TileKind::Solid if include_solid && !grid.is_solid(column, row - 1) => Some((top, false)),
+        _ => None,
+    }
+}
+
+// Floor under the middle of the feet, checking the row the feet are in and
+// the one above for when the body has just walked into the next tile.
+// Returns the floor height and whether it is a slope.
+fn floor_under(
+    body: &Body,
+    grid: &CollisionGrid,
+    reach: f32,
+    include_solid: bool,
+) -> Option<(f32, bool)> {
+    let tile = tile_size();
+    let x = body.centre().x;
+    let column = (x / tile).floor() as i32;
+    let feet_row = ((body.bottom() - physics::CONTACT_EPSILON) / tile).floor() as i32;
+    let reach_row = ((body.bottom() + reach) / tile).floor() as i32;
+
+    (feet_row - 1..=reach_row)
+        .filter_map(|row| floor_surface(grid, column, row, x, include_solid))
+        .find(|(surface, _)| *surface >= body.bottom() - tile && *surface <= body.bottom() + reach)
+}
+
+fn is_on_slope(body: &Body, grid: &CollisionGrid) -> bool {
+    floor_under(body, grid, physics::CONTACT_EPSILON, false).is_some()
+}
+
+// Puts bodies back on top of slopes they sank into. Coming off a slope the
+// feet can end up just below the ground next to it, so that counts as well.
+fn resolve_slopes(body: &mut Body, grid: &CollisionGrid, was_grounded: bool, was_on_slope: bool) {
+    if body.velocity.y < 0.0 {
+        return;
+    }
+    // Grounded bodies stick to the slope when walking downhill
+    let reach = if was_grounded {
+        physics::SLOPE_SNAP
+    } else {
+        physics::CONTACT_EPSILON
+    };
+    if let Some((surface, is_slope)) = floor_under(body, grid, reach, was_on_slope) {
+        body.position.y = surface - body.size.y;
+        body.velocity.y = 0.0;
+        body.contacts.ground = true;
+        body.contacts.on_slope = is_slope;
+    }
+}
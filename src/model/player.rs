@@ -0,0 +1,290 @@
+use super::animation::{AnimationSet, Animator, Pose};
+use super::combat::{Combatant, DamageEvent, Fighter, Health, Hitbox, Team};
+use super::geometry::Rect;
+use super::level::CollisionGrid;
+use super::physics::{self, Body};
+use super::state_machine::{Effect, Facts, StateMachine};
+use crate::constants::{gameplay, graphics};
+use crate::platform::input::{Action, ActionState};
+
+const ANIMATIONS: &str = "assets/animations/knight.anim";
+const STATES: &str = "assets/animations/knight.states";
+
+// The Rusty Knight, moved by a state machine read from the states file
+pub struct Player {
+    pub body: Body,
+    pub facing_left: bool,
+    pub animations: AnimationSet,
+    pub animator: Animator,
+    pub fighter: Fighter,
+    // Hit since the last tick
+    pub hurt: bool,
+    // Animation events reached during the last tick
+    pub events: Vec<String>,
+    // Names of the abilities unlocked so far, kept in saves. None of the
+    // knight's moves need unlocking yet.
+    pub abilities: Vec<String>,
+    states: StateMachine,
+    state: usize,
+    // Seconds since entering the state
+    state_time: f32,
+    // Actions pressed since entering the state
+    buffered: Vec<Action>,
+    // Top of the ledge being hung from or climbed
+    ledge: Option<f32>,
+}
+
+impl Player {
+    pub fn load(feet: glm::Vec2) -> Self {
+        Self::new(
+            StateMachine::load(STATES),
+            AnimationSet::load(ANIMATIONS),
+            feet,
+        )
+    }
+
+    // Starts in the first state, standing with the middle of its feet on the given point
+    pub fn new(states: StateMachine, animations: AnimationSet, feet: glm::Vec2) -> Self {
+        let size = glm::Vec2::new(gameplay::KNIGHT_WIDTH, gameplay::KNIGHT_HEIGHT);
+        let body = Body::new(feet - glm::Vec2::new(0.5 * size.x, size.y), size);
+        let animator = Animator::new(&animations, &states.states[0].clip);
+
+        Player {
+            body,
+            facing_left: false,
+            animations,
+            animator,
+            fighter: Fighter::new(
+                Team::Knight,
+                Health::new(gameplay::KNIGHT_HEALTH, gameplay::KNIGHT_RECOVERY),
+            ),
+            hurt: false,
+            events: Vec::new(),
+            abilities: Vec::new(),
+            states,
+            state: 0,
+            state_time: 0.0,
+            buffered: Vec::new(),
+            ledge: None,
+        }
+    }
+
+    pub fn state_name(&self) -> &str {
+        &self.states.states[self.state].name
+    }
+
+    fn facing(&self) -> f32 {
+        if self.facing_left { -1.0 } else { 1.0 }
+    }
+
+    // One fixed tick, dt in seconds
+    pub fn update(&mut self, dt: f32, actions: &ActionState, grid: &CollisionGrid) {
+        for action in Action::ALL {
+            if actions.pressed(action) && !self.buffered.contains(&action) {
+                self.buffered.push(action);
+            }
+        }
+
+        let facts = Facts {
+            grounded: self.body.contacts.ground,
+            moving: actions.axis(Action::MoveLeft, Action::MoveRight) != 0.0,
+            rising: self.body.velocity.y < 0.0,
+            falling: self.body.velocity.y > 0.0,
+            finished: self.animator.finished(),
+            hurt: self.hurt,
+            dead: self.fighter.health.is_dead(),
+            ledge: self.find_ledge(grid).is_some(),
+            over_ledge: self.ledge.is_some_and(|top| self.body.bottom() <= top),
+            headroom: self.fits(grid, gameplay::KNIGHT_HEIGHT),
+            time: self.state_time,
+            actions,
+            buffered: &self.buffered,
+        };
+        if let Some(transition) = self.states.next(self.state, &facts) {
+            let (target, effects) = (transition.target, transition.effects.clone());
+            for effect in effects {
+                self.apply(effect, grid);
+            }
+            self.enter(target);
+        }
+        self.hurt = false;
+
+        let state = &self.states.states[self.state];
+        let axis = actions.axis(Action::MoveLeft, Action::MoveRight);
+        if !state.lock_facing && axis != 0.0 {
+            self.facing_left = axis < 0.0;
+        }
+        self.body.velocity.x = match state.friction {
+            _ if state.dash != 0.0 => self.facing() * state.dash,
+            Some(friction) if state.run == 0.0 => {
+                let speed = glm::max(self.body.velocity.x.abs() - friction * dt, 0.0);
+                self.body.velocity.x.signum() * speed
+            }
+            _ => state.run * axis,
+        };
+        if state.lift > 0.0 {
+            self.body.velocity.y = -state.lift;
+        }
+        self.body.gravity_scale = state.gravity;
+
+        physics::step(&mut self.body, grid, dt);
+        self.events = self.animator.advance(&self.animations, dt);
+        self.fighter.health.tick(dt);
+        self.state_time += dt;
+    }
+
+    fn enter(&mut self, target: usize) {
+        self.state = target;
+        self.state_time = 0.0;
+        self.buffered.clear();
+
+        let state = &self.states.states[target];
+        self.animator.restart(&self.animations, &state.clip);
+        if state.launch > 0.0 {
+            self.body.velocity.y = -state.launch;
+        }
+
+        // Resize around the feet, moving the previous position too so the
+        // interpolated sprite does not jump
+        let height = state.height.unwrap_or(gameplay::KNIGHT_HEIGHT);
+        let shift = self.body.size.y - height;
+        self.body.position.y += shift;
+        self.body.previous_position.y += shift;
+        self.body.size.y = height;
+    }
+
+    fn apply(&mut self, effect: Effect, grid: &CollisionGrid) {
+        match effect {
+            Effect::DropThrough => self.body.drop_through = true,
+            Effect::CutJump => self.body.velocity.y *= 0.5,
+            Effect::GrabLedge => {
+                self.ledge = self.find_ledge(grid);
+                if let Some(top) = self.ledge {
+                    self.body.position.y = top + gameplay::LEDGE_GRAB_HEIGHT;
+                    self.body.velocity = glm::Vec2::new(0.0, 0.0);
+                }
+            }
+            Effect::MountLedge => {
+                if let Some(top) = self.ledge.take() {
+                    self.body.position.y = top - self.body.size.y;
+                    self.body.position.x += self.facing() * self.body.size.x;
+                    self.body.velocity = glm::Vec2::new(0.0, 0.0);
+                }
+            }
+        }
+    }
+
+    // Whether a box of the given height standing where the body stands is clear
+    fn fits(&self, grid: &CollisionGrid, height: f32) -> bool {
+        let max = glm::Vec2::new(self.body.right(), self.body.bottom());
+        let min = glm::Vec2::new(self.body.position.x, max.y - height);
+        grid.tiles_overlapping(min, max)
+            .iter()
+            .all(|(_, _, kind)| !kind.is_solid())
+    }
+
+    // Top of a ledge the hands can reach while falling against a wall, with
+    // room to stand on it
+    fn find_ledge(&self, grid: &CollisionGrid) -> Option<f32> {
+        let against_wall = if self.facing_left {
+            self.body.contacts.wall_left
+        } else {
+            self.body.contacts.wall_right
+        };
+        if !against_wall || self.body.velocity.y < 0.0 {
+            return None;
+        }
+
+        let tile = graphics::TILE_PIXELS as f32;
+        let column = if self.facing_left {
+            (self.body.position.x / tile).floor() as i32 - 1
+        } else {
+            (self.body.right() / tile).floor() as i32
+        };
+        let hands = self.body.position.y - gameplay::LEDGE_GRAB_HEIGHT;
+        let first = ((hands - gameplay::LEDGE_REACH) / tile).ceil() as i32;
+        let last = ((hands + gameplay::LEDGE_REACH) / tile).floor() as i32;
+
+        let row = (first..=last)
+            .find(|row| grid.is_solid(column, *row) && !grid.is_solid(column, row - 1))?;
+        let top = row as f32 * tile;
+
+        let min = glm::Vec2::new(
+            self.body.position.x + self.facing() * self.body.size.x,
+            top - self.body.size.y,
+        );
+        let max = min + self.body.size;
+        grid.tiles_overlapping(min, max)
+            .iter()
+            .all(|(_, _, kind)| !kind.is_solid())
+            .then_some(top)
+    }
+
+    // Where the current frame's top left corner goes for a body at position
+    fn frame_position(&self, position: glm::Vec2) -> glm::Vec2 {
+        let feet = position + glm::Vec2::new(0.5 * self.body.size.x, self.body.size.y);
+        self.animator
+            .origin(&self.animations, feet, self.facing_left)
+    }
+
+    // Current frame at the interpolated position
+    pub fn pose(&self, alpha: f32) -> Pose {
+        let position = self.frame_position(self.body.interpolated_position(alpha));
+
+        let mut pose = self.animator.pose(&self.animations, position);
+        pose.flip_x = self.facing_left;
+        // Faded while it cannot be hurt again
+        if self.fighter.health.is_invulnerable() && !self.fighter.health.is_dead() {
+            pose.opacity = 0.5;
+        }
+        pose
+    }
+}
+
+impl Combatant for Player {
+    fn fighter(&self) -> &Fighter {
+        &self.fighter
+    }
+
+    fn fighter_mut(&mut self) -> &mut Fighter {
+        &mut self.fighter
+    }
+
+    fn hitboxes(&self) -> Vec<Hitbox> {
+        let frame = self.animator.frame(&self.animations);
+        let position = self.frame_position(self.body.position);
+        frame
+            .hitboxes
+            .iter()
+            .map(|hitbox| Hitbox {
+                rect: frame.place(hitbox.rect, position, self.facing_left),
+                ..*hitbox
+            })
+            .collect()
+    }
+
+    fn hurtboxes(&self) -> Vec<Rect> {
+        let frame = self.animator.frame(&self.animations);
+        if frame.hurtboxes.is_empty() {
+            return vec![Rect::new(
+                self.body.position.x,
+                self.body.position.y,
+                self.body.size.x,
+                self.body.size.y,
+            )];
+        }
+        let position = self.frame_position(self.body.position);
+        frame
+            .hurtboxes
+            .iter()
+            .map(|hurtbox| frame.place(*hurtbox, position, self.facing_left))
+            .collect()
+    }
+
+    // Turns to face whoever landed the hit and gets knocked away from them
+    fn take_hit(&mut self, hit: &DamageEvent) {
+        self.hurt = true;
+        self.facing_left = hit.knockback.x > 0.0;
+        self.body.velocity = hit.knockback;
+    }
+}
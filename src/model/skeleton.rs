@@ -0,0 +1,293 @@
+use super::animation::{AnimationSet, Animator, Pose};
+use super::combat::{Combatant, DamageEvent, Fighter, Health, Hitbox, Team};
+use super::geometry::Rect;
+use super::level::{CollisionGrid, TileKind};
+use super::physics::{self, Body};
+use crate::constants::gameplay;
+
+pub const ANIMATIONS: &str = "assets/animations/skeleton.anim";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Behaviour {
+    // Walks back and forth, turning at walls and ledges
+    Patrol,
+    // Heads for the player while it can see them
+    Chase,
+    // Swinging one of its two attacks
+    Attack,
+    // Knocked back by a hit
+    Hit,
+    // Falls apart and then despawns
+    Dead,
+}
+
+// Skeleton swordsman. Skeletons share one animation set, which is passed in
+// instead of each keeping its own.
+pub struct Skeleton {
+    pub body: Body,
+    pub facing_left: bool,
+    pub animator: Animator,
+    pub fighter: Fighter,
+    // Animation events reached during the last tick
+    pub events: Vec<String>,
+    behaviour: Behaviour,
+    // Hit since the last tick
+    hurt: bool,
+    // Seconds since the player was last seen
+    unseen: f32,
+    // Seconds until it can attack again
+    cooldown: f32,
+    // Attacks started so far, A and B take turns
+    attacks: u32,
+    // The current frame's hitboxes in world pixels, placed at the end of each tick
+    hitboxes: Vec<Hitbox>,
+}
+
+impl Skeleton {
+    // Starts patrolling to the right, standing with the middle of its feet on the given point
+    pub fn new(animations: &AnimationSet, feet: glm::Vec2) -> Self {
+        let size = glm::Vec2::new(gameplay::SKELETON_WIDTH, gameplay::SKELETON_HEIGHT);
+
+        Skeleton {
+            body: Body::new(feet - glm::Vec2::new(0.5 * size.x, size.y), size),
+            facing_left: false,
+            animator: Animator::new(animations, "walk"),
+            fighter: Fighter::new(
+                Team::Monsters,
+                Health::new(gameplay::SKELETON_HEALTH, gameplay::SKELETON_RECOVERY),
+            ),
+            events: Vec::new(),
+            behaviour: Behaviour::Patrol,
+            hurt: false,
+            unseen: 0.0,
+            cooldown: 0.0,
+            attacks: 0,
+            hitboxes: Vec::new(),
+        }
+    }
+
+    pub fn behaviour(&self) -> Behaviour {
+        self.behaviour
+    }
+
+    // Once the death clip has played out it can be removed from the world
+    pub fn despawned(&self) -> bool {
+        self.behaviour == Behaviour::Dead && self.animator.finished()
+    }
+
+    fn facing(&self) -> f32 {
+        if self.facing_left { -1.0 } else { 1.0 }
+    }
+
+    // One fixed tick, dt in seconds
+    pub fn update(
+        &mut self,
+        dt: f32,
+        animations: &AnimationSet,
+        grid: &CollisionGrid,
+        player: &Body,
+    ) {
+        self.think(animations, grid, player, dt);
+        self.hurt = false;
+
+        let offset = player.centre() - self.body.centre();
+        self.body.velocity.x = match self.behaviour {
+            Behaviour::Patrol => {
+                if self.body.contacts.ground && self.blocked_ahead(grid) {
+                    self.facing_left = !self.facing_left;
+                }
+                self.facing() * gameplay::SKELETON_PATROL_SPEED
+            }
+            Behaviour::Chase => {
+                if offset.x != 0.0 {
+                    self.facing_left = offset.x < 0.0;
+                }
+                // Waits at the edge rather than following the player off it
+                if self.in_reach(player) || self.blocked_ahead(grid) {
+                    0.0
+                } else {
+                    self.facing() * gameplay::SKELETON_CHASE_SPEED
+                }
+            }
+            Behaviour::Attack => 0.0,
+            Behaviour::Hit | Behaviour::Dead => {
+                let speed = glm::max(
+                    self.body.velocity.x.abs() - gameplay::SKELETON_FRICTION * dt,
+                    0.0,
+                );
+                self.body.velocity.x.signum() * speed
+            }
+        };
+        if self.behaviour == Behaviour::Chase {
+            let clip = if self.body.velocity.x == 0.0 {
+                "idle"
+            } else {
+                "walk"
+            };
+            self.animator.play(animations, clip);
+        }
+
+        physics::step(&mut self.body, grid, dt);
+        self.events = self.animator.advance(animations, dt);
+        self.fighter.health.tick(dt);
+        self.cooldown = glm::max(self.cooldown - dt, 0.0);
+        self.hitboxes = self.place_hitboxes(animations);
+    }
+
+    // Picks what to do this tick
+    fn think(&mut self, animations: &AnimationSet, grid: &CollisionGrid, player: &Body, dt: f32) {
+        if self.behaviour == Behaviour::Dead {
+            return;
+        }
+        if self.fighter.health.is_dead() {
+            self.enter(animations, Behaviour::Dead);
+            return;
+        }
+        if self.hurt {
+            self.enter(animations, Behaviour::Hit);
+            return;
+        }
+
+        match self.behaviour {
+            Behaviour::Patrol => {
+                let ahead = (player.centre().x - self.body.centre().x) * self.facing() > 0.0;
+                if ahead && self.sees(grid, player, gameplay::SKELETON_SIGHT) {
+                    self.enter(animations, Behaviour::Chase);
+                }
+            }
+            Behaviour::Chase => {
+                if self.sees(grid, player, gameplay::SKELETON_PURSUIT) {
+                    self.unseen = 0.0;
+                } else {
+                    self.unseen += dt;
+                }
+                if self.unseen >= gameplay::SKELETON_MEMORY {
+                    self.enter(animations, Behaviour::Patrol);
+                } else if self.cooldown == 0.0 && self.in_reach(player) {
+                    self.enter(animations, Behaviour::Attack);
+                }
+            }
+            Behaviour::Attack | Behaviour::Hit => {
+                if self.animator.finished() {
+                    self.enter(animations, Behaviour::Chase);
+                }
+            }
+            Behaviour::Dead => {}
+        }
+    }
+
+    fn enter(&mut self, animations: &AnimationSet, behaviour: Behaviour) {
+        if self.behaviour == Behaviour::Attack {
+            self.cooldown = gameplay::SKELETON_ATTACK_COOLDOWN;
+        }
+        self.behaviour = behaviour;
+
+        let clip = match behaviour {
+            Behaviour::Patrol | Behaviour::Chase => "walk",
+            Behaviour::Attack => {
+                self.attacks += 1;
+                if self.attacks % 2 == 1 {
+                    "attack_a"
+                } else {
+                    "attack_b"
+                }
+            }
+            Behaviour::Hit => "hit",
+            Behaviour::Dead => "dead",
+        };
+        self.animator.restart(animations, clip);
+        // Whoever hit it is worth chasing straight away
+        self.unseen = 0.0;
+    }
+
+    // Within the given distance of the eyes with nothing solid in the way
+    fn sees(&self, grid: &CollisionGrid, player: &Body, range: f32) -> bool {
+        let eyes = self.body.position + glm::Vec2::new(0.5 * self.body.size.x, 8.0);
+        let target = player.centre();
+        glm::distance(eyes, target) <= range && grid.line_of_sight(eyes, target)
+    }
+
+    fn in_reach(&self, player: &Body) -> bool {
+        let offset = player.centre() - self.body.centre();
+        offset.x.abs() <= gameplay::SKELETON_ATTACK_RANGE
+            && offset.y.abs() < 0.5 * (self.body.size.y + player.size.y)
+    }
+
+    // A wall, a drop or a hazard just in front of the feet
+    fn blocked_ahead(&self, grid: &CollisionGrid) -> bool {
+        let wall = if self.facing_left {
+            self.body.contacts.wall_left
+        } else {
+            self.body.contacts.wall_right
+        };
+        let front = if self.facing_left {
+            self.body.position.x - 1.0
+        } else {
+            self.body.right() + 1.0
+        };
+        let (x, y) = grid.tile_at(glm::Vec2::new(front, self.body.bottom() + 1.0));
+        let below = grid.get(x, y);
+        let ground = below.is_solid() || below.is_slope() || below == TileKind::OneWay;
+
+        wall || !ground || grid.get(x, y - 1) == TileKind::Hazard
+    }
+
+    // Where the current frame's top left corner goes for a body at position
+    fn frame_position(&self, animations: &AnimationSet, position: glm::Vec2) -> glm::Vec2 {
+        let feet = position + glm::Vec2::new(0.5 * self.body.size.x, self.body.size.y);
+        self.animator.origin(animations, feet, self.facing_left)
+    }
+
+    fn place_hitboxes(&self, animations: &AnimationSet) -> Vec<Hitbox> {
+        let frame = self.animator.frame(animations);
+        let position = self.frame_position(animations, self.body.position);
+        frame
+            .hitboxes
+            .iter()
+            .map(|hitbox| Hitbox {
+                rect: frame.place(hitbox.rect, position, self.facing_left),
+                ..*hitbox
+            })
+            .collect()
+    }
+
+    // Current frame at the interpolated position
+    pub fn pose(&self, animations: &AnimationSet, alpha: f32) -> Pose {
+        let position = self.frame_position(animations, self.body.interpolated_position(alpha));
+
+        let mut pose = self.animator.pose(animations, position);
+        pose.flip_x = self.facing_left;
+        pose
+    }
+}
+
+impl Combatant for Skeleton {
+    fn fighter(&self) -> &Fighter {
+        &self.fighter
+    }
+
+    fn fighter_mut(&mut self) -> &mut Fighter {
+        &mut self.fighter
+    }
+
+    fn hitboxes(&self) -> Vec<Hitbox> {
+        self.hitboxes.clone()
+    }
+
+    fn hurtboxes(&self) -> Vec<Rect> {
+        vec![Rect::new(
+            self.body.position.x,
+            self.body.position.y,
+            self.body.size.x,
+            self.body.size.y,
+        )]
+    }
+
+    // Being hit cuts its swing short
+    fn take_hit(&mut self, hit: &DamageEvent) {
+        self.hurt = true;
+        self.facing_left = hit.knockback.x > 0.0;
+        self.body.velocity = hit.knockback;
+        self.hitboxes.clear();
+    }
+}
@@ -0,0 +1,338 @@
+use crate::platform::input::{Action, ActionState};
+use crate::utility::string::split;
+
+// Character states are described in text files. Each state names the clip it
+// plays, how it moves and where it can go next:
+//
+//   any                        transitions below are tried from every state first
+//   state <name>               starts a new state
+//   clip <name>                animation clip to play
+//   run <speed>                input moves the character up to this speed
+//   dash <speed>               moves forwards at this speed, negative for backwards
+//   launch <speed>             upwards speed given on entering the state
+//   lift <speed>               moves upwards at this speed the whole time
+//   gravity <scale>            gravity multiplier, 1 by default
+//   friction <deceleration>    slows down gradually instead of stopping dead when
+//                              neither run nor dash moves the character
+//   height <pixels>            body height while in the state
+//   lock_facing                input cannot turn the character round
+//   to <state> when <conditions> [do <effects>]
+//   reenter <state> when <conditions> [do <effects>]
+//                              like to, but also taken from the state itself,
+//                              which starts it over
+//
+// Transitions are tried top to bottom and the first one whose conditions all
+// hold is taken. Conditions can be negated with a leading !.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Condition {
+    Grounded,
+    Moving,
+    Rising,
+    Falling,
+    // The state's clip has played to the end
+    Finished,
+    Hurt,
+    Dead,
+    // A ledge is within reach while pressing against a wall
+    Ledge,
+    // The feet have come up past the ledge being climbed
+    OverLedge,
+    // There is room to stand up
+    Headroom,
+    Pressed(Action),
+    Held(Action),
+    Released(Action),
+    // Pressed at any point since entering the state
+    Buffered(Action),
+    // Seconds spent in the state
+    After(f32),
+}
+
+impl Condition {
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some((kind, argument)) = name.split_once(':') {
+            if kind == "after" {
+                return argument.parse().ok().map(Condition::After);
+            }
+            let action = Action::from_name(argument)?;
+            return match kind {
+                "pressed" => Some(Condition::Pressed(action)),
+                "held" => Some(Condition::Held(action)),
+                "released" => Some(Condition::Released(action)),
+                "buffered" => Some(Condition::Buffered(action)),
+                _ => None,
+            };
+        }
+
+        match name {
+            "grounded" => Some(Condition::Grounded),
+            "moving" => Some(Condition::Moving),
+            "rising" => Some(Condition::Rising),
+            "falling" => Some(Condition::Falling),
+            "finished" => Some(Condition::Finished),
+            "hurt" => Some(Condition::Hurt),
+            "dead" => Some(Condition::Dead),
+            "ledge" => Some(Condition::Ledge),
+            "over_ledge" => Some(Condition::OverLedge),
+            "headroom" => Some(Condition::Headroom),
+            _ => None,
+        }
+    }
+}
+
+// Done once when a transition is taken, before the new state starts
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Effect {
+    // Falls through the one-way platform underneath
+    DropThrough,
+    // Halves the upwards speed for short hops
+    CutJump,
+    GrabLedge,
+    MountLedge,
+}
+
+impl Effect {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "drop_through" => Some(Effect::DropThrough),
+            "cut_jump" => Some(Effect::CutJump),
+            "grab_ledge" => Some(Effect::GrabLedge),
+            "mount_ledge" => Some(Effect::MountLedge),
+            _ => None,
+        }
+    }
+}
+
+// What the character knows about itself when picking the next state
+pub struct Facts<'a> {
+    pub grounded: bool,
+    pub moving: bool,
+    pub rising: bool,
+    pub falling: bool,
+    pub finished: bool,
+    pub hurt: bool,
+    pub dead: bool,
+    pub ledge: bool,
+    pub over_ledge: bool,
+    pub headroom: bool,
+    pub time: f32,
+    pub actions: &'a ActionState,
+    pub buffered: &'a [Action],
+}
+
+impl Facts<'_> {
+    pub fn holds(&self, condition: Condition) -> bool {
+        match condition {
+            Condition::Grounded => self.grounded,
+            Condition::Moving => self.moving,
+            Condition::Rising => self.rising,
+            Condition::Falling => self.falling,
+            Condition::Finished => self.finished,
+            Condition::Hurt => self.hurt,
+            Condition::Dead => self.dead,
+            Condition::Ledge => self.ledge,
+            Condition::OverLedge => self.over_ledge,
+            Condition::Headroom => self.headroom,
+            Condition::Pressed(action) => self.actions.pressed(action),
+            Condition::Held(action) => self.actions.held(action),
+            Condition::Released(action) => self.actions.released(action),
+            Condition::Buffered(action) => self.buffered.contains(&action),
+            Condition::After(seconds) => self.time >= seconds,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Transition {
+    pub target: usize,
+    // Each condition with the value it needs
+    pub conditions: Vec<(Condition, bool)>,
+    pub effects: Vec<Effect>,
+    // Taken even when already in the target state
+    pub reenter: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct StateDef {
+    pub name: String,
+    pub clip: String,
+    pub run: f32,
+    pub dash: f32,
+    pub launch: f32,
+    pub lift: f32,
+    pub gravity: f32,
+    pub friction: Option<f32>,
+    pub height: Option<f32>,
+    pub lock_facing: bool,
+    pub transitions: Vec<Transition>,
+}
+
+impl StateDef {
+    fn new(name: &str) -> Self {
+        StateDef {
+            name: name.to_string(),
+            clip: name.to_string(),
+            run: 0.0,
+            dash: 0.0,
+            launch: 0.0,
+            lift: 0.0,
+            gravity: 1.0,
+            friction: None,
+            height: None,
+            lock_facing: false,
+            transitions: Vec::new(),
+        }
+    }
+}
+
+pub struct StateMachine {
+    pub states: Vec<StateDef>,
+    pub any: Vec<Transition>,
+}
+
+impl StateMachine {
+    pub fn load(filename: &str) -> Self {
+        let text = std::fs::read_to_string(filename)
+            .unwrap_or_else(|e| panic!("Cannot read states {filename}: {e}"));
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let mut states: Vec<StateDef> = Vec::new();
+        // Targets are names until every state is known. None is the any section.
+        let mut transitions: Vec<(Option<usize>, String, Transition)> = Vec::new();
+        let mut current: Option<Option<usize>> = None;
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let words: Vec<String> = split(line, " ")
+                .into_iter()
+                .filter(|word| !word.is_empty())
+                .collect();
+            let parsed = match (words.as_slice(), current) {
+                ([command], _) if command == "any" => {
+                    current = Some(None);
+                    Some(())
+                }
+                ([command, name], _) if command == "state" => {
+                    states.push(StateDef::new(name));
+                    current = Some(Some(states.len() - 1));
+                    Some(())
+                }
+                ([command, target, rest @ ..], Some(owner))
+                    if command == "to" || command == "reenter" =>
+                {
+                    Self::parse_transition(rest).map(|mut transition| {
+                        transition.reenter = command == "reenter";
+                        transitions.push((owner, target.clone(), transition));
+                    })
+                }
+                (words, Some(Some(state))) => Self::parse_property(&mut states[state], words),
+                _ => None,
+            };
+            if parsed.is_none() {
+                eprintln!("Ignoring states line {}: {line}", number + 1);
+            }
+        }
+
+        let mut machine = StateMachine {
+            states,
+            any: Vec::new(),
+        };
+        for (owner, target, mut transition) in transitions {
+            let Some(index) = machine.state(&target) else {
+                eprintln!("Ignoring transition to unknown state {target}");
+                continue;
+            };
+            transition.target = index;
+            match owner {
+                Some(state) => machine.states[state].transitions.push(transition),
+                None => machine.any.push(transition),
+            }
+        }
+
+        machine
+    }
+
+    fn parse_property(state: &mut StateDef, words: &[String]) -> Option<()> {
+        let number = |word: &String| word.parse::<f32>().ok();
+
+        match words {
+            [command, clip] if command == "clip" => state.clip = clip.clone(),
+            [command, speed] if command == "run" => state.run = number(speed)?,
+            [command, speed] if command == "dash" => state.dash = number(speed)?,
+            [command, speed] if command == "launch" => state.launch = number(speed)?,
+            [command, speed] if command == "lift" => state.lift = number(speed)?,
+            [command, scale] if command == "gravity" => state.gravity = number(scale)?,
+            [command, deceleration] if command == "friction" => {
+                state.friction = Some(number(deceleration)?);
+            }
+            [command, height] if command == "height" => {
+                state.height = Some(number(height).filter(|height| *height > 0.0)?);
+            }
+            [command] if command == "lock_facing" => state.lock_facing = true,
+            _ => return None,
+        }
+
+        Some(())
+    }
+
+    // "when <conditions> [do <effects>]"
+    fn parse_transition(words: &[String]) -> Option<Transition> {
+        let (when, rest) = words.split_first()?;
+        if when != "when" {
+            return None;
+        }
+        let split_at = rest.iter().position(|word| word == "do");
+        let (conditions, effects) = match split_at {
+            Some(i) => (&rest[..i], &rest[i + 1..]),
+            None => (rest, &[][..]),
+        };
+
+        let conditions = conditions
+            .iter()
+            .map(|word| match word.strip_prefix('!') {
+                Some(name) => Condition::from_name(name).map(|condition| (condition, false)),
+                None => Condition::from_name(word).map(|condition| (condition, true)),
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let effects = effects
+            .iter()
+            .map(|word| Effect::from_name(word))
+            .collect::<Option<Vec<_>>>()?;
+        if conditions.is_empty() {
+            return None;
+        }
+
+        Some(Transition {
+            target: 0,
+            conditions,
+            effects,
+            reenter: false,
+        })
+    }
+
+    pub fn state(&self, name: &str) -> Option<usize> {
+        self.states.iter().position(|state| state.name == name)
+    }
+
+    // First transition out of the current state that can be taken, if any.
+    // Transitions back into the current state are only taken when they reenter.
+    pub fn next(&self, current: usize, facts: &Facts) -> Option<&Transition> {
+        self.any
+            .iter()
+            .chain(self.states[current].transitions.iter())
+            .filter(|transition| transition.target != current || transition.reenter)
+            .find(|transition| {
+                transition
+                    .conditions
+                    .iter()
+                    .all(|(condition, expected)| facts.holds(*condition) == *expected)
+            })
+    }
+}
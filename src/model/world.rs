@@ -1,37 +1,111 @@
-use crate::constants;
+use crate::constants::{gameplay, graphics};
 
+use super::animation::AnimationSet;
+use super::boss::{self, Boss, Projectile};
+use super::combat::{self, Combatant, DamageEvent};
+use super::fight::Fight;
 use super::game_object;
-use std::collections::HashMap;
+use super::geometry::Rect;
+use super::level::{Level, TileKind};
+use super::player::Player;
+use super::skeleton::{self, Skeleton};
+use crate::platform::input::ActionState;
 
 pub struct World {
     pub quads: Vec<game_object::Object>,
     pub tris: Vec<game_object::Object>,
     pub camera: game_object::Camera,
-    pub keys: HashMap<glfw::Key, bool>,
+    pub camera_2d: game_object::OrthoCamera,
+    pub level: Level,
+    pub player: Player,
+    // Shared by every skeleton
+    pub skeleton_animations: AnimationSet,
+    pub skeletons: Vec<Skeleton>,
+    // Shared by the boss and its projectiles, which outlive it
+    pub boss_animations: AnimationSet,
+    pub boss: Option<Boss>,
+    pub projectiles: Vec<Projectile>,
+    // Hits landed during the last tick
+    pub damage_events: Vec<DamageEvent>,
+    // Where the player comes back after dying, the middle of their feet in
+    // world pixels, or the level's spawn point when None. Walking into the
+    // boss's arena is the level's checkpoint.
+    pub checkpoint: Option<glm::Vec2>,
+    // The checkpoint was reached during the last tick
+    pub checkpoint_reached: bool,
+    // The boss has died and faded away
+    boss_beaten: bool,
 }
 
 impl World {
-    pub fn new() -> Self {
-        let mut world = World {
+    pub fn new(level: Level) -> Self {
+        Self::resume(level, None)
+    }
+
+    // Starts with the player at the checkpoint, if there is one
+    pub fn resume(level: Level, checkpoint: Option<glm::Vec2>) -> Self {
+        let player = Player::load(checkpoint.unwrap_or(level.spawn_point()));
+        let skeleton_animations = AnimationSet::load(skeleton::ANIMATIONS);
+        let skeletons = level
+            .enemy_spawns()
+            .into_iter()
+            .map(|feet| Skeleton::new(&skeleton_animations, feet))
+            .collect();
+        let boss_animations = AnimationSet::load(boss::ANIMATIONS);
+        let boss = level.boss_spawn().map(|feet| {
+            Boss::new(
+                Fight::load(boss::FIGHT),
+                &boss_animations,
+                &level.collision,
+                feet,
+            )
+        });
+
+        let mut camera_2d = game_object::OrthoCamera::new();
+        camera_2d.set_zoom(gameplay::CAMERA_ZOOM);
+        camera_2d.set_bounds(glm::Vec2::new(0.0, 0.0), level.pixel_size());
+        camera_2d.follow(player.body.centre(), 0.0);
+        camera_2d.previous_position = camera_2d.position;
+
+        World {
             quads: Vec::new(),
             tris: Vec::new(),
             camera: game_object::Camera::new(),
-            keys: HashMap::new(),
-        };
-
-        world.keys.insert(glfw::Key::W, false);
-        world.keys.insert(glfw::Key::A, false);
-        world.keys.insert(glfw::Key::S, false);
-        world.keys.insert(glfw::Key::D, false);
+            camera_2d,
+            level,
+            player,
+            skeleton_animations,
+            skeletons,
+            boss_animations,
+            boss,
+            projectiles: Vec::new(),
+            damage_events: Vec::new(),
+            checkpoint,
+            checkpoint_reached: false,
+            boss_beaten: false,
+        }
+    }
 
-        world
+    // Beat the level's boss, or walked out through an exit
+    pub fn is_won(&self) -> bool {
+        let body = &self.player.body;
+        self.boss_beaten
+            || self.level.collision.any_overlapping(
+                body.position,
+                body.position + body.size,
+                TileKind::Exit,
+            )
     }
 
-    pub fn set_key(&mut self, key: glfw::Key, state: bool) {
-        self.keys.insert(key, state);
+    // The player is dead and has finished falling over
+    pub fn is_lost(&self) -> bool {
+        self.player.fighter.health.is_dead() && self.player.animator.finished()
     }
 
-    pub fn update(&mut self, dt: f32, window: &mut glfw::Window) {
+    // One fixed tick, dt in milliseconds
+    pub fn update(&mut self, dt: f32, actions: &ActionState) {
+        self.camera_2d.previous_position = self.camera_2d.position;
+
         for i in 0..self.tris.len() {
             self.tris[i].angle += 0.001 * dt;
             if self.tris[i].angle > 360.0 {
@@ -39,29 +113,108 @@ impl World {
             }
         }
 
-        let mouse_pos = window.get_cursor_pos();
-        window.set_cursor_pos(640.0, 360.0);
-        let dx = (-40.0 * (mouse_pos.0 - 640.0) / 640.0) as f32;
-        let dy = (-40.0 * (mouse_pos.1 - 360.0) / 360.0) as f32;
+        self.camera.camera_spin(actions.look.x, actions.look.y);
 
-        self.camera.camera_spin(dx, dy);
+        // Removed before anything else so the damage events line up with the
+        // combatants until the next tick: the player, then the skeletons, then
+        // the boss, then its projectiles
+        self.skeletons.retain(|skeleton| !skeleton.despawned());
+        self.projectiles.retain(|projectile| !projectile.gone());
+        if self.boss.as_ref().is_some_and(|boss| boss.despawned()) {
+            self.boss = None;
+            self.boss_beaten = true;
+        }
 
-        let mut d_right: f32 = 0.0;
-        let mut d_forwards: f32 = 0.0;
+        self.player
+            .update(dt / 1000.0, actions, &self.level.collision);
+        for skeleton in &mut self.skeletons {
+            skeleton.update(
+                dt / 1000.0,
+                &self.skeleton_animations,
+                &self.level.collision,
+                &self.player.body,
+            );
+        }
+        if let Some(boss) = &mut self.boss {
+            self.projectiles.extend(boss.update(
+                dt / 1000.0,
+                &self.boss_animations,
+                &mut self.level.collision,
+                &self.player.body,
+            ));
+        }
+        for projectile in &mut self.projectiles {
+            projectile.update(dt / 1000.0, &self.boss_animations, &self.level.collision);
+        }
 
-        if self.keys[&glfw::Key::W] {
-            d_forwards += constants::gameplay::MOVEMENT_SPEED;
+        self.checkpoint_reached = false;
+        if self.checkpoint.is_none()
+            && self
+                .boss
+                .as_ref()
+                .is_some_and(|boss| boss.arena.is_locked())
+        {
+            let body = &self.player.body;
+            self.checkpoint = Some(glm::Vec2::new(body.centre().x, body.bottom()));
+            self.checkpoint_reached = true;
+        }
+
+        let mut combatants: Vec<&mut dyn Combatant> = vec![&mut self.player];
+        for skeleton in &mut self.skeletons {
+            combatants.push(skeleton);
+        }
+        if let Some(boss) = &mut self.boss {
+            combatants.push(boss);
         }
-        if self.keys[&glfw::Key::A] {
-            d_right -= constants::gameplay::MOVEMENT_SPEED;
+        for projectile in &mut self.projectiles {
+            combatants.push(projectile);
         }
-        if self.keys[&glfw::Key::S] {
-            d_forwards -= constants::gameplay::MOVEMENT_SPEED;
+        self.damage_events = combat::exchange_blows(&mut combatants);
+        if let Some(event) = self.hazard_hit() {
+            self.player.fighter.health.damage(event.damage);
+            self.player.take_hit(&event);
+            self.damage_events.push(event);
         }
-        if self.keys[&glfw::Key::D] {
-            d_right += constants::gameplay::MOVEMENT_SPEED;
+
+        self.camera_2d.follow(self.player.body.centre(), dt);
+    }
+
+    // Hazard tiles hurt the player like any other hit, with the same recovery
+    // time afterwards. Monsters keep away from them on their own.
+    fn hazard_hit(&self) -> Option<DamageEvent> {
+        if !self.player.fighter.health.can_be_hurt() {
+            return None;
         }
 
-        self.camera.camera_move(d_right, d_forwards);
+        let tile = graphics::TILE_PIXELS as f32;
+        let grid = &self.level.collision;
+        let (hurtbox, overlap) = self.player.hurtboxes().into_iter().find_map(|hurtbox| {
+            let min = glm::Vec2::new(hurtbox.x, hurtbox.y);
+            let max = min + glm::Vec2::new(hurtbox.w, hurtbox.h);
+            grid.tiles_overlapping(min, max)
+                .into_iter()
+                .filter(|(_, _, kind)| *kind == TileKind::Hazard)
+                .find_map(|(x, y, _)| {
+                    let corner = grid.tile_position(x, y);
+                    let rect = Rect::new(corner.x, corner.y, tile, tile);
+                    hurtbox.overlap(&rect).map(|overlap| (hurtbox, overlap))
+                })
+        })?;
+
+        let away = if hurtbox.centre().x < overlap.centre().x {
+            -1.0
+        } else {
+            1.0
+        };
+        Some(DamageEvent {
+            attacker: 0,
+            target: 0,
+            damage: gameplay::HAZARD_DAMAGE,
+            knockback: glm::Vec2::new(
+                away * gameplay::HAZARD_KNOCKBACK_X,
+                gameplay::HAZARD_KNOCKBACK_Y,
+            ),
+            position: overlap.centre(),
+        })
     }
 }
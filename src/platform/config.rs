@@ -0,0 +1,24 @@
+use std::env;
+use std::path::PathBuf;
+
+// Per-user settings directory: RUSTY_KNIGHT_CONFIG_DIR if set, otherwise the
+// platform's usual place, falling back to ./config next to the game
+pub fn config_dir() -> PathBuf {
+    if let Some(dir) = env::var_os("RUSTY_KNIGHT_CONFIG_DIR") {
+        return PathBuf::from(dir);
+    }
+
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+
+    match base {
+        Some(base) => base.join("rusty_knight"),
+        None => PathBuf::from("config"),
+    }
+}
+
+pub fn config_path(filename: &str) -> PathBuf {
+    config_dir().join(filename)
+}
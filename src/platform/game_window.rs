@@ -1,5 +1,5 @@
 use crate::constants::graphics;
-use glfw::{fail_on_errors, Glfw, GlfwReceiver, PWindow, WindowEvent, WindowHint};
+use glfw::{Glfw, GlfwReceiver, PWindow, WindowEvent, WindowHint, fail_on_errors};
 
 pub struct GameWindow {
     pub glfw: Glfw,
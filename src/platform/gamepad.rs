@@ -0,0 +1,196 @@
+use glfw::{GamepadAxis, GamepadButton, JoystickId};
+
+pub const BUTTON_COUNT: usize = 15;
+pub const AXIS_COUNT: usize = 6;
+
+// Sticks closer to the centre than this read as centred
+pub const STICK_DEADZONE: f32 = 0.25;
+// Analog inputs count as held past this point
+pub const PRESS_THRESHOLD: f32 = 0.5;
+
+// One pad at one moment, in glfw's standard gamepad layout
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GamepadSnapshot {
+    pub buttons: [bool; BUTTON_COUNT],
+    // Sticks from -1 to 1 with y pointing down, triggers from -1 (released) to 1
+    pub axes: [f32; AXIS_COUNT],
+}
+
+impl Default for GamepadSnapshot {
+    fn default() -> Self {
+        let mut axes = [0.0; AXIS_COUNT];
+        axes[GamepadAxis::AxisLeftTrigger as usize] = -1.0;
+        axes[GamepadAxis::AxisRightTrigger as usize] = -1.0;
+        GamepadSnapshot {
+            buttons: [false; BUTTON_COUNT],
+            axes,
+        }
+    }
+}
+
+impl GamepadSnapshot {
+    pub fn button(&self, button: GamepadButton) -> bool {
+        self.buttons[button as usize]
+    }
+
+    // How far an axis is pushed one way, from 0 to 1 with deadzones applied.
+    // Sticks use a radial deadzone so diagonals are not cut off.
+    pub fn axis_value(&self, axis: GamepadAxis, positive: bool) -> f32 {
+        let raw = self.axes[axis as usize];
+        let value = match axis {
+            GamepadAxis::AxisLeftTrigger | GamepadAxis::AxisRightTrigger => {
+                return if positive { 0.5 * (raw + 1.0) } else { 0.0 };
+            }
+            GamepadAxis::AxisLeftX | GamepadAxis::AxisLeftY => {
+                self.stick(GamepadAxis::AxisLeftX, GamepadAxis::AxisLeftY, axis)
+            }
+            GamepadAxis::AxisRightX | GamepadAxis::AxisRightY => {
+                self.stick(GamepadAxis::AxisRightX, GamepadAxis::AxisRightY, axis)
+            }
+        };
+
+        if positive {
+            glm::max(value, 0.0)
+        } else {
+            glm::max(-value, 0.0)
+        }
+    }
+
+    fn stick(&self, x_axis: GamepadAxis, y_axis: GamepadAxis, axis: GamepadAxis) -> f32 {
+        let x = self.axes[x_axis as usize];
+        let y = self.axes[y_axis as usize];
+        let magnitude = (x * x + y * y).sqrt();
+        if magnitude <= STICK_DEADZONE {
+            return 0.0;
+        }
+
+        // Rescale so movement starts from 0 at the edge of the deadzone
+        let scaled = glm::min((magnitude - STICK_DEADZONE) / (1.0 - STICK_DEADZONE), 1.0);
+        self.axes[axis as usize] / magnitude * scaled
+    }
+}
+
+// Where pad states come from: glfw in the game, anything in tests
+pub trait GamepadSource {
+    // Slots that currently have a pad plugged in
+    fn connected(&self) -> Vec<usize>;
+    fn name(&self, slot: usize) -> String;
+    fn snapshot(&self, slot: usize) -> Option<GamepadSnapshot>;
+}
+
+// Nothing plugged in, ever
+pub struct NoGamepads;
+
+impl GamepadSource for NoGamepads {
+    fn connected(&self) -> Vec<usize> {
+        Vec::new()
+    }
+
+    fn name(&self, _slot: usize) -> String {
+        String::new()
+    }
+
+    fn snapshot(&self, _slot: usize) -> Option<GamepadSnapshot> {
+        None
+    }
+}
+
+const JOYSTICKS: [JoystickId; 16] = [
+    JoystickId::Joystick1,
+    JoystickId::Joystick2,
+    JoystickId::Joystick3,
+    JoystickId::Joystick4,
+    JoystickId::Joystick5,
+    JoystickId::Joystick6,
+    JoystickId::Joystick7,
+    JoystickId::Joystick8,
+    JoystickId::Joystick9,
+    JoystickId::Joystick10,
+    JoystickId::Joystick11,
+    JoystickId::Joystick12,
+    JoystickId::Joystick13,
+    JoystickId::Joystick14,
+    JoystickId::Joystick15,
+    JoystickId::Joystick16,
+];
+
+// Joysticks glfw has a gamepad mapping for
+pub struct GlfwGamepads {
+    joysticks: Vec<glfw::Joystick>,
+}
+
+impl GlfwGamepads {
+    pub fn new(glfw: &glfw::Glfw) -> Self {
+        GlfwGamepads {
+            joysticks: JOYSTICKS.iter().map(|id| glfw.get_joystick(*id)).collect(),
+        }
+    }
+}
+
+impl GamepadSource for GlfwGamepads {
+    fn connected(&self) -> Vec<usize> {
+        (0..self.joysticks.len())
+            .filter(|slot| self.joysticks[*slot].is_present() && self.joysticks[*slot].is_gamepad())
+            .collect()
+    }
+
+    fn name(&self, slot: usize) -> String {
+        self.joysticks[slot]
+            .get_gamepad_name()
+            .unwrap_or_else(|| format!("Gamepad {}", slot + 1))
+    }
+
+    fn snapshot(&self, slot: usize) -> Option<GamepadSnapshot> {
+        let state = self.joysticks[slot].get_gamepad_state()?;
+
+        let mut snapshot = GamepadSnapshot::default();
+        for (i, pressed) in snapshot.buttons.iter_mut().enumerate() {
+            let button = GamepadButton::from_i32(i as i32).unwrap();
+            *pressed = state.get_button_state(button) == glfw::Action::Press;
+        }
+        for (i, value) in snapshot.axes.iter_mut().enumerate() {
+            *value = state.get_axis(GamepadAxis::from_i32(i as i32).unwrap());
+        }
+        Some(snapshot)
+    }
+}
+
+// Keeps track of which pads are plugged in and reads all of them each tick
+pub struct Gamepads {
+    source: Box<dyn GamepadSource>,
+    connected: Vec<usize>,
+}
+
+impl Gamepads {
+    pub fn new(source: Box<dyn GamepadSource>) -> Self {
+        Gamepads {
+            source,
+            connected: Vec::new(),
+        }
+    }
+
+    // Picks up pads plugged in or pulled out since the last poll
+    pub fn poll(&mut self) -> Vec<GamepadSnapshot> {
+        let connected = self.source.connected();
+        for slot in &connected {
+            if !self.connected.contains(slot) {
+                eprintln!("Gamepad connected: {}", self.source.name(*slot));
+            }
+        }
+        for slot in &self.connected {
+            if !connected.contains(slot) {
+                eprintln!("Gamepad {} disconnected", slot + 1);
+            }
+        }
+        self.connected = connected;
+
+        self.connected
+            .iter()
+            .filter_map(|slot| self.source.snapshot(*slot))
+            .collect()
+    }
+
+    pub fn connected(&self) -> &[usize] {
+        &self.connected
+    }
+}
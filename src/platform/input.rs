@@ -0,0 +1,477 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use super::gamepad::{self, GamepadSource, Gamepads, NoGamepads};
+use super::keys;
+use crate::utility::string::split;
+use glfw::{GamepadAxis, GamepadButton};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    Jump,
+    Attack,
+    Roll,
+    Pause,
+}
+
+pub const ACTION_COUNT: usize = 8;
+
+impl Action {
+    pub const ALL: [Action; ACTION_COUNT] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::Jump,
+        Action::Attack,
+        Action::Roll,
+        Action::Pause,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    // As written in config files
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "MoveLeft",
+            Action::MoveRight => "MoveRight",
+            Action::MoveUp => "MoveUp",
+            Action::MoveDown => "MoveDown",
+            Action::Jump => "Jump",
+            Action::Attack => "Attack",
+            Action::Roll => "Roll",
+            Action::Pause => "Pause",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.iter().copied().find(|a| a.name() == name)
+    }
+
+    // As shown to players
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "MOVE LEFT",
+            Action::MoveRight => "MOVE RIGHT",
+            Action::MoveUp => "MOVE UP",
+            Action::MoveDown => "MOVE DOWN",
+            Action::Jump => "JUMP",
+            Action::Attack => "ATTACK",
+            Action::Roll => "ROLL",
+            Action::Pause => "PAUSE",
+        }
+    }
+}
+
+// What the game sees of the input, with no trace of where it came from.
+// Advanced once per simulation tick, so every press is seen by exactly one tick.
+// Values go from 0 to 1: keys are all or nothing, sticks and triggers are analog.
+#[derive(Clone)]
+pub struct ActionState {
+    current: [f32; ACTION_COUNT],
+    previous: [f32; ACTION_COUNT],
+    // Mouse look since the last tick, in degrees
+    pub look: glm::Vec2,
+}
+
+impl Default for ActionState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ActionState {
+    pub fn new() -> Self {
+        ActionState {
+            current: [0.0; ACTION_COUNT],
+            previous: [0.0; ACTION_COUNT],
+            look: glm::Vec2::new(0.0, 0.0),
+        }
+    }
+
+    // Latches new action values, keeping the old ones for edges
+    pub fn advance(&mut self, values: [f32; ACTION_COUNT]) {
+        self.previous = self.current;
+        self.current = values;
+    }
+
+    pub fn value(&self, action: Action) -> f32 {
+        self.current[action.index()]
+    }
+
+    pub fn held(&self, action: Action) -> bool {
+        self.current[action.index()] >= gamepad::PRESS_THRESHOLD
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.held(action) && self.previous[action.index()] < gamepad::PRESS_THRESHOLD
+    }
+
+    pub fn released(&self, action: Action) -> bool {
+        !self.held(action) && self.previous[action.index()] >= gamepad::PRESS_THRESHOLD
+    }
+
+    // From -1 to 1, with both directions held cancelling out
+    pub fn axis(&self, negative: Action, positive: Action) -> f32 {
+        self.value(positive) - self.value(negative)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Binding {
+    Key(glfw::Key),
+    Mouse(glfw::MouseButton),
+    PadButton(GamepadButton),
+    // An axis pushed towards its positive or negative end
+    PadAxis(GamepadAxis, bool),
+}
+
+impl Binding {
+    pub fn name(self) -> Option<&'static str> {
+        match self {
+            Binding::Key(key) => keys::key_name(key),
+            Binding::Mouse(button) => keys::mouse_name(button),
+            Binding::PadButton(button) => keys::pad_button_name(button),
+            Binding::PadAxis(axis, positive) => keys::pad_axis_name(axis, positive),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Binding> {
+        keys::key_from_name(name)
+            .map(Binding::Key)
+            .or_else(|| keys::mouse_from_name(name).map(Binding::Mouse))
+            .or_else(|| keys::pad_button_from_name(name).map(Binding::PadButton))
+            .or_else(|| keys::pad_axis_from_name(name).map(|(a, p)| Binding::PadAxis(a, p)))
+    }
+
+    fn is_gamepad(self) -> bool {
+        matches!(self, Binding::PadButton(_) | Binding::PadAxis(..))
+    }
+
+    fn same_device(self, other: Binding) -> bool {
+        matches!(
+            (self, other),
+            (Binding::Key(_), Binding::Key(_)) | (Binding::Mouse(_), Binding::Mouse(_))
+        ) || (self.is_gamepad() && other.is_gamepad())
+    }
+
+    // How far a pad pushes this binding, 0 for keys and mouse buttons
+    fn pad_value(self, pad: &gamepad::GamepadSnapshot) -> f32 {
+        match self {
+            Binding::PadButton(button) => pad.button(button) as i32 as f32,
+            Binding::PadAxis(axis, positive) => pad.axis_value(axis, positive),
+            _ => 0.0,
+        }
+    }
+}
+
+// Physical inputs to actions. An action can have any number of bindings.
+#[derive(Clone)]
+pub struct InputMap {
+    bindings: Vec<(Binding, Action)>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        let mut map = InputMap::new();
+        map.bind(Action::MoveLeft, Binding::Key(glfw::Key::A));
+        map.bind(Action::MoveLeft, Binding::Key(glfw::Key::Left));
+        map.bind(Action::MoveRight, Binding::Key(glfw::Key::D));
+        map.bind(Action::MoveRight, Binding::Key(glfw::Key::Right));
+        map.bind(Action::MoveUp, Binding::Key(glfw::Key::W));
+        map.bind(Action::MoveUp, Binding::Key(glfw::Key::Up));
+        map.bind(Action::MoveDown, Binding::Key(glfw::Key::S));
+        map.bind(Action::MoveDown, Binding::Key(glfw::Key::Down));
+        map.bind(Action::Jump, Binding::Key(glfw::Key::Space));
+        map.bind(Action::Attack, Binding::Key(glfw::Key::J));
+        map.bind(Action::Attack, Binding::Mouse(glfw::MouseButton::Button1));
+        map.bind(Action::Roll, Binding::Key(glfw::Key::K));
+        map.bind(Action::Roll, Binding::Key(glfw::Key::LeftShift));
+        map.bind(Action::Pause, Binding::Key(glfw::Key::Escape));
+
+        map.bind(
+            Action::MoveLeft,
+            Binding::PadAxis(GamepadAxis::AxisLeftX, false),
+        );
+        map.bind(
+            Action::MoveLeft,
+            Binding::PadButton(GamepadButton::ButtonDpadLeft),
+        );
+        map.bind(
+            Action::MoveRight,
+            Binding::PadAxis(GamepadAxis::AxisLeftX, true),
+        );
+        map.bind(
+            Action::MoveRight,
+            Binding::PadButton(GamepadButton::ButtonDpadRight),
+        );
+        map.bind(
+            Action::MoveUp,
+            Binding::PadAxis(GamepadAxis::AxisLeftY, false),
+        );
+        map.bind(
+            Action::MoveUp,
+            Binding::PadButton(GamepadButton::ButtonDpadUp),
+        );
+        map.bind(
+            Action::MoveDown,
+            Binding::PadAxis(GamepadAxis::AxisLeftY, true),
+        );
+        map.bind(
+            Action::MoveDown,
+            Binding::PadButton(GamepadButton::ButtonDpadDown),
+        );
+        map.bind(Action::Jump, Binding::PadButton(GamepadButton::ButtonA));
+        map.bind(Action::Attack, Binding::PadButton(GamepadButton::ButtonX));
+        map.bind(
+            Action::Attack,
+            Binding::PadAxis(GamepadAxis::AxisRightTrigger, true),
+        );
+        map.bind(Action::Roll, Binding::PadButton(GamepadButton::ButtonB));
+        map.bind(
+            Action::Roll,
+            Binding::PadButton(GamepadButton::ButtonRightBumper),
+        );
+        map.bind(
+            Action::Pause,
+            Binding::PadButton(GamepadButton::ButtonStart),
+        );
+        map
+    }
+}
+
+impl InputMap {
+    pub fn new() -> Self {
+        InputMap {
+            bindings: Vec::new(),
+        }
+    }
+
+    pub fn bind(&mut self, action: Action, binding: Binding) {
+        if !self.bindings.contains(&(binding, action)) {
+            self.bindings.push((binding, action));
+        }
+    }
+
+    pub fn unbind(&mut self, action: Action) {
+        self.bindings.retain(|(_, a)| *a != action);
+    }
+
+    // Makes binding the only key (or mouse button) for the action,
+    // taking it away from whatever action had it before
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        self.bindings
+            .retain(|(b, a)| *b != binding && !(*a == action && b.same_device(binding)));
+        self.bindings.push((binding, action));
+    }
+
+    pub fn bindings_for(&self, action: Action) -> Vec<Binding> {
+        self.bindings
+            .iter()
+            .filter(|(_, a)| *a == action)
+            .map(|(b, _)| *b)
+            .collect()
+    }
+
+    pub fn actions_for(&self, binding: Binding) -> Vec<Action> {
+        self.bindings
+            .iter()
+            .filter(|(b, _)| *b == binding)
+            .map(|(_, a)| *a)
+            .collect()
+    }
+
+    // One "bind <action> <key>" line per binding, see keys.rs for key names
+    pub fn to_config(&self) -> String {
+        let mut text = String::from("# Rusty Knight key bindings: bind <action> <key>\n");
+        for action in Action::ALL {
+            let bindings = self.bindings_for(action);
+            // Remembers actions left without a binding, which would otherwise get the defaults back
+            if bindings.is_empty() {
+                text.push_str(&format!("unbind {}\n", action.name()));
+            }
+            for binding in bindings {
+                if let Some(name) = binding.name() {
+                    text.push_str(&format!("bind {} {name}\n", action.name()));
+                }
+            }
+        }
+        text
+    }
+
+    // Actions the file does not mention keep their default bindings
+    pub fn from_config(text: &str) -> Self {
+        let mut map = InputMap::new();
+        let mut mentioned: HashSet<Action> = HashSet::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let words: Vec<String> = split(line, " ")
+                .into_iter()
+                .filter(|word| !word.is_empty())
+                .collect();
+            let parsed = match words.as_slice() {
+                [command, action, binding] if command == "bind" => {
+                    Action::from_name(action).zip(Binding::from_name(binding).map(Some))
+                }
+                [command, action] if command == "unbind" => {
+                    Action::from_name(action).map(|action| (action, None))
+                }
+                _ => None,
+            };
+            match parsed {
+                Some((action, binding)) => {
+                    mentioned.insert(action);
+                    if let Some(binding) = binding {
+                        map.bind(action, binding);
+                    }
+                }
+                None => eprintln!("Ignoring bindings line {}: {line}", number + 1),
+            }
+        }
+
+        let defaults = InputMap::default();
+        for action in Action::ALL {
+            if !mentioned.contains(&action) {
+                for binding in defaults.bindings_for(action) {
+                    map.bind(action, binding);
+                }
+            }
+        }
+
+        map
+    }
+
+    // Falls back to the defaults when there is no file yet
+    pub fn load(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::from_config(&text),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_config())
+    }
+}
+
+// Turns glfw window events into an ActionState
+pub struct Input {
+    pub map: InputMap,
+    down: HashSet<Binding>,
+    // Bindings pressed since the last tick, so a tap shorter than a tick still counts
+    tapped: HashSet<Binding>,
+    look: glm::Vec2,
+    gamepads: Gamepads,
+    state: ActionState,
+}
+
+impl Default for Input {
+    fn default() -> Self {
+        Self::new(InputMap::default())
+    }
+}
+
+impl Input {
+    pub fn new(map: InputMap) -> Self {
+        Input {
+            map,
+            down: HashSet::new(),
+            tapped: HashSet::new(),
+            look: glm::Vec2::new(0.0, 0.0),
+            gamepads: Gamepads::new(Box::new(NoGamepads)),
+            state: ActionState::new(),
+        }
+    }
+
+    pub fn set_gamepad_source(&mut self, source: Box<dyn GamepadSource>) {
+        self.gamepads = Gamepads::new(source);
+    }
+
+    pub fn gamepads(&self) -> &Gamepads {
+        &self.gamepads
+    }
+
+    pub fn handle_event(&mut self, event: &glfw::WindowEvent) {
+        match event {
+            glfw::WindowEvent::Key(key, _, glfw::Action::Press, _) => {
+                self.press(Binding::Key(*key));
+            }
+            glfw::WindowEvent::Key(key, _, glfw::Action::Release, _) => {
+                self.down.remove(&Binding::Key(*key));
+            }
+            glfw::WindowEvent::MouseButton(button, glfw::Action::Press, _) => {
+                self.press(Binding::Mouse(*button));
+            }
+            glfw::WindowEvent::MouseButton(button, glfw::Action::Release, _) => {
+                self.down.remove(&Binding::Mouse(*button));
+            }
+            _ => {}
+        }
+    }
+
+    fn press(&mut self, binding: Binding) {
+        self.down.insert(binding);
+        self.tapped.insert(binding);
+    }
+
+    // Reads how far the hidden cursor moved and puts it back in the middle
+    pub fn poll_cursor(&mut self, window: &mut glfw::Window) {
+        let (width, height) = window.get_size();
+        let centre = (0.5 * width as f64, 0.5 * height as f64);
+        let mouse_pos = window.get_cursor_pos();
+        window.set_cursor_pos(centre.0, centre.1);
+
+        self.look.x += (-40.0 * (mouse_pos.0 - centre.0) / centre.0) as f32;
+        self.look.y += (-40.0 * (mouse_pos.1 - centre.1) / centre.1) as f32;
+    }
+
+    // Call once per simulation tick
+    pub fn tick(&mut self) -> &ActionState {
+        let mut values = [0.0; ACTION_COUNT];
+        for binding in self.down.iter().chain(self.tapped.iter()) {
+            for action in self.map.actions_for(*binding) {
+                values[action.index()] = 1.0;
+            }
+        }
+        self.tapped.clear();
+
+        // Every pad drives the same actions, the strongest input wins
+        let pads = self.gamepads.poll();
+        for (binding, action) in &self.map.bindings {
+            for pad in &pads {
+                let value = binding.pad_value(pad);
+                values[action.index()] = glm::max(values[action.index()], value);
+            }
+        }
+
+        self.state.advance(values);
+        self.state.look = self.look;
+        self.look = glm::Vec2::new(0.0, 0.0);
+
+        &self.state
+    }
+
+    // Forgets taps and mouse look that no tick has seen, e.g. while the game is paused
+    pub fn flush(&mut self) {
+        self.tapped.clear();
+        self.look = glm::Vec2::new(0.0, 0.0);
+    }
+
+    pub fn state(&self) -> &ActionState {
+        &self.state
+    }
+}
@@ -0,0 +1,175 @@
+use glfw::{GamepadAxis, GamepadButton, Key, MouseButton};
+
+// Names used for keys in config files. Only keys listed here can be bound.
+const KEY_NAMES: [(Key, &str); 82] = [
+    (Key::A, "A"),
+    (Key::B, "B"),
+    (Key::C, "C"),
+    (Key::D, "D"),
+    (Key::E, "E"),
+    (Key::F, "F"),
+    (Key::G, "G"),
+    (Key::H, "H"),
+    (Key::I, "I"),
+    (Key::J, "J"),
+    (Key::K, "K"),
+    (Key::L, "L"),
+    (Key::M, "M"),
+    (Key::N, "N"),
+    (Key::O, "O"),
+    (Key::P, "P"),
+    (Key::Q, "Q"),
+    (Key::R, "R"),
+    (Key::S, "S"),
+    (Key::T, "T"),
+    (Key::U, "U"),
+    (Key::V, "V"),
+    (Key::W, "W"),
+    (Key::X, "X"),
+    (Key::Y, "Y"),
+    (Key::Z, "Z"),
+    (Key::Num0, "0"),
+    (Key::Num1, "1"),
+    (Key::Num2, "2"),
+    (Key::Num3, "3"),
+    (Key::Num4, "4"),
+    (Key::Num5, "5"),
+    (Key::Num6, "6"),
+    (Key::Num7, "7"),
+    (Key::Num8, "8"),
+    (Key::Num9, "9"),
+    (Key::F1, "F1"),
+    (Key::F2, "F2"),
+    (Key::F3, "F3"),
+    (Key::F4, "F4"),
+    (Key::F5, "F5"),
+    (Key::F6, "F6"),
+    (Key::F7, "F7"),
+    (Key::F8, "F8"),
+    (Key::F9, "F9"),
+    (Key::F10, "F10"),
+    (Key::F11, "F11"),
+    (Key::F12, "F12"),
+    (Key::Up, "Up"),
+    (Key::Down, "Down"),
+    (Key::Left, "Left"),
+    (Key::Right, "Right"),
+    (Key::Period, "Period"),
+    (Key::Comma, "Comma"),
+    (Key::Slash, "Slash"),
+    (Key::Backslash, "Backslash"),
+    (Key::Semicolon, "Semicolon"),
+    (Key::Apostrophe, "Apostrophe"),
+    (Key::LeftBracket, "LeftBracket"),
+    (Key::RightBracket, "RightBracket"),
+    (Key::Equal, "Equal"),
+    (Key::Minus, "Minus"),
+    (Key::GraveAccent, "GraveAccent"),
+    (Key::Tab, "Tab"),
+    (Key::Escape, "Escape"),
+    (Key::PrintScreen, "PrintScreen"),
+    (Key::Backspace, "Backspace"),
+    (Key::LeftShift, "LeftShift"),
+    (Key::RightShift, "RightShift"),
+    (Key::PageUp, "PageUp"),
+    (Key::PageDown, "PageDown"),
+    (Key::Enter, "Enter"),
+    (Key::LeftControl, "LeftControl"),
+    (Key::RightControl, "RightControl"),
+    (Key::LeftAlt, "LeftAlt"),
+    (Key::RightAlt, "RightAlt"),
+    (Key::Space, "Space"),
+    (Key::Insert, "Insert"),
+    (Key::Delete, "Delete"),
+    (Key::End, "End"),
+    (Key::Home, "Home"),
+    (Key::Pause, "Pause"),
+];
+
+const MOUSE_NAMES: [(MouseButton, &str); 3] = [
+    (MouseButton::Button1, "MouseLeft"),
+    (MouseButton::Button2, "MouseRight"),
+    (MouseButton::Button3, "MouseMiddle"),
+];
+
+const PAD_BUTTON_NAMES: [(GamepadButton, &str); 15] = [
+    (GamepadButton::ButtonA, "PadA"),
+    (GamepadButton::ButtonB, "PadB"),
+    (GamepadButton::ButtonX, "PadX"),
+    (GamepadButton::ButtonY, "PadY"),
+    (GamepadButton::ButtonLeftBumper, "PadLeftBumper"),
+    (GamepadButton::ButtonRightBumper, "PadRightBumper"),
+    (GamepadButton::ButtonBack, "PadBack"),
+    (GamepadButton::ButtonStart, "PadStart"),
+    (GamepadButton::ButtonGuide, "PadGuide"),
+    (GamepadButton::ButtonLeftThumb, "PadLeftThumb"),
+    (GamepadButton::ButtonRightThumb, "PadRightThumb"),
+    (GamepadButton::ButtonDpadUp, "PadUp"),
+    (GamepadButton::ButtonDpadRight, "PadRight"),
+    (GamepadButton::ButtonDpadDown, "PadDown"),
+    (GamepadButton::ButtonDpadLeft, "PadLeft"),
+];
+
+// Sticks have a name per direction, triggers only go one way
+const PAD_AXIS_NAMES: [(GamepadAxis, bool, &str); 10] = [
+    (GamepadAxis::AxisLeftX, false, "PadLeftStickLeft"),
+    (GamepadAxis::AxisLeftX, true, "PadLeftStickRight"),
+    (GamepadAxis::AxisLeftY, false, "PadLeftStickUp"),
+    (GamepadAxis::AxisLeftY, true, "PadLeftStickDown"),
+    (GamepadAxis::AxisRightX, false, "PadRightStickLeft"),
+    (GamepadAxis::AxisRightX, true, "PadRightStickRight"),
+    (GamepadAxis::AxisRightY, false, "PadRightStickUp"),
+    (GamepadAxis::AxisRightY, true, "PadRightStickDown"),
+    (GamepadAxis::AxisLeftTrigger, true, "PadLeftTrigger"),
+    (GamepadAxis::AxisRightTrigger, true, "PadRightTrigger"),
+];
+
+pub fn key_name(key: Key) -> Option<&'static str> {
+    KEY_NAMES.iter().find(|(k, _)| *k == key).map(|(_, n)| *n)
+}
+
+pub fn key_from_name(name: &str) -> Option<Key> {
+    KEY_NAMES.iter().find(|(_, n)| *n == name).map(|(k, _)| *k)
+}
+
+pub fn mouse_name(button: MouseButton) -> Option<&'static str> {
+    MOUSE_NAMES
+        .iter()
+        .find(|(b, _)| *b == button)
+        .map(|(_, n)| *n)
+}
+
+pub fn mouse_from_name(name: &str) -> Option<MouseButton> {
+    MOUSE_NAMES
+        .iter()
+        .find(|(_, n)| *n == name)
+        .map(|(b, _)| *b)
+}
+
+pub fn pad_button_name(button: GamepadButton) -> Option<&'static str> {
+    PAD_BUTTON_NAMES
+        .iter()
+        .find(|(b, _)| *b == button)
+        .map(|(_, n)| *n)
+}
+
+pub fn pad_button_from_name(name: &str) -> Option<GamepadButton> {
+    PAD_BUTTON_NAMES
+        .iter()
+        .find(|(_, n)| *n == name)
+        .map(|(b, _)| *b)
+}
+
+pub fn pad_axis_name(axis: GamepadAxis, positive: bool) -> Option<&'static str> {
+    PAD_AXIS_NAMES
+        .iter()
+        .find(|(a, p, _)| *a == axis && *p == positive)
+        .map(|(_, _, n)| *n)
+}
+
+pub fn pad_axis_from_name(name: &str) -> Option<(GamepadAxis, bool)> {
+    PAD_AXIS_NAMES
+        .iter()
+        .find(|(_, _, n)| *n == name)
+        .map(|(a, p, _)| (*a, *p))
+}
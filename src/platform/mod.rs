@@ -1,2 +1,5 @@
+pub mod config;
 pub mod game_window;
+pub mod gamepad;
 pub mod input;
+pub mod keys;
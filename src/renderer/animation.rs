@@ -0,0 +1,24 @@
+use super::renderer::State;
+use super::sprite_batch::Sprite;
+use crate::model::animation::{AnimationSet, Animator};
+
+// The drawing side of the animations, which live with the rest of the
+// gameplay in model::animation
+
+impl AnimationSet {
+    pub fn load_textures(&mut self, state: &mut State) {
+        self.textures = self
+            .images
+            .iter()
+            .map(|image| state.load_sprite_texture(image))
+            .collect();
+    }
+}
+
+impl Animator {
+    // The current frame ready for the sprite batch, needs the set's textures loaded
+    pub fn sprite(&self, set: &AnimationSet, position: glm::Vec2) -> Sprite {
+        let frame = self.frame(set);
+        Sprite::new(set.textures[frame.image], frame.source, position)
+    }
+}
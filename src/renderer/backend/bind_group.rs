@@ -37,8 +37,8 @@ impl<'a> Builder<'a> {
         self.entries.push(wgpu::BindGroupEntry {
             binding: self.entries.len() as u32,
             resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
-                buffer: &buffer,
-                offset: offset,
+                buffer,
+                offset,
                 size: None,
             }),
         })
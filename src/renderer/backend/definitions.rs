@@ -10,6 +10,14 @@ pub enum PipelineType {
     Simple,
     TexturedModel,
     ColoredModel,
+    Sprite,
+    Blit,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CameraKind {
+    Perspective,
+    Orthographic,
 }
 
 pub struct Material {
@@ -19,6 +27,12 @@ pub struct Material {
     pub bind_group: Option<wgpu::BindGroup>,
 }
 
+impl Default for Material {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Material {
     pub fn new() -> Self {
         Material {
@@ -84,6 +98,56 @@ impl ModelVertex {
     }
 }
 
+#[repr(C)] // C-style data layout
+pub struct SpriteVertex {
+    pub position: glm::Vec2,
+    pub tex_coord: glm::Vec2,
+    pub color: glm::Vec4,
+}
+
+impl SpriteVertex {
+    pub fn get_layout() -> wgpu::VertexBufferLayout<'static> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
+            0 => Float32x2,
+            1 => Float32x2,
+            2 => Float32x4];
+
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SpriteVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+pub struct SpriteTexture {
+    pub bind_group: wgpu::BindGroup,
+    pub width: u32,
+    pub height: u32,
+}
+
+// Per-instance transform, read by the vertex shader at locations 4 to 7
+#[repr(C)] // C-style data layout
+pub struct InstanceData {
+    pub model: glm::Mat4,
+}
+
+impl InstanceData {
+    pub fn get_layout() -> wgpu::VertexBufferLayout<'static> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+            4 => Float32x4,
+            5 => Float32x4,
+            6 => Float32x4,
+            7 => Float32x4];
+
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<InstanceData>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
 pub struct Mesh {
     pub buffer: wgpu::Buffer,
     pub offset: u64,
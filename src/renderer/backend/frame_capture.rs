@@ -0,0 +1,81 @@
+use std::sync::mpsc;
+
+pub fn read_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> Result<image::RgbaImage, wgpu::BufferAsyncError> {
+    let width = texture.width();
+    let height = texture.height();
+
+    // Rows in the readback buffer have to be padded to the copy alignment
+    let unpadded_bytes_per_row = 4 * width;
+    let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(alignment) * alignment;
+
+    let buffer_descriptor = wgpu::BufferDescriptor {
+        label: Some("Frame Capture Buffer"),
+        size: (padded_bytes_per_row * height) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    };
+    let buffer = device.create_buffer(&buffer_descriptor);
+
+    let command_encoder_descriptor = wgpu::CommandEncoderDescriptor {
+        label: Some("Frame Capture Encoder"),
+    };
+    let mut command_encoder = device.create_command_encoder(&command_encoder_descriptor);
+    command_encoder.copy_texture_to_buffer(
+        wgpu::TexelCopyTextureInfo {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit(std::iter::once(command_encoder.finish()));
+
+    let slice = buffer.slice(..);
+    // The mapping result comes back through the callback, a callback that never ran counts as failed
+    let (sender, receiver) = mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        sender.send(result).ok();
+    });
+    device.poll(wgpu::MaintainBase::Wait).ok();
+    receiver.recv().unwrap_or(Err(wgpu::BufferAsyncError))?;
+
+    let swap_red_blue = matches!(
+        texture.format(),
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+    );
+
+    let mut pixels: Vec<u8> = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+    {
+        let data = slice.get_mapped_range();
+        for row in data.chunks(padded_bytes_per_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+        }
+    }
+    buffer.unmap();
+
+    if swap_red_blue {
+        for pixel in pixels.chunks_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+
+    Ok(image::RgbaImage::from_raw(width, height, pixels).unwrap())
+}
@@ -0,0 +1,49 @@
+use super::definitions::InstanceData;
+use super::mesh_builder::vec_to_u8_slice;
+
+pub struct InstanceBuffer {
+    pub buffer: wgpu::Buffer,
+    capacity: usize,
+}
+
+impl InstanceBuffer {
+    pub fn new(device: &wgpu::Device, capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let buffer = Self::create_buffer(device, capacity);
+
+        Self { buffer, capacity }
+    }
+
+    fn create_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        let buffer_descriptor = wgpu::BufferDescriptor {
+            label: Some("Instance buffer"),
+            size: (capacity * std::mem::size_of::<InstanceData>()) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        };
+        device.create_buffer(&buffer_descriptor)
+    }
+
+    // Grows the buffer (doubling) whenever more instances are uploaded than it can hold
+    pub fn upload(
+        &mut self,
+        instances: &[InstanceData],
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        if instances.is_empty() {
+            return;
+        }
+
+        if instances.len() > self.capacity {
+            while self.capacity < instances.len() {
+                self.capacity *= 2;
+            }
+            self.buffer.destroy();
+            self.buffer = Self::create_buffer(device, self.capacity);
+        }
+
+        let data: &[u8] = vec_to_u8_slice(instances);
+        queue.write_buffer(&self.buffer, 0, data);
+    }
+}
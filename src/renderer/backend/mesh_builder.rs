@@ -1,4 +1,4 @@
-use std::{collections::HashMap, env::current_dir, fs};
+use std::{collections::HashMap, fs, path::Path};
 
 use super::definitions::{self, Mesh, Vertex};
 use crate::utility::string;
@@ -10,13 +10,8 @@ pub fn any_as_u8_slice<T: Sized>(p: &T) -> &[u8] {
     }
 }
 
-pub fn vec_to_u8_slice<T: Sized>(p: &Vec<T>) -> &[u8] {
-    unsafe {
-        ::core::slice::from_raw_parts(
-            (p.as_ptr() as *const T) as *const u8,
-            p.len() * ::core::mem::size_of::<T>(),
-        )
-    }
+pub fn vec_to_u8_slice<T: Sized>(p: &[T]) -> &[u8] {
+    unsafe { ::core::slice::from_raw_parts(p.as_ptr() as *const u8, ::core::mem::size_of_val(p)) }
 }
 
 pub fn make_triangle(device: &wgpu::Device) -> wgpu::Buffer {
@@ -97,6 +92,12 @@ pub struct ObjLoader {
     current_submesh: definitions::Submesh,
 }
 
+impl Default for ObjLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ObjLoader {
     pub fn new() -> Self {
         ObjLoader {
@@ -132,25 +133,20 @@ impl ObjLoader {
         };
     }
 
+    // The material library is looked for beside the model file
     pub fn load(
         &mut self,
-        filename: &str,
+        path: &Path,
         materials: &mut Vec<definitions::Material>,
         device: &wgpu::Device,
         pre_transform: &glm::Mat4,
     ) -> definitions::Model {
-        self.parse_materials(filename, materials);
-        self.load_obj(device, filename, pre_transform)
+        self.parse_materials(path, materials);
+        self.load_obj(device, path, pre_transform)
     }
 
-    fn parse_materials(&mut self, filename: &str, materials: &mut Vec<definitions::Material>) {
-        let mut full_filepath = current_dir().unwrap();
-        full_filepath.push("assets/");
-        full_filepath.push("models/");
-        full_filepath.push(filename);
-        let mut filepath_str = full_filepath.into_os_string().into_string().unwrap();
-
-        let full_contents = fs::read_to_string(filepath_str).expect("Cannot read model file!");
+    fn parse_materials(&mut self, path: &Path, materials: &mut Vec<definitions::Material>) {
+        let full_contents = fs::read_to_string(path).expect("Cannot read model file!");
         let mut token: &str = "\n";
 
         let lines = string::split(&full_contents, token);
@@ -166,13 +162,8 @@ impl ObjLoader {
             }
         }
 
-        full_filepath = current_dir().unwrap();
-        full_filepath.push("assets/");
-        full_filepath.push("models/");
-        full_filepath.push(mtl_filename);
-        filepath_str = full_filepath.into_os_string().into_string().unwrap();
-
-        let full_contents = fs::read_to_string(filepath_str).expect("Cannot read material file!");
+        let mtl_path = path.with_file_name(mtl_filename);
+        let full_contents = fs::read_to_string(mtl_path).expect("Cannot read material file!");
         token = "\n";
 
         let lines = string::split(&full_contents, token);
@@ -232,19 +223,13 @@ impl ObjLoader {
     fn load_obj(
         &mut self,
         device: &wgpu::Device,
-        filename: &str,
+        path: &Path,
         pre_transform: &glm::Mat4,
     ) -> definitions::Model {
         let mut submeshes: Vec<definitions::Submesh> = Vec::new();
         self.recording = false;
 
-        let mut full_filepath = current_dir().unwrap();
-        full_filepath.push("assets/");
-        full_filepath.push("models/");
-        full_filepath.push(filename);
-        let filepath_str = full_filepath.into_os_string().into_string().unwrap();
-
-        let full_contents = fs::read_to_string(filepath_str).expect("Cannot read model file!");
+        let full_contents = fs::read_to_string(path).expect("Cannot read model file!");
         let mut token: &str = "\n";
 
         let lines = string::split(&full_contents, token);
@@ -287,7 +272,7 @@ impl ObjLoader {
         model
     }
 
-    fn read_v(&mut self, words: &Vec<String>, pre_transform: &glm::Mat4) {
+    fn read_v(&mut self, words: &[String], pre_transform: &glm::Mat4) {
         let x: f32 = words[1].parse().unwrap();
         let y: f32 = words[2].parse().unwrap();
         let z: f32 = words[3].parse().unwrap();
@@ -296,14 +281,14 @@ impl ObjLoader {
         self.v.push(pos);
     }
 
-    fn read_vt(&mut self, words: &Vec<String>) {
+    fn read_vt(&mut self, words: &[String]) {
         let u: f32 = words[1].parse().unwrap();
         let v: f32 = words[2].parse().unwrap();
         let tex_coord = glm::Vec2::new(u, 1.0 - v);
         self.vt.push(tex_coord);
     }
 
-    fn read_vn(&mut self, words: &Vec<String>, pre_transform: &glm::Mat4) {
+    fn read_vn(&mut self, words: &[String], pre_transform: &glm::Mat4) {
         let x: f32 = words[1].parse().unwrap();
         let y: f32 = words[2].parse().unwrap();
         let z: f32 = words[3].parse().unwrap();
@@ -312,17 +297,12 @@ impl ObjLoader {
         self.vn.push(normal);
     }
 
-    fn start_new_submesh(
-        &mut self,
-        words: &Vec<String>,
-        submeshes: &mut Vec<definitions::Submesh>,
-    ) {
+    fn start_new_submesh(&mut self, words: &[String], submeshes: &mut Vec<definitions::Submesh>) {
         //println!("New submesh: {}", words[1]);
 
         if self.recording {
             submeshes.push(self.current_submesh);
-            self.current_submesh.first_index =
-                self.current_submesh.first_index + self.current_submesh.index_count as i32;
+            self.current_submesh.first_index += self.current_submesh.index_count as i32;
             self.current_submesh.index_count = 0;
         }
 
@@ -330,7 +310,7 @@ impl ObjLoader {
         self.recording = true;
     }
 
-    fn read_f(&mut self, words: &Vec<String>) {
+    fn read_f(&mut self, words: &[String]) {
         let triangle_count = words.len() - 3;
 
         for i in 0..triangle_count {
@@ -372,7 +352,7 @@ impl ObjLoader {
             tex_coord: self.vt[j],
             normal: self.vn[k],
         });
-        self.current_submesh.index_count = self.current_submesh.index_count + 1;
+        self.current_submesh.index_count += 1;
     }
 
     fn finalize(&mut self, device: &wgpu::Device) -> definitions::Model {
@@ -1,6 +1,8 @@
 pub mod bind_group;
 pub mod bind_group_layout;
 pub mod definitions;
+pub mod frame_capture;
+pub mod instance_buffer;
 pub mod mesh_builder;
 pub mod pipeline;
 pub mod texture;
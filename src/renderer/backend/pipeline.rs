@@ -6,6 +6,11 @@ pub struct Builder<'a> {
     vertex_entry: String,
     fragment_entry: String,
     pixel_format: wgpu::TextureFormat,
+    blend_state: wgpu::BlendState,
+    cull_mode: Option<wgpu::Face>,
+    depth_write_enabled: bool,
+    depth_compare: wgpu::CompareFunction,
+    depth_enabled: bool,
     vertex_buffer_layouts: Vec<wgpu::VertexBufferLayout<'static>>,
     bind_group_layouts: Vec<&'a wgpu::BindGroupLayout>,
     device: &'a wgpu::Device,
@@ -18,6 +23,11 @@ impl<'a> Builder<'a> {
             vertex_entry: "dummy".to_string(),
             fragment_entry: "dummy".to_string(),
             pixel_format: wgpu::TextureFormat::Rgba8Unorm,
+            blend_state: wgpu::BlendState::REPLACE,
+            cull_mode: Some(wgpu::Face::Back),
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            depth_enabled: true,
             vertex_buffer_layouts: Vec::new(),
             bind_group_layouts: Vec::new(),
             device,
@@ -27,6 +37,11 @@ impl<'a> Builder<'a> {
     fn reset(&mut self) {
         self.vertex_buffer_layouts.clear();
         self.bind_group_layouts.clear();
+        self.blend_state = wgpu::BlendState::REPLACE;
+        self.cull_mode = Some(wgpu::Face::Back);
+        self.depth_write_enabled = true;
+        self.depth_compare = wgpu::CompareFunction::Less;
+        self.depth_enabled = true;
     }
 
     pub fn add_vertex_buffer_layout(&mut self, layout: wgpu::VertexBufferLayout<'static>) {
@@ -52,6 +67,24 @@ impl<'a> Builder<'a> {
         self.pixel_format = pixel_format;
     }
 
+    pub fn set_blend_state(&mut self, blend_state: wgpu::BlendState) {
+        self.blend_state = blend_state;
+    }
+
+    pub fn set_cull_mode(&mut self, cull_mode: Option<wgpu::Face>) {
+        self.cull_mode = cull_mode;
+    }
+
+    pub fn set_depth_test(&mut self, write_enabled: bool, compare: wgpu::CompareFunction) {
+        self.depth_write_enabled = write_enabled;
+        self.depth_compare = compare;
+    }
+
+    // For passes that have no depth attachment at all
+    pub fn set_depth_enabled(&mut self, enabled: bool) {
+        self.depth_enabled = enabled;
+    }
+
     pub fn build(&mut self, label: &str) -> wgpu::RenderPipeline {
         let mut filepath = current_dir().unwrap();
         filepath.push("src/");
@@ -76,14 +109,14 @@ impl<'a> Builder<'a> {
 
         let render_targets = [Some(wgpu::ColorTargetState {
             format: self.pixel_format,
-            blend: Some(wgpu::BlendState::REPLACE),
+            blend: Some(self.blend_state),
             write_mask: wgpu::ColorWrites::ALL,
         })];
 
         let depth_stencil = wgpu::DepthStencilState {
             format: wgpu::TextureFormat::Depth32Float,
-            depth_write_enabled: true,
-            depth_compare: wgpu::CompareFunction::Less,
+            depth_write_enabled: self.depth_write_enabled,
+            depth_compare: self.depth_compare,
             stencil: wgpu::StencilState::default(),
             bias: wgpu::DepthBiasState::default(),
         };
@@ -105,7 +138,7 @@ impl<'a> Builder<'a> {
                 topology: wgpu::PrimitiveTopology::TriangleList,
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
+                cull_mode: self.cull_mode,
                 polygon_mode: wgpu::PolygonMode::Fill,
                 unclipped_depth: false,
                 conservative: false,
@@ -118,7 +151,7 @@ impl<'a> Builder<'a> {
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
             }),
 
-            depth_stencil: Some(depth_stencil),
+            depth_stencil: self.depth_enabled.then_some(depth_stencil),
             multisample: wgpu::MultisampleState {
                 count: 1,
                 mask: !0,
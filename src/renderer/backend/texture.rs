@@ -5,6 +5,7 @@ use wgpu::util::DeviceExt;
 use glm::Vec4;
 
 use super::bind_group;
+use super::definitions::SpriteTexture;
 
 pub struct Texture {
     pub texture: wgpu::Texture,
@@ -39,6 +40,58 @@ pub fn new_depth_texture(
     Texture { texture, view }
 }
 
+pub fn new_color_texture(
+    device: &wgpu::Device,
+    config: &wgpu::SurfaceConfiguration,
+    label: &str,
+) -> Texture {
+    let size = wgpu::Extent3d {
+        width: config.width.max(1),
+        height: config.height.max(1),
+        depth_or_array_layers: 1,
+    };
+
+    let descriptor = wgpu::TextureDescriptor {
+        label: Some(label),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: config.usage,
+        view_formats: &[],
+    };
+    let texture = device.create_texture(&descriptor);
+
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    Texture { texture, view }
+}
+
+// Samples a render target without filtering, so upscaled pixels stay square
+pub fn new_render_target_material(
+    target: &Texture,
+    device: &wgpu::Device,
+    label: &str,
+    layout: &wgpu::BindGroupLayout,
+) -> wgpu::BindGroup {
+    let sampler_descriptor = wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: wgpu::FilterMode::Nearest,
+        min_filter: wgpu::FilterMode::Nearest,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        ..Default::default()
+    };
+    let sampler = device.create_sampler(&sampler_descriptor);
+
+    let mut builder = bind_group::Builder::new(device);
+    builder.set_layout(layout);
+    builder.add_material(&target.view, &sampler);
+    builder.build(label)
+}
+
 pub fn new_texture(
     filename: &str,
     device: &wgpu::Device,
@@ -56,8 +109,102 @@ pub fn new_texture(
     let bytes = std::fs::read(filepath).unwrap();
     let loaded_image = image::load_from_memory(&bytes).unwrap();
     let converted = loaded_image.to_rgba8();
-    use image::GenericImageView;
-    let size = loaded_image.dimensions();
+    let texture = upload_image(
+        &converted,
+        wgpu::TextureFormat::Rgba8Unorm,
+        device,
+        queue,
+        label,
+    );
+
+    // Get a view of the texture
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    // Make a sampler
+    let sampler_descriptor = wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::Repeat,
+        address_mode_v: wgpu::AddressMode::Repeat,
+        address_mode_w: wgpu::AddressMode::Repeat,
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Nearest,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        ..Default::default()
+    };
+    let sampler = device.create_sampler(&sampler_descriptor);
+
+    // Make a bind group for everything
+    let mut builder = bind_group::Builder::new(device);
+    builder.set_layout(layout);
+    builder.add_material(&view, &sampler);
+    builder.build(label)
+}
+
+// Sprite sheets are addressed relative to the crate root, e.g. "assets/sprites/..."
+pub fn new_sprite_texture(
+    filename: &str,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    label: &str,
+    layout: &wgpu::BindGroupLayout,
+) -> SpriteTexture {
+    let mut filepath = current_dir().unwrap();
+    filepath.push(filename);
+
+    let bytes = std::fs::read(&filepath)
+        .unwrap_or_else(|e| panic!("Cannot read sprite sheet {}: {e}", filepath.display()));
+    let converted = image::load_from_memory(&bytes).unwrap().to_rgba8();
+
+    new_sprite_texture_from_image(&converted, device, queue, label, layout)
+}
+
+pub fn new_sprite_texture_from_image(
+    image: &image::RgbaImage,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    label: &str,
+    layout: &wgpu::BindGroupLayout,
+) -> SpriteTexture {
+    let texture = upload_image(
+        image,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+        device,
+        queue,
+        label,
+    );
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    // Pixel art: no filtering, and no bleeding in from the opposite edge of the sheet
+    let sampler_descriptor = wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: wgpu::FilterMode::Nearest,
+        min_filter: wgpu::FilterMode::Nearest,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        ..Default::default()
+    };
+    let sampler = device.create_sampler(&sampler_descriptor);
+
+    let mut builder = bind_group::Builder::new(device);
+    builder.set_layout(layout);
+    builder.add_material(&view, &sampler);
+    let bind_group = builder.build(label);
+
+    SpriteTexture {
+        bind_group,
+        width: image.width(),
+        height: image.height(),
+    }
+}
+
+fn upload_image(
+    image: &image::RgbaImage,
+    format: wgpu::TextureFormat,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    label: &str,
+) -> wgpu::Texture {
+    let size = image.dimensions();
     let texture_size = wgpu::Extent3d {
         width: size.0,
         height: size.1,
@@ -70,10 +217,10 @@ pub fn new_texture(
         mip_level_count: 1,
         sample_count: 1,
         dimension: wgpu::TextureDimension::D2,
-        format: wgpu::TextureFormat::Rgba8Unorm,
+        format,
         usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
         label: Some(label),
-        view_formats: &[wgpu::TextureFormat::Rgba8Unorm],
+        view_formats: &[format],
     };
     let texture = device.create_texture(&texture_descriptor);
 
@@ -85,7 +232,7 @@ pub fn new_texture(
             origin: wgpu::Origin3d::ZERO,
             aspect: wgpu::TextureAspect::All,
         },
-        &converted,
+        image,
         wgpu::TexelCopyBufferLayout {
             offset: 0,
             bytes_per_row: Some(4 * size.0),
@@ -94,28 +241,7 @@ pub fn new_texture(
         texture_size,
     );
 
-    // Get a view of the texture
-    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-
-    // Make a sampler
-    let sampler_descriptor = wgpu::SamplerDescriptor {
-        address_mode_u: wgpu::AddressMode::Repeat,
-        address_mode_v: wgpu::AddressMode::Repeat,
-        address_mode_w: wgpu::AddressMode::Repeat,
-        mag_filter: wgpu::FilterMode::Linear,
-        min_filter: wgpu::FilterMode::Nearest,
-        mipmap_filter: wgpu::FilterMode::Nearest,
-        ..Default::default()
-    };
-    let sampler = device.create_sampler(&sampler_descriptor);
-
-    // Make a bind group for everything
-    let mut builder = bind_group::Builder::new(device);
-    builder.set_layout(layout);
-    builder.add_material(&view, &sampler);
-    let bind_group = builder.build(label);
-
-    bind_group
+    texture
 }
 
 pub fn new_color(
@@ -1,2 +1,6 @@
+pub mod animation;
 pub mod backend;
+#[allow(clippy::module_inception)]
 pub mod renderer;
+pub mod sprite_batch;
+pub mod text;
@@ -1,42 +1,73 @@
 use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
 
-use super::backend::ubo::{self};
-use super::backend::{bind_group_layout, pipeline, texture};
+use super::backend::ubo;
+use super::backend::{bind_group_layout, frame_capture, instance_buffer, pipeline, texture};
+use super::sprite_batch::{Sprite, SpriteBatch};
+use crate::constants::graphics;
 use crate::model::game_object;
+use crate::model::geometry::Rect;
 use crate::renderer::backend::{definitions, mesh_builder};
 use glfw::Window;
 use glm::ext;
 
-pub struct State<'a> {
+// Why a headless state could not be made
+#[derive(Debug)]
+pub enum HeadlessError {
+    NoAdapter(wgpu::RequestAdapterError),
+    NoDevice(wgpu::RequestDeviceError),
+}
+
+impl fmt::Display for HeadlessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HeadlessError::NoAdapter(e) => write!(f, "no adapter: {e}"),
+            HeadlessError::NoDevice(e) => write!(f, "device request failed: {e}"),
+        }
+    }
+}
+
+pub struct State {
     instance: wgpu::Instance,
-    surface: wgpu::Surface<'a>,
+    surface: Option<wgpu::Surface<'static>>,
     device: wgpu::Device,
     queue: wgpu::Queue,
     config: wgpu::SurfaceConfiguration,
+    // The scene is drawn at a fixed logical resolution, then scaled up onto the surface
+    target_config: wgpu::SurfaceConfiguration,
     pub size: (i32, i32),
-    pub window: &'a mut Window,
     render_pipelines: HashMap<definitions::PipelineType, wgpu::RenderPipeline>,
     triangle_mesh: wgpu::Buffer,
     quad_mesh: definitions::Mesh,
     triangle_material: wgpu::BindGroup,
     quad_material: wgpu::BindGroup,
-    ubo: Option<ubo::UBOGroup>,
+    instances: instance_buffer::InstanceBuffer,
     projection_ubo: ubo::UBO,
+    sprite_projection_ubo: ubo::UBO,
+    sprite_batch: SpriteBatch,
+    // Sprites in screen space, drawn last and unaffected by the 2D camera
+    ui_projection_ubo: ubo::UBO,
+    ui_batch: SpriteBatch,
+    sprite_textures: Vec<definitions::SpriteTexture>,
+    // Handles of the textures loaded from files, so each file is uploaded once
+    sprite_texture_files: HashMap<String, usize>,
+    camera_kind: definitions::CameraKind,
     bind_group_layouts: HashMap<definitions::BindScope, wgpu::BindGroupLayout>,
     models: Vec<definitions::Model>,
     materials: Vec<definitions::Material>,
     depth_buffer: texture::Texture,
+    color_buffer: texture::Texture,
+    blit_material: wgpu::BindGroup,
+    capture_requested: bool,
+    captured_frame: Option<image::RgbaImage>,
 }
 
-impl<'a> State<'a> {
-    pub async fn new(window: &'a mut Window) -> Self {
+impl State {
+    pub async fn new(window: &mut Window) -> Self {
         let size = window.get_framebuffer_size();
 
-        let instance_descriptor = wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(),
-            ..Default::default()
-        };
-        let instance = wgpu::Instance::new(&instance_descriptor);
+        let instance = Self::build_instance();
         let surface = instance.create_surface(window.render_context()).unwrap();
 
         let adapter_descriptor = wgpu::RequestAdapterOptionsBase {
@@ -46,23 +77,17 @@ impl<'a> State<'a> {
         };
         let adapter = instance.request_adapter(&adapter_descriptor).await.unwrap();
 
-        let device_descriptor = wgpu::DeviceDescriptor {
-            required_features: wgpu::Features::empty(),
-            required_limits: wgpu::Limits::default(),
-            label: Some("Device"),
-            memory_hints: wgpu::MemoryHints::Performance,
-            trace: wgpu::Trace::Off,
-        };
-
-        let (device, queue) = adapter.request_device(&device_descriptor).await.unwrap();
+        let (device, queue) = adapter
+            .request_device(&Self::device_descriptor())
+            .await
+            .unwrap();
 
         let surface_capabilities = surface.get_capabilities(&adapter);
         let surface_format = surface_capabilities
             .formats
             .iter()
             .copied()
-            .filter(|f| f.is_srgb())
-            .next()
+            .find(|f| f.is_srgb())
             .unwrap_or(surface_capabilities.formats[0]);
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
@@ -76,19 +101,123 @@ impl<'a> State<'a> {
         };
         surface.configure(&device, &config);
 
+        let target_config = wgpu::SurfaceConfiguration {
+            usage: Self::target_usage(),
+            width: graphics::MAP_WDITH_PIXELS,
+            height: graphics::MAP_HEIGHT_PIXELS,
+            ..config.clone()
+        };
+
+        Self::build(
+            instance,
+            Some(surface),
+            device,
+            queue,
+            config,
+            target_config,
+            size,
+        )
+    }
+
+    // Renders into an offscreen texture instead of a window surface,
+    // falling back to the software adapter when no GPU is present. Fails when
+    // there is no adapter at all or it cannot give a device
+    pub async fn new_headless(size: (i32, i32)) -> Result<Self, HeadlessError> {
+        let instance = Self::build_instance();
+
+        let mut adapter_descriptor = wgpu::RequestAdapterOptionsBase {
+            power_preference: wgpu::PowerPreference::default(),
+            compatible_surface: None,
+            force_fallback_adapter: false,
+        };
+        let adapter = match instance.request_adapter(&adapter_descriptor).await {
+            Ok(adapter) => adapter,
+            Err(_) => {
+                adapter_descriptor.force_fallback_adapter = true;
+                instance
+                    .request_adapter(&adapter_descriptor)
+                    .await
+                    .map_err(HeadlessError::NoAdapter)?
+            }
+        };
+
+        let (device, queue) = adapter
+            .request_device(&Self::device_descriptor())
+            .await
+            .map_err(HeadlessError::NoDevice)?;
+
+        // Without a window the logical target is the requested size, there is nothing to scale
+        let config = wgpu::SurfaceConfiguration {
+            usage: Self::target_usage(),
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            width: size.0 as u32,
+            height: size.1 as u32,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+
+        let target_config = config.clone();
+
+        Ok(Self::build(
+            instance,
+            None,
+            device,
+            queue,
+            config,
+            target_config,
+            size,
+        ))
+    }
+
+    fn target_usage() -> wgpu::TextureUsages {
+        wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_SRC
+    }
+
+    fn build_instance() -> wgpu::Instance {
+        let instance_descriptor = wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        };
+        wgpu::Instance::new(&instance_descriptor)
+    }
+
+    fn device_descriptor() -> wgpu::DeviceDescriptor<'static> {
+        wgpu::DeviceDescriptor {
+            required_features: wgpu::Features::empty(),
+            required_limits: wgpu::Limits::default(),
+            label: Some("Device"),
+            memory_hints: wgpu::MemoryHints::Performance,
+            trace: wgpu::Trace::Off,
+        }
+    }
+
+    fn build(
+        instance: wgpu::Instance,
+        surface: Option<wgpu::Surface<'static>>,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        config: wgpu::SurfaceConfiguration,
+        target_config: wgpu::SurfaceConfiguration,
+        size: (i32, i32),
+    ) -> Self {
         let triangle_mesh = mesh_builder::make_triangle(&device);
         let quad_mesh = mesh_builder::make_quad(&device);
 
         let bind_group_layouts = Self::build_bind_group_layouts(&device);
 
-        let render_pipelines = Self::build_pipelines(&device, &config, &bind_group_layouts);
+        let render_pipelines =
+            Self::build_pipelines(&device, &config, &target_config, &bind_group_layouts);
 
         let quad_material = texture::new_texture(
             "../assets/levels/level_2_design.png",
             &device,
             &queue,
             "Quad Material",
-            &bind_group_layouts
+            bind_group_layouts
                 .get(&definitions::BindScope::Texture)
                 .unwrap(),
         );
@@ -97,39 +226,75 @@ impl<'a> State<'a> {
             &device,
             &queue,
             "Triangle Material",
-            &bind_group_layouts
+            bind_group_layouts
                 .get(&definitions::BindScope::Texture)
                 .unwrap(),
         );
 
         let projection_ubo = ubo::UBO::new(
             &device,
-            &bind_group_layouts
+            bind_group_layouts
                 .get(&definitions::BindScope::UBO)
                 .unwrap(),
         );
 
-        let depth_buffer = texture::new_depth_texture(&device, &config, "Depth Buffer");
+        let sprite_projection_ubo = ubo::UBO::new(
+            &device,
+            bind_group_layouts
+                .get(&definitions::BindScope::UBO)
+                .unwrap(),
+        );
+
+        let ui_projection_ubo = ubo::UBO::new(
+            &device,
+            bind_group_layouts
+                .get(&definitions::BindScope::UBO)
+                .unwrap(),
+        );
+
+        let instances = instance_buffer::InstanceBuffer::new(&device, 64);
+        let sprite_batch = SpriteBatch::new(&device, 256);
+        let ui_batch = SpriteBatch::new(&device, 64);
+
+        let depth_buffer = texture::new_depth_texture(&device, &target_config, "Depth Buffer");
+        let color_buffer = texture::new_color_texture(&device, &target_config, "Color Buffer");
+        let blit_material = texture::new_render_target_material(
+            &color_buffer,
+            &device,
+            "Blit Material",
+            &bind_group_layouts[&definitions::BindScope::Texture],
+        );
 
         Self {
             instance,
-            window,
             surface,
             device,
             queue,
             config,
+            target_config,
             size,
             render_pipelines,
             triangle_mesh,
             quad_mesh,
             triangle_material,
             quad_material,
-            ubo: None,
+            instances,
             projection_ubo,
+            sprite_projection_ubo,
+            sprite_batch,
+            ui_projection_ubo,
+            ui_batch,
+            sprite_textures: Vec::new(),
+            sprite_texture_files: HashMap::new(),
+            camera_kind: definitions::CameraKind::Perspective,
             bind_group_layouts,
             models: Vec::new(),
             materials: Vec::new(),
             depth_buffer,
+            color_buffer,
+            blit_material,
+            capture_requested: false,
+            captured_frame: None,
         }
     }
 
@@ -160,6 +325,7 @@ impl<'a> State<'a> {
     fn build_pipelines(
         device: &wgpu::Device,
         config: &wgpu::SurfaceConfiguration,
+        target_config: &wgpu::SurfaceConfiguration,
         bind_group_layouts: &HashMap<definitions::BindScope, wgpu::BindGroupLayout>,
     ) -> HashMap<definitions::PipelineType, wgpu::RenderPipeline> {
         let mut pipelines: HashMap<definitions::PipelineType, wgpu::RenderPipeline> =
@@ -169,54 +335,78 @@ impl<'a> State<'a> {
         let mut builder = pipeline::Builder::new(device);
         pipeline_type = definitions::PipelineType::Simple;
         builder.set_shader_module("shaders/shader.wgsl", "vs_main", "fs_main");
-        builder.set_pixel_format(config.format);
+        builder.set_pixel_format(target_config.format);
         builder.add_vertex_buffer_layout(definitions::Vertex::get_layout());
+        builder.add_vertex_buffer_layout(definitions::InstanceData::get_layout());
         builder.add_bind_group_layout(&bind_group_layouts[&definitions::BindScope::Texture]);
         builder.add_bind_group_layout(&bind_group_layouts[&definitions::BindScope::UBO]);
-        builder.add_bind_group_layout(&bind_group_layouts[&definitions::BindScope::UBO]);
         pipeline = builder.build("Simple Pipeline");
         pipelines.insert(pipeline_type, pipeline);
 
         pipeline_type = definitions::PipelineType::ColoredModel;
         builder.set_shader_module("shaders/colored_model_shader.wgsl", "vs_main", "fs_main");
-        builder.set_pixel_format(config.format);
+        builder.set_pixel_format(target_config.format);
         builder.add_vertex_buffer_layout(definitions::ModelVertex::get_layout());
+        builder.add_vertex_buffer_layout(definitions::InstanceData::get_layout());
         builder.add_bind_group_layout(&bind_group_layouts[&definitions::BindScope::Color]);
         builder.add_bind_group_layout(&bind_group_layouts[&definitions::BindScope::UBO]);
-        builder.add_bind_group_layout(&bind_group_layouts[&definitions::BindScope::UBO]);
         pipeline = builder.build("Colored Model Pipeline");
         pipelines.insert(pipeline_type, pipeline);
 
         pipeline_type = definitions::PipelineType::TexturedModel;
         builder.set_shader_module("shaders/textured_model_shader.wgsl", "vs_main", "fs_main");
-        builder.set_pixel_format(config.format);
+        builder.set_pixel_format(target_config.format);
         builder.add_vertex_buffer_layout(definitions::ModelVertex::get_layout());
+        builder.add_vertex_buffer_layout(definitions::InstanceData::get_layout());
         builder.add_bind_group_layout(&bind_group_layouts[&definitions::BindScope::Texture]);
         builder.add_bind_group_layout(&bind_group_layouts[&definitions::BindScope::UBO]);
-        builder.add_bind_group_layout(&bind_group_layouts[&definitions::BindScope::UBO]);
         pipeline = builder.build("Textured Model Pipeline");
         pipelines.insert(pipeline_type, pipeline);
 
+        // Sprites are drawn back to front with alpha blending, flipped quads must not be culled
+        pipeline_type = definitions::PipelineType::Sprite;
+        builder.set_shader_module("shaders/sprite_shader.wgsl", "vs_main", "fs_main");
+        builder.set_pixel_format(target_config.format);
+        builder.add_vertex_buffer_layout(definitions::SpriteVertex::get_layout());
+        builder.add_bind_group_layout(&bind_group_layouts[&definitions::BindScope::Texture]);
+        builder.add_bind_group_layout(&bind_group_layouts[&definitions::BindScope::UBO]);
+        builder.set_blend_state(wgpu::BlendState::ALPHA_BLENDING);
+        builder.set_cull_mode(None);
+        builder.set_depth_test(false, wgpu::CompareFunction::Always);
+        pipeline = builder.build("Sprite Pipeline");
+        pipelines.insert(pipeline_type, pipeline);
+
+        // Upscales the logical target onto the surface, drawn without depth
+        pipeline_type = definitions::PipelineType::Blit;
+        builder.set_shader_module("shaders/blit_shader.wgsl", "vs_main", "fs_main");
+        builder.set_pixel_format(config.format);
+        builder.add_bind_group_layout(&bind_group_layouts[&definitions::BindScope::Texture]);
+        builder.set_cull_mode(None);
+        builder.set_depth_enabled(false);
+        pipeline = builder.build("Blit Pipeline");
+        pipelines.insert(pipeline_type, pipeline);
+
         pipelines
     }
 
     pub fn load_assets(&mut self) {
-        let mut loader = mesh_builder::ObjLoader::new();
-
         let c0 = glm::Vec4::new(5.0, 0.0, 0.0, 0.0);
         let c1 = glm::Vec4::new(0.0, 5.0, 0.0, 0.0);
         let c2 = glm::Vec4::new(0.0, 0.0, 5.0, 0.0);
         let c3 = glm::Vec4::new(0.0, 0.0, 0.0, 1.0);
         let pre_transform = glm::Matrix4::new(c0, c1, c2, c3);
 
-        self.models.push(loader.load(
-            "poses.obj",
-            &mut self.materials,
-            &self.device,
-            &pre_transform,
-        ));
+        self.load_model(Path::new("assets/models/poses.obj"), &pre_transform);
+    }
 
-        for material in &mut self.materials {
+    // Adds a model drawn every frame with the quads and triangles
+    pub fn load_model(&mut self, path: &Path, pre_transform: &glm::Mat4) {
+        let first_material = self.materials.len();
+        let mut loader = mesh_builder::ObjLoader::new();
+        self.models
+            .push(loader.load(path, &mut self.materials, &self.device, pre_transform));
+
+        for material in &mut self.materials[first_material..] {
             material.bind_group = match material.pipeline_type {
                 definitions::PipelineType::ColoredModel => Some(texture::new_color(
                     &(material.color.unwrap()),
@@ -238,15 +428,90 @@ impl<'a> State<'a> {
         }
     }
 
-    pub fn build_ubos_for_objects(&mut self, object_count: usize) {
-        self.ubo = Some(ubo::UBOGroup::new(
+    // Returns a handle to pass as Sprite::texture, the same one every time a
+    // file is loaded again
+    pub fn load_sprite_texture(&mut self, filename: &str) -> usize {
+        if let Some(texture) = self.sprite_texture_files.get(filename) {
+            return *texture;
+        }
+
+        let sprite_texture = texture::new_sprite_texture(
+            filename,
+            &self.device,
+            &self.queue,
+            filename,
+            &self.bind_group_layouts[&definitions::BindScope::Texture],
+        );
+        self.sprite_textures.push(sprite_texture);
+        self.sprite_texture_files
+            .insert(filename.to_string(), self.sprite_textures.len() - 1);
+
+        self.sprite_textures.len() - 1
+    }
+
+    pub fn load_sprite_texture_from_image(
+        &mut self,
+        image: &image::RgbaImage,
+        label: &str,
+    ) -> usize {
+        let sprite_texture = texture::new_sprite_texture_from_image(
+            image,
             &self.device,
-            object_count,
-            &self.bind_group_layouts[&definitions::BindScope::UBO],
-        ));
+            &self.queue,
+            label,
+            &self.bind_group_layouts[&definitions::BindScope::Texture],
+        );
+        self.sprite_textures.push(sprite_texture);
+
+        self.sprite_textures.len() - 1
+    }
+
+    pub fn sprite_texture_size(&self, texture: usize) -> (u32, u32) {
+        let sprite_texture = &self.sprite_textures[texture];
+        (sprite_texture.width, sprite_texture.height)
+    }
+
+    // Queues a sprite for the next call to render
+    pub fn draw_sprite(&mut self, sprite: Sprite) {
+        self.sprite_batch.draw(sprite);
+    }
+
+    // Queues a sprite positioned in logical screen pixels, drawn over everything else
+    pub fn draw_ui_sprite(&mut self, sprite: Sprite) {
+        self.ui_batch.draw(sprite);
+    }
+
+    // Picks the camera the 3D objects are seen through, sprites always use the 2D camera
+    pub fn set_camera_kind(&mut self, camera_kind: definitions::CameraKind) {
+        self.camera_kind = camera_kind;
     }
 
-    fn update_projection(&mut self, camera: &game_object::Camera) {
+    fn update_projection(
+        &mut self,
+        camera: &game_object::Camera,
+        camera_2d: &game_object::OrthoCamera,
+        alpha: f32,
+    ) {
+        let sprite_view_proj = Self::orthographic_view_projection(camera_2d, alpha);
+        let view_proj = match self.camera_kind {
+            definitions::CameraKind::Perspective => Self::perspective_view_projection(camera),
+            definitions::CameraKind::Orthographic => sprite_view_proj,
+        };
+
+        self.projection_ubo.upload(&view_proj, &self.queue);
+        self.sprite_projection_ubo
+            .upload(&sprite_view_proj, &self.queue);
+
+        let ui_view_proj = Self::pixel_projection(
+            0.0,
+            0.0,
+            self.target_config.width as f32,
+            self.target_config.height as f32,
+        );
+        self.ui_projection_ubo.upload(&ui_view_proj, &self.queue);
+    }
+
+    fn perspective_view_projection(camera: &game_object::Camera) -> glm::Mat4 {
         let c0 = glm::Vec4::new(camera.right.x, camera.up.x, -camera.forwards.x, 0.0);
         let c1 = glm::Vec4::new(camera.right.y, camera.up.y, -camera.forwards.y, 0.0);
         let c2 = glm::Vec4::new(camera.right.z, camera.up.z, -camera.forwards.z, 0.0);
@@ -261,51 +526,77 @@ impl<'a> State<'a> {
         let z_far = 10.0;
         let projection = ext::perspective(fov_y, aspect, z_near, z_far);
 
-        let view_proj = projection * view;
-        self.projection_ubo.upload(&view_proj, &self.queue);
+        projection * view
     }
 
-    fn update_transforms(
-        &mut self,
-        quads: &Vec<game_object::Object>,
-        tris: &Vec<game_object::Object>,
-    ) {
-        let mut offset: u64 = 0;
-        for i in 0..quads.len() {
-            let c0 = glm::Vec4::new(1.0, 0.0, 0.0, 0.0);
-            let c1 = glm::Vec4::new(0.0, 1.0, 0.0, 0.0);
-            let c2 = glm::Vec4::new(0.0, 0.0, 1.0, 0.0);
-            let c3 = glm::Vec4::new(0.0, 0.0, 0.0, 1.0);
-            let m1 = glm::Matrix4::new(c0, c1, c2, c3);
-            let m2 = glm::Matrix4::new(c0, c1, c2, c3);
-
-            let matrix = ext::rotate(&m1, quads[i].angle, glm::Vec3::new(0.0, 0.0, 1.0))
-                * ext::translate(&m2, quads[i].position);
-            self.ubo
-                .as_mut()
-                .unwrap()
-                .upload(offset + i as u64, &matrix, &self.queue);
+    // World pixels to clip space, y pointing down
+    fn orthographic_view_projection(camera: &game_object::OrthoCamera, alpha: f32) -> glm::Mat4 {
+        // Snap to whole pixels so pixel art does not shimmer while scrolling
+        let top_left = camera.interpolated_top_left(alpha);
+        let size = camera.view_size();
+        let left = glm::round(top_left.x);
+        let top = glm::round(top_left.y);
+
+        Self::pixel_projection(left, top, left + size.x, top + size.y)
+    }
+
+    // Maps a rectangle of pixels, y pointing down, onto the whole target
+    fn pixel_projection(left: f32, top: f32, right: f32, bottom: f32) -> glm::Mat4 {
+        let c0 = glm::Vec4::new(2.0 / (right - left), 0.0, 0.0, 0.0);
+        let c1 = glm::Vec4::new(0.0, 2.0 / (top - bottom), 0.0, 0.0);
+        let c2 = glm::Vec4::new(0.0, 0.0, 1.0, 0.0);
+        let c3 = glm::Vec4::new(
+            -(right + left) / (right - left),
+            -(top + bottom) / (top - bottom),
+            0.0,
+            1.0,
+        );
+
+        glm::Matrix4::new(c0, c1, c2, c3)
+    }
+
+    fn object_transform(object: &game_object::Object) -> glm::Mat4 {
+        let c0 = glm::Vec4::new(1.0, 0.0, 0.0, 0.0);
+        let c1 = glm::Vec4::new(0.0, 1.0, 0.0, 0.0);
+        let c2 = glm::Vec4::new(0.0, 0.0, 1.0, 0.0);
+        let c3 = glm::Vec4::new(0.0, 0.0, 0.0, 1.0);
+        let m1 = glm::Matrix4::new(c0, c1, c2, c3);
+        let m2 = glm::Matrix4::new(c0, c1, c2, c3);
+
+        ext::rotate(&m1, object.angle, glm::Vec3::new(0.0, 0.0, 1.0))
+            * ext::translate(&m2, object.position)
+    }
+
+    // Instances are laid out as all quads, then all triangles, then one per model
+    fn update_transforms(&mut self, quads: &[game_object::Object], tris: &[game_object::Object]) {
+        let mut instances: Vec<definitions::InstanceData> =
+            Vec::with_capacity(quads.len() + tris.len() + self.models.len());
+
+        for object in quads.iter().chain(tris.iter()) {
+            instances.push(definitions::InstanceData {
+                model: Self::object_transform(object),
+            });
         }
 
-        offset += quads.len() as u64;
-
-        for i in 0..tris.len() {
-            let c0 = glm::Vec4::new(1.0, 0.0, 0.0, 0.0);
-            let c1 = glm::Vec4::new(0.0, 1.0, 0.0, 0.0);
-            let c2 = glm::Vec4::new(0.0, 0.0, 1.0, 0.0);
-            let c3 = glm::Vec4::new(0.0, 0.0, 0.0, 1.0);
-            let m1 = glm::Matrix4::new(c0, c1, c2, c3);
-            let m2 = glm::Matrix4::new(c0, c1, c2, c3);
-            let matrix = ext::rotate(&m1, tris[i].angle, glm::Vec3::new(0.0, 0.0, 1.0))
-                * ext::translate(&m2, tris[i].position);
-            self.ubo
-                .as_mut()
-                .unwrap()
-                .upload(offset + i as u64, &matrix, &self.queue);
+        let c0 = glm::Vec4::new(1.0, 0.0, 0.0, 0.0);
+        let c1 = glm::Vec4::new(0.0, 1.0, 0.0, 0.0);
+        let c2 = glm::Vec4::new(0.0, 0.0, 1.0, 0.0);
+        let c3 = glm::Vec4::new(0.0, 0.0, 0.0, 1.0);
+        for _ in &self.models {
+            instances.push(definitions::InstanceData {
+                model: glm::Matrix4::new(c0, c1, c2, c3),
+            });
         }
+
+        self.instances.upload(&instances, &self.device, &self.queue);
     }
 
-    fn render_model(&self, model: &definitions::Model, renderpass: &mut wgpu::RenderPass) {
+    fn render_model(
+        &self,
+        model: &definitions::Model,
+        instance: u32,
+        renderpass: &mut wgpu::RenderPass,
+    ) {
         // Bind vertex and index buffer
         renderpass.set_vertex_buffer(0, model.buffer.slice(0..model.ebo_offset));
         renderpass.set_index_buffer(
@@ -313,44 +604,44 @@ impl<'a> State<'a> {
             wgpu::IndexFormat::Uint32,
         );
 
-        // Transforms
-        renderpass.set_bind_group(1, &(self.ubo.as_ref().unwrap()).bind_groups[0], &[]);
-        //renderpass.set_bind_group(2, &self.projection_ubo.bind_group, &[]);
-
         for submesh in &model.submeshes {
-            //let submesh = &model.submeshes[1];
-
-            //println!("index count: {}, first index: {}", submesh.index_count, submesh.first_index);
-
             // Select pipeline
             let material = &self.materials[submesh.material_id];
             renderpass.set_pipeline(&self.render_pipelines[&material.pipeline_type]);
             renderpass.set_bind_group(0, (material.bind_group).as_ref().unwrap(), &[]);
 
-            renderpass.draw_indexed(0..submesh.index_count, submesh.first_index, 0..1);
+            renderpass.draw_indexed(
+                0..submesh.index_count,
+                submesh.first_index,
+                instance..instance + 1,
+            );
         }
     }
 
     pub fn render(
         &mut self,
-        quads: &Vec<game_object::Object>,
-        tris: &Vec<game_object::Object>,
+        quads: &[game_object::Object],
+        tris: &[game_object::Object],
         camera: &game_object::Camera,
+        camera_2d: &game_object::OrthoCamera,
+        // How far between the previous and the current tick to draw
+        alpha: f32,
     ) -> Result<(), wgpu::SurfaceError> {
         self.device.poll(wgpu::MaintainBase::Wait).ok();
 
-        self.update_projection(camera);
+        self.update_projection(camera, camera_2d, alpha);
 
         self.update_transforms(quads, tris);
 
+        self.sprite_batch
+            .prepare(&self.sprite_textures, &self.device, &self.queue);
+        self.ui_batch
+            .prepare(&self.sprite_textures, &self.device, &self.queue);
+
         let event = self.queue.submit([]);
         let maintain = wgpu::MaintainBase::WaitForSubmissionIndex(event);
         let _ = self.device.poll(maintain);
 
-        let drawable = self.surface.get_current_texture()?;
-        let image_view_descriptor = wgpu::TextureViewDescriptor::default();
-        let image_view = drawable.texture.create_view(&image_view_descriptor);
-
         let command_encoder_descriptor = wgpu::CommandEncoderDescriptor {
             label: Some("Render Encoder"),
         };
@@ -359,7 +650,7 @@ impl<'a> State<'a> {
             .create_command_encoder(&command_encoder_descriptor);
 
         let color_attachment = wgpu::RenderPassColorAttachment {
-            view: &image_view,
+            view: &self.color_buffer.view,
             resolve_target: None,
             ops: wgpu::Operations {
                 load: wgpu::LoadOp::Clear(wgpu::Color {
@@ -395,63 +686,217 @@ impl<'a> State<'a> {
 
             // Quads
             renderpass.set_bind_group(0, &self.quad_material, &[]);
-            renderpass.set_bind_group(2, &self.projection_ubo.bind_group, &[]);
+            renderpass.set_bind_group(1, &self.projection_ubo.bind_group, &[]);
             renderpass.set_vertex_buffer(0, self.quad_mesh.buffer.slice(0..self.quad_mesh.offset));
+            renderpass.set_vertex_buffer(1, self.instances.buffer.slice(..));
             renderpass.set_index_buffer(
                 self.quad_mesh.buffer.slice(self.quad_mesh.offset..),
                 wgpu::IndexFormat::Uint16,
             );
-            let mut offset: usize = 0;
-            for i in 0..quads.len() {
-                renderpass.set_bind_group(
-                    1,
-                    &(self.ubo.as_ref().unwrap()).bind_groups[offset + i],
-                    &[],
-                );
-                renderpass.draw_indexed(0..6, 0, 0..1);
+            let mut first_instance: u32 = 0;
+            let quad_count = quads.len() as u32;
+            if quad_count > 0 {
+                renderpass.draw_indexed(0..6, 0, first_instance..first_instance + quad_count);
             }
+            first_instance += quad_count;
 
             // Triangles
             renderpass.set_bind_group(0, &self.triangle_material, &[]);
             renderpass.set_vertex_buffer(0, self.triangle_mesh.slice(..));
-            offset = quads.len();
-            for i in 0..tris.len() {
-                renderpass.set_bind_group(
-                    1,
-                    &(self.ubo.as_ref().unwrap()).bind_groups[offset + i],
-                    &[],
-                );
-                renderpass.draw(0..3, 0..1);
+            let tri_count = tris.len() as u32;
+            if tri_count > 0 {
+                renderpass.draw(0..3, first_instance..first_instance + tri_count);
+            }
+            first_instance += tri_count;
+
+            for model in &self.models {
+                self.render_model(model, first_instance, &mut renderpass);
+                first_instance += 1;
             }
 
-            self.render_model(&self.models[0], &mut renderpass);
+            // Sprites, on top of the scene
+            if !self.sprite_batch.is_empty() {
+                renderpass.set_pipeline(&self.render_pipelines[&definitions::PipelineType::Sprite]);
+                renderpass.set_bind_group(1, &self.sprite_projection_ubo.bind_group, &[]);
+                self.sprite_batch
+                    .render(&self.sprite_textures, &mut renderpass);
+            }
+
+            if !self.ui_batch.is_empty() {
+                renderpass.set_pipeline(&self.render_pipelines[&definitions::PipelineType::Sprite]);
+                renderpass.set_bind_group(1, &self.ui_projection_ubo.bind_group, &[]);
+                self.ui_batch.render(&self.sprite_textures, &mut renderpass);
+            }
         }
         self.queue.submit(std::iter::once(command_encoder.finish()));
+        self.sprite_batch.clear();
+        self.ui_batch.clear();
         self.device.poll(wgpu::MaintainBase::wait()).ok();
 
+        // Captures are taken at the logical resolution, before any scaling
+        if self.capture_requested {
+            self.capture_requested = false;
+            let frame =
+                frame_capture::read_texture(&self.device, &self.queue, &self.color_buffer.texture);
+            match frame {
+                Ok(frame) => self.captured_frame = Some(frame),
+                Err(e) => eprintln!("Cannot read back the frame: {e}"),
+            }
+        }
+
+        if self.surface.is_some() {
+            self.present()?;
+        }
+
+        Ok(())
+    }
+
+    fn present(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let drawable = self.surface.as_ref().unwrap().get_current_texture()?;
+        let image_view_descriptor = wgpu::TextureViewDescriptor::default();
+        let image_view = drawable.texture.create_view(&image_view_descriptor);
+
+        let command_encoder_descriptor = wgpu::CommandEncoderDescriptor {
+            label: Some("Blit Encoder"),
+        };
+        let mut command_encoder = self
+            .device
+            .create_command_encoder(&command_encoder_descriptor);
+
+        // Whatever the scaled image does not cover becomes the black bars
+        let color_attachment = wgpu::RenderPassColorAttachment {
+            view: &image_view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                store: wgpu::StoreOp::Store,
+            },
+        };
+
+        let render_pass_descriptor = wgpu::RenderPassDescriptor {
+            label: Some("Blit Pass"),
+            color_attachments: &[Some(color_attachment)],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        };
+
+        {
+            let viewport = self.viewport();
+            let mut renderpass = command_encoder.begin_render_pass(&render_pass_descriptor);
+            renderpass.set_viewport(viewport.x, viewport.y, viewport.w, viewport.h, 0.0, 1.0);
+            renderpass.set_pipeline(&self.render_pipelines[&definitions::PipelineType::Blit]);
+            renderpass.set_bind_group(0, &self.blit_material, &[]);
+            renderpass.draw(0..3, 0..1);
+        }
+        self.queue.submit(std::iter::once(command_encoder.finish()));
+
         drawable.present();
 
         Ok(())
     }
 
+    // Where the logical image lands on the window, in framebuffer pixels
+    pub fn viewport(&self) -> Rect {
+        letterbox(
+            (self.config.width, self.config.height),
+            (self.target_config.width, self.target_config.height),
+        )
+    }
+
+    // Maps a framebuffer position (e.g. the cursor) to logical pixels,
+    // None when it falls on the bars
+    pub fn window_to_logical(&self, x: f32, y: f32) -> Option<glm::Vec2> {
+        window_to_logical(
+            (self.config.width, self.config.height),
+            (self.target_config.width, self.target_config.height),
+            x,
+            y,
+        )
+    }
+
+    // Copies the next rendered frame back to the CPU, fetch it with take_capture
+    pub fn request_capture(&mut self) {
+        self.capture_requested = true;
+    }
+
+    pub fn take_capture(&mut self) -> Option<image::RgbaImage> {
+        self.captured_frame.take()
+    }
+
     pub fn resize(&mut self, new_size: (i32, i32)) {
         if new_size.0 > 0 && new_size.1 > 0 {
             self.size = new_size;
             self.config.width = new_size.0 as u32;
             self.config.height = new_size.1 as u32;
-            self.surface.configure(&self.device, &self.config);
+            match &self.surface {
+                // The logical target keeps its size, only the scaling changes
+                Some(surface) => surface.configure(&self.device, &self.config),
+                None => {
+                    self.target_config.width = self.config.width;
+                    self.target_config.height = self.config.height;
+                    self.rebuild_targets();
+                }
+            }
+        }
+    }
 
-            self.depth_buffer.texture.destroy();
+    fn rebuild_targets(&mut self) {
+        self.color_buffer.texture.destroy();
+        self.depth_buffer.texture.destroy();
 
-            self.depth_buffer =
-                texture::new_depth_texture(&self.device, &self.config, "Depth Buffer")
+        self.color_buffer =
+            texture::new_color_texture(&self.device, &self.target_config, "Color Buffer");
+        self.depth_buffer =
+            texture::new_depth_texture(&self.device, &self.target_config, "Depth Buffer");
+        self.blit_material = texture::new_render_target_material(
+            &self.color_buffer,
+            &self.device,
+            "Blit Material",
+            &self.bind_group_layouts[&definitions::BindScope::Texture],
+        );
+    }
+
+    pub fn update_surface(&mut self, window: &mut Window) {
+        if self.surface.is_some() {
+            self.surface = Some(
+                self.instance
+                    .create_surface(window.render_context())
+                    .unwrap(),
+            );
         }
     }
+}
 
-    pub fn update_surface(&mut self) {
-        self.surface = self
-            .instance
-            .create_surface(self.window.render_context())
-            .unwrap();
+// Largest whole-number scale of the logical size that fits the window, centred.
+// Windows smaller than the logical size get a fractional fit instead.
+pub fn letterbox(window: (u32, u32), logical: (u32, u32)) -> Rect {
+    let fit_x = window.0 as f32 / logical.0 as f32;
+    let fit_y = window.1 as f32 / logical.1 as f32;
+    let fit = fit_x.min(fit_y);
+    let scale = if fit >= 1.0 { fit.floor() } else { fit };
+
+    let w = logical.0 as f32 * scale;
+    let h = logical.1 as f32 * scale;
+    let x = ((window.0 as f32 - w) / 2.0).floor();
+    let y = ((window.1 as f32 - h) / 2.0).floor();
+    Rect::new(x, y, w, h)
+}
+
+pub fn window_to_logical(
+    window: (u32, u32),
+    logical: (u32, u32),
+    x: f32,
+    y: f32,
+) -> Option<glm::Vec2> {
+    let viewport = letterbox(window, logical);
+    let local_x = (x - viewport.x) / viewport.w;
+    let local_y = (y - viewport.y) / viewport.h;
+    if !(0.0..1.0).contains(&local_x) || !(0.0..1.0).contains(&local_y) {
+        return None;
     }
+    Some(glm::vec2(
+        local_x * logical.0 as f32,
+        local_y * logical.1 as f32,
+    ))
 }
@@ -0,0 +1,197 @@
+use super::backend::definitions::{SpriteTexture, SpriteVertex};
+use super::backend::mesh_builder::vec_to_u8_slice;
+use crate::model::geometry::Rect;
+
+#[derive(Clone, Copy)]
+pub struct Sprite {
+    pub texture: usize,
+    // Region of the sheet in texels
+    pub source: Rect,
+    // Top left corner in world pixels
+    pub position: glm::Vec2,
+    pub scale: glm::Vec2,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    pub tint: glm::Vec4,
+    // Higher layers are drawn on top
+    pub layer: i32,
+}
+
+impl Sprite {
+    pub fn new(texture: usize, source: Rect, position: glm::Vec2) -> Self {
+        Sprite {
+            texture,
+            source,
+            position,
+            scale: glm::Vec2::new(1.0, 1.0),
+            flip_x: false,
+            flip_y: false,
+            tint: glm::Vec4::new(1.0, 1.0, 1.0, 1.0),
+            layer: 0,
+        }
+    }
+}
+
+struct Batch {
+    texture: usize,
+    first_index: u32,
+    index_count: u32,
+}
+
+pub struct SpriteBatch {
+    sprites: Vec<Sprite>,
+    batches: Vec<Batch>,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    capacity: usize,
+}
+
+impl SpriteBatch {
+    pub fn new(device: &wgpu::Device, capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let (vertex_buffer, index_buffer) = Self::create_buffers(device, capacity);
+
+        SpriteBatch {
+            sprites: Vec::new(),
+            batches: Vec::new(),
+            vertex_buffer,
+            index_buffer,
+            capacity,
+        }
+    }
+
+    fn create_buffers(device: &wgpu::Device, capacity: usize) -> (wgpu::Buffer, wgpu::Buffer) {
+        let vertex_buffer_descriptor = wgpu::BufferDescriptor {
+            label: Some("Sprite vertex buffer"),
+            size: (4 * capacity * std::mem::size_of::<SpriteVertex>()) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        };
+        let vertex_buffer = device.create_buffer(&vertex_buffer_descriptor);
+
+        // Every sprite is two triangles, the index pattern never changes
+        let mut indices: Vec<u32> = Vec::with_capacity(6 * capacity);
+        for i in 0..capacity as u32 {
+            let first = 4 * i;
+            indices.extend_from_slice(&[first, first + 1, first + 2, first + 2, first + 3, first]);
+        }
+        let index_buffer_descriptor = wgpu::BufferDescriptor {
+            label: Some("Sprite index buffer"),
+            size: (indices.len() * std::mem::size_of::<u32>()) as u64,
+            usage: wgpu::BufferUsages::INDEX,
+            mapped_at_creation: true,
+        };
+        let index_buffer = device.create_buffer(&index_buffer_descriptor);
+        index_buffer
+            .slice(..)
+            .get_mapped_range_mut()
+            .copy_from_slice(vec_to_u8_slice(&indices));
+        index_buffer.unmap();
+
+        (vertex_buffer, index_buffer)
+    }
+
+    pub fn draw(&mut self, sprite: Sprite) {
+        self.sprites.push(sprite);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sprites.is_empty()
+    }
+
+    // Sorts the queued sprites by layer and texture, then uploads them as one vertex buffer
+    pub fn prepare(
+        &mut self,
+        textures: &[SpriteTexture],
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        self.batches.clear();
+        if self.sprites.is_empty() {
+            return;
+        }
+
+        if self.sprites.len() > self.capacity {
+            while self.capacity < self.sprites.len() {
+                self.capacity *= 2;
+            }
+            self.vertex_buffer.destroy();
+            self.index_buffer.destroy();
+            (self.vertex_buffer, self.index_buffer) = Self::create_buffers(device, self.capacity);
+        }
+
+        // Stable, so sprites on the same layer and sheet keep their submission order
+        self.sprites
+            .sort_by_key(|sprite| (sprite.layer, sprite.texture));
+
+        let mut vertices: Vec<SpriteVertex> = Vec::with_capacity(4 * self.sprites.len());
+        for (i, sprite) in self.sprites.iter().enumerate() {
+            Self::push_vertices(sprite, &textures[sprite.texture], &mut vertices);
+
+            let first_index = 6 * i as u32;
+            match self.batches.last_mut() {
+                Some(batch) if batch.texture == sprite.texture => batch.index_count += 6,
+                _ => self.batches.push(Batch {
+                    texture: sprite.texture,
+                    first_index,
+                    index_count: 6,
+                }),
+            }
+        }
+
+        queue.write_buffer(&self.vertex_buffer, 0, vec_to_u8_slice(&vertices));
+    }
+
+    fn push_vertices(sprite: &Sprite, texture: &SpriteTexture, vertices: &mut Vec<SpriteVertex>) {
+        let left = sprite.position.x;
+        let top = sprite.position.y;
+        let right = left + sprite.source.w * sprite.scale.x;
+        let bottom = top + sprite.source.h * sprite.scale.y;
+
+        let mut u0 = sprite.source.x / texture.width as f32;
+        let mut v0 = sprite.source.y / texture.height as f32;
+        let mut u1 = (sprite.source.x + sprite.source.w) / texture.width as f32;
+        let mut v1 = (sprite.source.y + sprite.source.h) / texture.height as f32;
+        if sprite.flip_x {
+            std::mem::swap(&mut u0, &mut u1);
+        }
+        if sprite.flip_y {
+            std::mem::swap(&mut v0, &mut v1);
+        }
+
+        let corners = [
+            (left, bottom, u0, v1),
+            (right, bottom, u1, v1),
+            (right, top, u1, v0),
+            (left, top, u0, v0),
+        ];
+        for (x, y, u, v) in corners {
+            vertices.push(SpriteVertex {
+                position: glm::Vec2::new(x, y),
+                tex_coord: glm::Vec2::new(u, v),
+                color: sprite.tint,
+            });
+        }
+    }
+
+    pub fn render(&self, textures: &[SpriteTexture], renderpass: &mut wgpu::RenderPass) {
+        if self.batches.is_empty() {
+            return;
+        }
+
+        renderpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        renderpass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        for batch in &self.batches {
+            renderpass.set_bind_group(0, &textures[batch.texture].bind_group, &[]);
+            renderpass.draw_indexed(
+                batch.first_index..batch.first_index + batch.index_count,
+                0,
+                0..1,
+            );
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.sprites.clear();
+    }
+}
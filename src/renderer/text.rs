@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::renderer::State;
+use super::sprite_batch::Sprite;
+use crate::model::geometry::Rect;
+
+// Fonts are BMFont text files, one tag per line followed by key=value pairs:
+//
+//   common lineHeight=<pixels> base=<pixels>      spacing between lines, and the
+//                                                 baseline from the top of a line
+//   page id=<index> file="<image>"                glyph atlas, next to the font file
+//   char id=<code point> x= y= width= height= xoffset= yoffset= xadvance= page=
+//   kerning first=<code point> second=<code point> amount=<pixels>
+//
+// Other tags, like info and the counts, are not needed and skipped.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Align {
+    Left,
+    Centre,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Glyph {
+    pub page: usize,
+    pub source: Rect,
+    // From the pen position to the top left of the glyph
+    pub offset: glm::Vec2,
+    pub advance: f32,
+}
+
+// How a piece of text is laid out and drawn
+#[derive(Clone, Copy, Debug)]
+pub struct TextStyle {
+    pub scale: f32,
+    pub colour: glm::Vec4,
+    // Lines are lined up on the x they are drawn at: their left edge, middle
+    // or right edge
+    pub align: Align,
+    // Words go on the next line rather than past this many pixels, scale included
+    pub wrap_width: Option<f32>,
+    pub layer: i32,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        TextStyle {
+            scale: 1.0,
+            colour: glm::Vec4::new(1.0, 1.0, 1.0, 1.0),
+            align: Align::Left,
+            wrap_width: None,
+            layer: 0,
+        }
+    }
+}
+
+pub struct Font {
+    pub line_height: f32,
+    pub base: f32,
+    // Atlas images by page id
+    pub pages: Vec<String>,
+    pub glyphs: HashMap<char, Glyph>,
+    pub kernings: HashMap<(char, char), f32>,
+    // Sprite textures for the pages once they are loaded
+    pub textures: Vec<usize>,
+}
+
+// Drawn for characters the font has no glyph for
+const REPLACEMENT: char = '?';
+
+impl Font {
+    pub fn load(filename: &str) -> Self {
+        let text = std::fs::read_to_string(filename)
+            .unwrap_or_else(|e| panic!("Cannot read font {filename}: {e}"));
+        let directory = Path::new(filename).parent().unwrap_or(Path::new(""));
+        Self::parse(&text, directory)
+    }
+
+    pub fn parse(text: &str, directory: &Path) -> Self {
+        let mut font = Font {
+            line_height: 0.0,
+            base: 0.0,
+            pages: Vec::new(),
+            glyphs: HashMap::new(),
+            kernings: HashMap::new(),
+            textures: Vec::new(),
+        };
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (tag, values) = tag_values(line);
+            let parsed = match tag {
+                "common" => font.parse_common(&values),
+                "page" => font.parse_page(&values, directory),
+                "char" => font.parse_char(&values),
+                "kerning" => font.parse_kerning(&values),
+                _ => Some(()),
+            };
+            if parsed.is_none() {
+                eprintln!("Ignoring font line {}: {line}", number + 1);
+            }
+        }
+
+        // Pages can come in any order, so glyphs are only checked once all are read
+        let pages = &font.pages;
+        font.glyphs.retain(|character, glyph| {
+            let declared = pages.get(glyph.page).is_some_and(|page| !page.is_empty());
+            if !declared {
+                eprintln!(
+                    "Ignoring glyph {character:?}, there is no page {}",
+                    glyph.page
+                );
+            }
+            declared
+        });
+
+        font
+    }
+
+    fn parse_common(&mut self, values: &HashMap<&str, &str>) -> Option<()> {
+        self.line_height = values.get("lineHeight")?.parse().ok()?;
+        self.base = values.get("base")?.parse().ok()?;
+        Some(())
+    }
+
+    fn parse_page(&mut self, values: &HashMap<&str, &str>, directory: &Path) -> Option<()> {
+        let id: usize = values.get("id")?.parse().ok()?;
+        let file = directory.join(values.get("file")?);
+        if self.pages.len() <= id {
+            self.pages.resize(id + 1, String::new());
+        }
+        self.pages[id] = file.to_string_lossy().into_owned();
+        Some(())
+    }
+
+    fn parse_char(&mut self, values: &HashMap<&str, &str>) -> Option<()> {
+        let number = |key: &str| values.get(key)?.parse::<f32>().ok();
+        let id = char::from_u32(values.get("id")?.parse().ok()?)?;
+        let glyph = Glyph {
+            page: values
+                .get("page")
+                .map_or(Some(0), |page| page.parse().ok())?,
+            source: Rect::new(
+                number("x")?,
+                number("y")?,
+                number("width")?,
+                number("height")?,
+            ),
+            offset: glm::Vec2::new(number("xoffset")?, number("yoffset")?),
+            advance: number("xadvance")?,
+        };
+        self.glyphs.insert(id, glyph);
+        Some(())
+    }
+
+    fn parse_kerning(&mut self, values: &HashMap<&str, &str>) -> Option<()> {
+        let code = |key: &str| char::from_u32(values.get(key)?.parse().ok()?);
+        let amount = values.get("amount")?.parse().ok()?;
+        self.kernings
+            .insert((code("first")?, code("second")?), amount);
+        Some(())
+    }
+
+    pub fn load_textures(&mut self, state: &mut State) {
+        self.textures = self
+            .pages
+            .iter()
+            .map(|page| state.load_sprite_texture(page))
+            .collect();
+    }
+
+    pub fn glyph(&self, character: char) -> Option<&Glyph> {
+        self.glyphs
+            .get(&character)
+            .or_else(|| self.glyphs.get(&REPLACEMENT))
+    }
+
+    // Width of one line in font pixels, before scaling
+    pub fn line_width(&self, line: &str) -> f32 {
+        let mut width = 0.0;
+        let mut previous = None;
+        for character in line.chars() {
+            if let Some(previous) = previous {
+                width += self.kerning(previous, character);
+            }
+            width += self.glyph(character).map_or(0.0, |glyph| glyph.advance);
+            previous = Some(character);
+        }
+        width
+    }
+
+    fn kerning(&self, first: char, second: char) -> f32 {
+        self.kernings.get(&(first, second)).copied().unwrap_or(0.0)
+    }
+
+    // Breaks the text at newlines, and between words where it would be wider
+    // than the style's wrap width. A word wider than that gets a line to itself.
+    pub fn lines(&self, text: &str, style: &TextStyle) -> Vec<String> {
+        let mut lines = Vec::new();
+        for paragraph in text.split('\n') {
+            let Some(wrap_width) = style.wrap_width else {
+                lines.push(paragraph.to_string());
+                continue;
+            };
+
+            let mut line = String::new();
+            for word in paragraph.split(' ').filter(|word| !word.is_empty()) {
+                let longer = if line.is_empty() {
+                    word.to_string()
+                } else {
+                    format!("{line} {word}")
+                };
+                if !line.is_empty() && self.line_width(&longer) * style.scale > wrap_width {
+                    lines.push(std::mem::replace(&mut line, word.to_string()));
+                } else {
+                    line = longer;
+                }
+            }
+            lines.push(line);
+        }
+        lines
+    }
+
+    // Size of the laid out text in pixels, scale included
+    pub fn measure(&self, text: &str, style: &TextStyle) -> glm::Vec2 {
+        let lines = self.lines(text, style);
+        let width = lines
+            .iter()
+            .map(|line| self.line_width(line))
+            .fold(0.0, f32::max);
+        glm::Vec2::new(width, lines.len() as f32 * self.line_height) * style.scale
+    }
+
+    // One sprite per visible glyph, for text whose first line has its top at
+    // position. Needs the textures loaded, there are no sprites before that.
+    pub fn sprites(&self, text: &str, position: glm::Vec2, style: &TextStyle) -> Vec<Sprite> {
+        let mut sprites = Vec::new();
+        for (row, line) in self.lines(text, style).iter().enumerate() {
+            let width = self.line_width(line) * style.scale;
+            let left = match style.align {
+                Align::Left => position.x,
+                Align::Centre => position.x - width / 2.0,
+                Align::Right => position.x - width,
+            };
+            // Whole pixels keep the glyphs crisp
+            let mut pen = glm::Vec2::new(
+                left.floor(),
+                (position.y + row as f32 * self.line_height * style.scale).floor(),
+            );
+
+            let mut previous = None;
+            for character in line.chars() {
+                let Some(glyph) = self.glyph(character) else {
+                    continue;
+                };
+                if let Some(previous) = previous {
+                    pen.x += self.kerning(previous, character) * style.scale;
+                }
+                previous = Some(character);
+
+                if let Some(texture) = self.textures.get(glyph.page)
+                    && glyph.source.w > 0.0
+                    && glyph.source.h > 0.0
+                {
+                    let mut sprite =
+                        Sprite::new(*texture, glyph.source, pen + glyph.offset * style.scale);
+                    sprite.scale = glm::Vec2::new(style.scale, style.scale);
+                    sprite.tint = style.colour;
+                    sprite.layer = style.layer;
+                    sprites.push(sprite);
+                }
+                pen.x += glyph.advance * style.scale;
+            }
+        }
+        sprites
+    }
+
+    // Queues the text in logical screen pixels, batched with the other UI sprites
+    pub fn draw(&self, state: &mut State, text: &str, position: glm::Vec2, style: &TextStyle) {
+        for sprite in self.sprites(text, position, style) {
+            state.draw_ui_sprite(sprite);
+        }
+    }
+
+    // Queues the text in world pixels, batched with the other sprites
+    pub fn draw_in_world(
+        &self,
+        state: &mut State,
+        text: &str,
+        position: glm::Vec2,
+        style: &TextStyle,
+    ) {
+        for sprite in self.sprites(text, position, style) {
+            state.draw_sprite(sprite);
+        }
+    }
+}
+
+// The tag at the start of a line and its key=value pairs, values may be quoted
+fn tag_values(line: &str) -> (&str, HashMap<&str, &str>) {
+    let (tag, mut rest) = line.split_once(' ').unwrap_or((line, ""));
+    let mut values = HashMap::new();
+    loop {
+        rest = rest.trim_start();
+        let Some((key, after)) = rest.split_once('=') else {
+            break;
+        };
+        let (value, after) = match after.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => after.split_once(' ').unwrap_or((after, "")),
+        };
+        values.insert(key.trim(), value);
+        rest = after;
+    }
+    (tag, values)
+}
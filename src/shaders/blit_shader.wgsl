@@ -0,0 +1,24 @@
+@group(0) @binding(0) var myTexture: texture_2d<f32>;
+@group(0) @binding(1) var mySampler: sampler;
+
+struct VertexPayload {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coord: vec2<f32>,
+};
+
+// One triangle covering the whole viewport, no vertex buffer needed
+@vertex
+fn vs_main(@builtin(vertex_index) i: u32) -> VertexPayload {
+
+    let uv = vec2<f32>(f32((i << 1u) & 2u), f32(i & 2u));
+
+    var out: VertexPayload;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.tex_coord = uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexPayload) -> @location(0) vec4<f32> {
+    return textureSample(myTexture, mySampler, in.tex_coord);
+}
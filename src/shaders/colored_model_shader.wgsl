@@ -1,6 +1,5 @@
 @group(0) @binding(0) var<uniform> color: vec4<f32>;
-@group(1) @binding(0) var<uniform> model: mat4x4<f32>;
-@group(2) @binding(0) var<uniform> view_projection: mat4x4<f32>;
+@group(1) @binding(0) var<uniform> view_projection: mat4x4<f32>;
 
 struct Vertex {
     @location(0) position: vec3<f32>,
@@ -8,14 +7,22 @@ struct Vertex {
     @location(2) normal: vec3<f32>,
 };
 
+struct Instance {
+    @location(4) model_0: vec4<f32>,
+    @location(5) model_1: vec4<f32>,
+    @location(6) model_2: vec4<f32>,
+    @location(7) model_3: vec4<f32>,
+};
+
 struct VertexPayload {
     @builtin(position) position: vec4<f32>,
     @location(0) normal: vec3<f32>,
 };
 
 @vertex
-fn vs_main(vertex: Vertex) -> VertexPayload {
+fn vs_main(vertex: Vertex, instance: Instance) -> VertexPayload {
 
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
     var out: VertexPayload;
     out.position = view_projection * model * vec4<f32>(vertex.position, 1.0);
     out.normal = (model * vec4<f32>(vertex.normal, 0.0)).xyz;
@@ -1,13 +1,19 @@
 @group(0) @binding(0) var myTexture: texture_2d<f32>;
 @group(0) @binding(1) var mySampler: sampler;
-@group(1) @binding(0) var<uniform> model: mat4x4<f32>;
-@group(2) @binding(0) var<uniform> view_projection: mat4x4<f32>;
+@group(1) @binding(0) var<uniform> view_projection: mat4x4<f32>;
 
 struct Vertex {
     @location(0) position: vec3<f32>,
     @location(1) color: vec3<f32>,
 };
 
+struct Instance {
+    @location(4) model_0: vec4<f32>,
+    @location(5) model_1: vec4<f32>,
+    @location(6) model_2: vec4<f32>,
+    @location(7) model_3: vec4<f32>,
+};
+
 struct VertexPayload {
     @builtin(position) position: vec4<f32>,
     @location(0) color: vec3<f32>,
@@ -15,8 +21,9 @@ struct VertexPayload {
 };
 
 @vertex
-fn vs_main(vertex: Vertex) -> VertexPayload {
+fn vs_main(vertex: Vertex, instance: Instance) -> VertexPayload {
 
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
     var out: VertexPayload;
     out.position = view_projection * model * vec4<f32>(vertex.position, 1.0);
     out.color = vertex.color;
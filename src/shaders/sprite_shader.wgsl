@@ -0,0 +1,34 @@
+@group(0) @binding(0) var myTexture: texture_2d<f32>;
+@group(0) @binding(1) var mySampler: sampler;
+@group(1) @binding(0) var<uniform> view_projection: mat4x4<f32>;
+
+struct Vertex {
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coord: vec2<f32>,
+    @location(2) color: vec4<f32>,
+};
+
+struct VertexPayload {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coord: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(vertex: Vertex) -> VertexPayload {
+
+    var out: VertexPayload;
+    out.position = view_projection * vec4<f32>(vertex.position, 0.0, 1.0);
+    out.tex_coord = vertex.tex_coord;
+    out.color = vertex.color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexPayload) -> @location(0) vec4<f32> {
+    let base: vec4<f32> = textureSample(myTexture, mySampler, in.tex_coord);
+    if (base.a * in.color.a < 0.01) {
+        discard;
+    }
+    return base * in.color;
+}
@@ -0,0 +1,278 @@
+use crate::constants::graphics;
+use crate::model::animation::{AnimationSet, Animator};
+use crate::model::geometry::Rect;
+use crate::model::world::World;
+use crate::renderer::renderer::State;
+use crate::renderer::sprite_batch::Sprite;
+use crate::renderer::text::{Align, Font, TextStyle};
+use crate::ui::widgets;
+
+pub const ANIMATIONS: &str = "assets/animations/hud.anim";
+const BARS_SHEET: &str = "assets/gui/GUISprite.png";
+pub const FONT: &str = "assets/fonts/knight.fnt";
+
+// Full and empty versions of each bar sit one above the other on the sheet,
+// the fill runs between the given columns of the frame
+const PLAYER_FULL: Rect = Rect {
+    x: 16.0,
+    y: 96.0,
+    w: 64.0,
+    h: 16.0,
+};
+const PLAYER_EMPTY_Y: f32 = 112.0;
+const PLAYER_FILL: (f32, f32) = (15.0, 61.0);
+const BOSS_FULL: Rect = Rect {
+    x: 16.0,
+    y: 36.0,
+    w: 64.0,
+    h: 9.0,
+};
+const BOSS_EMPTY_Y: f32 = 51.0;
+const BOSS_FILL: (f32, f32) = (3.0, 61.0);
+
+// Screen pixels
+const PLAYER_BAR_POSITION: (f32, f32) = (24.0, 24.0);
+const PLAYER_BAR_SCALE: f32 = 4.0;
+const BOSS_BAR_SCALE: f32 = 6.0;
+const BOSS_BAR_BOTTOM: f32 = 32.0;
+const LAYER: i32 = 20;
+const TEXT_SCALE: f32 = 3.0;
+const TEXT_GAP: f32 = 12.0;
+const TEXT_COLOUR: (f32, f32, f32) = (0.95, 0.9, 0.8);
+const BOSS_NAME: &str = "The Nightmare";
+
+// The bars catch up with the real health at this fraction of a bar per second
+const DRAIN_SPEED: f32 = 0.5;
+// Hits of at least this much damage get the bigger effects
+const MEDIUM_DAMAGE: i32 = 12;
+const CRITICAL_DAMAGE: i32 = 20;
+const LARGE_HEAL: i32 = 20;
+// Effects are drawn at half size, which is one texel per screen pixel at
+// the camera's zoom, and float upwards in world pixels per second
+const EFFECT_SCALE: f32 = 0.5;
+const EFFECT_RISE: f32 = 12.0;
+const EFFECT_LAYER: i32 = 10;
+
+pub fn damage_effect(damage: i32) -> &'static str {
+    if damage >= CRITICAL_DAMAGE {
+        "critical_damage"
+    } else if damage >= MEDIUM_DAMAGE {
+        "medium_damage"
+    } else {
+        "minimum_damage"
+    }
+}
+
+pub fn healing_effect(amount: i32) -> &'static str {
+    if amount >= LARGE_HEAL {
+        "healing"
+    } else {
+        "regeneration"
+    }
+}
+
+// An effect playing once where something was hit or healed
+pub struct Effect {
+    pub animator: Animator,
+    // Middle of its bottom edge, in world pixels
+    pub position: glm::Vec2,
+}
+
+// The knight's health in the corner, the boss's along the bottom while it is
+// being fought, and an effect over every hit and heal
+pub struct Hud {
+    pub animations: AnimationSet,
+    pub font: Font,
+    pub effects: Vec<Effect>,
+    // Fractions of full health as drawn, easing towards the real ones
+    pub player_shown: f32,
+    pub boss_shown: Option<f32>,
+    // Last tick's health, to notice healing and for the numbers
+    player_health: i32,
+    player_max: i32,
+    bars: usize,
+}
+
+impl Hud {
+    pub fn new(world: &World) -> Self {
+        Hud {
+            animations: AnimationSet::load(ANIMATIONS),
+            font: Font::load(FONT),
+            effects: Vec::new(),
+            player_shown: health_fraction(
+                world.player.fighter.health.current,
+                world.player.fighter.health.max,
+            ),
+            boss_shown: None,
+            player_health: world.player.fighter.health.current,
+            player_max: world.player.fighter.health.max,
+            bars: 0,
+        }
+    }
+
+    pub fn load_textures(&mut self, state: &mut State) {
+        self.animations.load_textures(state);
+        self.font.load_textures(state);
+        self.bars = state.load_sprite_texture(BARS_SHEET);
+    }
+
+    // One fixed tick after the world's, dt in seconds
+    pub fn update(&mut self, dt: f32, world: &World) {
+        for effect in &mut self.effects {
+            effect.animator.advance(&self.animations, dt);
+            effect.position.y -= EFFECT_RISE * dt;
+        }
+        self.effects.retain(|effect| !effect.animator.finished());
+
+        for event in &world.damage_events {
+            self.spawn(damage_effect(event.damage), event.position);
+        }
+        let health = &world.player.fighter.health;
+        if health.current > self.player_health {
+            let body = &world.player.body;
+            let head = glm::Vec2::new(body.centre().x, body.position.y);
+            self.spawn(healing_effect(health.current - self.player_health), head);
+        }
+        self.player_health = health.current;
+        self.player_max = health.max;
+
+        let step = DRAIN_SPEED * dt;
+        self.player_shown = approach(
+            self.player_shown,
+            health_fraction(health.current, health.max),
+            step,
+        );
+        self.boss_shown = match &world.boss {
+            Some(boss) if boss.is_awake() => {
+                let health = &boss.fighter.health;
+                let target = health_fraction(health.current, health.max);
+                // Fills up from empty when the fight starts
+                Some(approach(self.boss_shown.unwrap_or(0.0), target, 2.0 * step))
+            }
+            _ => None,
+        };
+    }
+
+    fn spawn(&mut self, clip: &str, position: glm::Vec2) {
+        self.effects.push(Effect {
+            animator: Animator::new(&self.animations, clip),
+            position,
+        });
+    }
+
+    // Effects go in the world and the bars on the screen, needs the textures loaded
+    pub fn draw(&self, state: &mut State) {
+        for effect in &self.effects {
+            let offset = effect
+                .animator
+                .origin(&self.animations, glm::Vec2::new(0.0, 0.0), false);
+            let mut sprite = effect
+                .animator
+                .sprite(&self.animations, effect.position + offset * EFFECT_SCALE);
+            sprite.scale = glm::Vec2::new(EFFECT_SCALE, EFFECT_SCALE);
+            sprite.layer = EFFECT_LAYER;
+            state.draw_sprite(sprite);
+        }
+
+        let player = glm::Vec2::new(PLAYER_BAR_POSITION.0, PLAYER_BAR_POSITION.1);
+        self.draw_bar(
+            state,
+            PLAYER_FULL,
+            PLAYER_EMPTY_Y,
+            PLAYER_FILL,
+            self.player_shown,
+            player,
+            PLAYER_BAR_SCALE,
+        );
+        // Current and full health beside the bar, centred on it
+        let size = glm::Vec2::new(PLAYER_FULL.w, PLAYER_FULL.h) * PLAYER_BAR_SCALE;
+        let health = format!("{}/{}", self.player_health, self.player_max);
+        let top = player.y + (size.y - self.font.base * TEXT_SCALE) / 2.0;
+        self.draw_text(
+            state,
+            &health,
+            glm::Vec2::new(player.x + size.x + TEXT_GAP, top),
+            Align::Left,
+        );
+        if let Some(shown) = self.boss_shown {
+            let size = glm::Vec2::new(BOSS_FULL.w, BOSS_FULL.h) * BOSS_BAR_SCALE;
+            let position = glm::Vec2::new(
+                widgets::centred_x(size.x),
+                graphics::MAP_HEIGHT_PIXELS as f32 - BOSS_BAR_BOTTOM - size.y,
+            );
+            self.draw_bar(
+                state,
+                BOSS_FULL,
+                BOSS_EMPTY_Y,
+                BOSS_FILL,
+                shown,
+                position,
+                BOSS_BAR_SCALE,
+            );
+            let above = position.y - self.font.line_height * TEXT_SCALE;
+            self.draw_text(
+                state,
+                BOSS_NAME,
+                glm::Vec2::new(position.x + size.x / 2.0, above),
+                Align::Centre,
+            );
+        }
+    }
+
+    // Text with a drop shadow, so it reads over any background
+    fn draw_text(&self, state: &mut State, text: &str, position: glm::Vec2, align: Align) {
+        let mut style = TextStyle {
+            scale: TEXT_SCALE,
+            colour: glm::Vec4::new(0.0, 0.0, 0.0, 0.8),
+            align,
+            layer: LAYER,
+            ..Default::default()
+        };
+        let shadow = glm::Vec2::new(TEXT_SCALE, TEXT_SCALE);
+        self.font.draw(state, text, position + shadow, &style);
+        let (r, g, b) = TEXT_COLOUR;
+        style.colour = glm::Vec4::new(r, g, b, 1.0);
+        self.font.draw(state, text, position, &style);
+    }
+
+    // The empty bar with as much of the full one as the fraction over it
+    #[allow(clippy::too_many_arguments)]
+    fn draw_bar(
+        &self,
+        state: &mut State,
+        full: Rect,
+        empty_y: f32,
+        fill: (f32, f32),
+        fraction: f32,
+        position: glm::Vec2,
+        scale: f32,
+    ) {
+        let mut empty = Sprite::new(self.bars, Rect { y: empty_y, ..full }, position);
+        empty.scale = glm::Vec2::new(scale, scale);
+        empty.layer = LAYER;
+        state.draw_ui_sprite(empty);
+
+        // Nothing of the full bar past the fill, so an empty bar keeps its
+        // empty heart
+        if fraction <= 0.0 {
+            return;
+        }
+        let width = fill.0 + fraction * (fill.1 - fill.0);
+        let mut filled = Sprite::new(self.bars, Rect { w: width, ..full }, position);
+        filled.scale = glm::Vec2::new(scale, scale);
+        filled.layer = LAYER;
+        state.draw_ui_sprite(filled);
+    }
+}
+
+fn health_fraction(current: i32, max: i32) -> f32 {
+    glm::clamp(current as f32 / max as f32, 0.0, 1.0)
+}
+
+fn approach(from: f32, to: f32, step: f32) -> f32 {
+    if from < to {
+        glm::min(from + step, to)
+    } else {
+        glm::max(from - step, to)
+    }
+}
@@ -0,0 +1,2 @@
+pub mod hud;
+pub mod widgets;
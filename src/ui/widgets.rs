@@ -0,0 +1,450 @@
+use crate::audio::audio::Audio;
+use crate::audio::mixer::{Bus, Playback};
+use crate::constants::graphics;
+use crate::model::geometry::Rect;
+use crate::platform::input::{Action, ActionState};
+use crate::renderer::renderer::State;
+use crate::renderer::sprite_batch::Sprite;
+
+const LABELS_SHEET: &str = "assets/gui/buttons.png";
+const ICONS_SHEET: &str = "assets/gui/controlbuttons.png";
+const SOUND_SHEET: &str = "assets/gui/soundbuttons.png";
+const VOLUME_SHEET: &str = "assets/gui/volumebuttons.png";
+const PANEL_SHEET: &str = "assets/gui/background.png";
+const MOVE_SOUND: &str = "assets/sounds/ui_move.wav";
+const CONFIRM_SOUND: &str = "assets/sounds/ui_confirm.wav";
+
+// The button sheets have a row per button, in normal, hover and pressed columns
+const LABEL_WIDTH: f32 = 184.0;
+const LABEL_HEIGHT: f32 = 90.0;
+// Both the control and sound buttons
+const ICON_SIZE: f32 = 90.0;
+// The volume sheet has the three knobs side by side, then the track
+const KNOB_WIDTH: f32 = 30.0;
+const KNOB_HEIGHT: f32 = 44.0;
+const TRACK_X: f32 = 90.0;
+const TRACK_Y: f32 = 12.0;
+const TRACK_WIDTH: f32 = 209.0;
+const TRACK_HEIGHT: f32 = 24.0;
+// How far in from each end of the track the middle of the knob can go
+const TRACK_INSET: f32 = 10.0;
+// Fraction moved by each left or right press
+const SLIDER_STEP: f32 = 0.1;
+// Selector panels are tinted by state, darker when not focused
+const SELECTOR_TINTS: [f32; 3] = [0.6, 1.0, 0.85];
+
+// Nine slice panel with its corners, and a plain patch from inside it
+const PANEL_X: f32 = 62.0;
+const PANEL_Y: f32 = 4.0;
+const PANEL_SIZE: f32 = 54.0;
+const PANEL_BORDER: f32 = 8.0;
+const PLAIN: Rect = Rect {
+    x: 84.0,
+    y: 24.0,
+    w: 4.0,
+    h: 4.0,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Label {
+    Play,
+    Options,
+    Quit,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Icon {
+    Play,
+    Restart,
+    Home,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WidgetState {
+    Normal,
+    // Has the focus
+    Hover,
+    // Confirm is held down on it
+    Pressed,
+}
+
+impl WidgetState {
+    fn column(self) -> f32 {
+        match self {
+            WidgetState::Normal => 0.0,
+            WidgetState::Hover => 1.0,
+            WidgetState::Pressed => 2.0,
+        }
+    }
+}
+
+// Immediate mode widgets: each tick the scene starts with begin and then
+// declares its widgets, which answer whether they were used. The focus moves
+// through them in the order they are declared, with up and down, or left and
+// right unless it is on a slider. Jump or attack presses the focused widget,
+// which goes off when let go.
+//
+// Widgets queue their sprites in screen pixels, which draw hands over to the
+// renderer's UI pass until the next begin.
+pub struct Ui {
+    // Panels and images go on this layer, shades just under it and the
+    // widgets above it
+    pub layer: i32,
+    // Size of the button sheets' cells on screen
+    pub scale: f32,
+    labels: usize,
+    icons: usize,
+    sound: usize,
+    volume: usize,
+    panel: usize,
+    sprites: Vec<Sprite>,
+    focus: usize,
+    // Focusable widgets declared since begin, and during the tick before
+    count: usize,
+    previous_count: usize,
+    // The focused widget last tick was a slider or selector, which keep left
+    // and right
+    on_slider: bool,
+    // Confirm went down on the focused widget and is still held
+    pressing: bool,
+    // Confirm was let go of this tick
+    released: bool,
+    // -1 or 1 when left or right was pressed this tick
+    adjust: f32,
+    // What to play for this tick's moves and presses
+    cue: Option<&'static str>,
+}
+
+impl Ui {
+    pub fn new(state: &mut State) -> Self {
+        Ui {
+            layer: 0,
+            scale: 1.0,
+            labels: state.load_sprite_texture(LABELS_SHEET),
+            icons: state.load_sprite_texture(ICONS_SHEET),
+            sound: state.load_sprite_texture(SOUND_SHEET),
+            volume: state.load_sprite_texture(VOLUME_SHEET),
+            panel: state.load_sprite_texture(PANEL_SHEET),
+            sprites: Vec::new(),
+            focus: 0,
+            count: 0,
+            previous_count: 0,
+            on_slider: false,
+            pressing: false,
+            released: false,
+            adjust: 0.0,
+            cue: None,
+        }
+    }
+
+    // Index of the focused widget, in the order they are declared
+    pub fn focus(&self) -> usize {
+        self.focus
+    }
+
+    pub fn set_focus(&mut self, focus: usize) {
+        self.focus = focus;
+        self.pressing = false;
+    }
+
+    pub fn begin(&mut self, actions: &ActionState) {
+        self.sprites.clear();
+        self.previous_count = self.count;
+        self.count = 0;
+
+        let sideways = actions.axis(Action::MoveLeft, Action::MoveRight);
+        let sideways_pressed =
+            actions.pressed(Action::MoveLeft) || actions.pressed(Action::MoveRight);
+        self.adjust = 0.0;
+        let mut step = 0;
+        if actions.pressed(Action::MoveUp) {
+            step -= 1;
+        }
+        if actions.pressed(Action::MoveDown) {
+            step += 1;
+        }
+        if sideways_pressed && sideways != 0.0 {
+            if self.on_slider {
+                self.adjust = sideways.signum();
+            } else {
+                step += sideways.signum() as i32;
+            }
+        }
+        let focus_before = self.focus;
+        if self.previous_count > 0 {
+            let count = self.previous_count as i32;
+            let focus = glm::min(self.focus as i32, count - 1);
+            if step != 0 || focus != self.focus as i32 {
+                self.set_focus((focus + step).rem_euclid(count) as usize);
+            }
+        }
+        self.on_slider = false;
+
+        if actions.pressed(Action::Jump) || actions.pressed(Action::Attack) {
+            self.pressing = true;
+        }
+        let held = actions.held(Action::Jump) || actions.held(Action::Attack);
+        self.released = self.pressing && !held;
+        if self.released {
+            self.pressing = false;
+        }
+
+        let moved = step != 0 && self.focus != focus_before;
+        self.cue = if self.previous_count == 0 {
+            None
+        } else if self.released {
+            Some(CONFIRM_SOUND)
+        } else if moved || self.adjust != 0.0 {
+            Some(MOVE_SOUND)
+        } else {
+            None
+        };
+    }
+
+    // Clicks for what begin saw this tick, right after it
+    pub fn play_sounds(&self, audio: &mut Audio) {
+        if let Some(cue) = self.cue {
+            let sound = audio.load(cue);
+            audio.play(
+                sound,
+                Playback {
+                    bus: Bus::Ui,
+                    pitch_variation: 0.05,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    // Hands this tick's widgets to the renderer, can be called every frame
+    pub fn draw(&self, state: &mut State) {
+        for sprite in &self.sprites {
+            state.draw_ui_sprite(*sprite);
+        }
+    }
+
+    // Claims the next place in the focus order
+    fn next_widget(&mut self) -> WidgetState {
+        let id = self.count;
+        self.count += 1;
+        if id != self.focus {
+            WidgetState::Normal
+        } else if self.pressing {
+            WidgetState::Pressed
+        } else {
+            WidgetState::Hover
+        }
+    }
+
+    fn activated(&self, state: WidgetState) -> bool {
+        state != WidgetState::Normal && self.released
+    }
+
+    fn cell(&mut self, texture: usize, source: Rect, position: glm::Vec2) {
+        let mut sprite = Sprite::new(texture, source, position);
+        sprite.scale = glm::Vec2::new(self.scale, self.scale);
+        sprite.layer = self.layer + 1;
+        self.sprites.push(sprite);
+    }
+
+    // A text button from the buttons sheet, true when pressed
+    pub fn button(&mut self, label: Label, position: glm::Vec2) -> bool {
+        let state = self.next_widget();
+        let row = match label {
+            Label::Play => 0.0,
+            Label::Options => 1.0,
+            Label::Quit => 2.0,
+        };
+        let source = Rect::new(
+            state.column() * LABEL_WIDTH,
+            row * LABEL_HEIGHT,
+            LABEL_WIDTH,
+            LABEL_HEIGHT,
+        );
+        self.cell(self.labels, source, position);
+        self.activated(state)
+    }
+
+    // A square button from the control buttons sheet, true when pressed
+    pub fn icon_button(&mut self, icon: Icon, position: glm::Vec2) -> bool {
+        let state = self.next_widget();
+        let row = match icon {
+            Icon::Play => 0.0,
+            Icon::Restart => 1.0,
+            Icon::Home => 2.0,
+        };
+        let source = Rect::new(
+            state.column() * ICON_SIZE,
+            row * ICON_SIZE,
+            ICON_SIZE,
+            ICON_SIZE,
+        );
+        self.cell(self.icons, source, position);
+        self.activated(state)
+    }
+
+    // A sound on or off button, true when it was flipped
+    pub fn toggle(&mut self, on: &mut bool, position: glm::Vec2) -> bool {
+        let state = self.next_widget();
+        let flipped = self.activated(state);
+        if flipped {
+            *on = !*on;
+        }
+        let row = if *on { 0.0 } else { 1.0 };
+        let source = Rect::new(
+            state.column() * ICON_SIZE,
+            row * ICON_SIZE,
+            ICON_SIZE,
+            ICON_SIZE,
+        );
+        self.cell(self.sound, source, position);
+        flipped
+    }
+
+    // A value from 0 to 1 moved with left and right while focused, true when
+    // it changed. The position is the top left of the track.
+    pub fn slider(&mut self, value: &mut f32, position: glm::Vec2) -> bool {
+        let state = self.next_widget();
+        let before = *value;
+        if state != WidgetState::Normal {
+            self.on_slider = true;
+            if self.adjust != 0.0 {
+                // Snapped so that repeated steps land on round numbers
+                let stepped = ((*value / SLIDER_STEP).round() + self.adjust) * SLIDER_STEP;
+                *value = glm::clamp(stepped, 0.0, 1.0);
+            }
+        }
+
+        let track = Rect::new(TRACK_X, TRACK_Y, TRACK_WIDTH, TRACK_HEIGHT);
+        self.cell(self.volume, track, position);
+
+        let travel = TRACK_WIDTH - 2.0 * TRACK_INSET;
+        let knob_centre = TRACK_INSET + glm::clamp(*value, 0.0, 1.0) * travel;
+        let knob = glm::Vec2::new(
+            knob_centre - 0.5 * KNOB_WIDTH,
+            0.5 * (TRACK_HEIGHT - KNOB_HEIGHT),
+        );
+        let mut sprite = Sprite::new(
+            self.volume,
+            Rect::new(state.column() * KNOB_WIDTH, 0.0, KNOB_WIDTH, KNOB_HEIGHT),
+            position + knob * self.scale,
+        );
+        sprite.scale = glm::Vec2::new(self.scale, self.scale);
+        // Over the track, which shares its sheet
+        sprite.layer = self.layer + 2;
+        self.sprites.push(sprite);
+
+        *value != before
+    }
+
+    // One of count choices, stepped through with left and right while
+    // focused, true when it changed. Drawn as a panel over the rect for the
+    // scene to write the choice on.
+    pub fn selector(&mut self, value: &mut usize, count: usize, rect: Rect, scale: f32) -> bool {
+        let state = self.next_widget();
+        let before = *value;
+        if state != WidgetState::Normal {
+            self.on_slider = true;
+            if self.adjust != 0.0 && count > 0 {
+                let stepped = *value as i32 + self.adjust as i32;
+                *value = stepped.rem_euclid(count as i32) as usize;
+            }
+        }
+
+        let first = self.sprites.len();
+        self.panel(rect, scale);
+        let tint = SELECTOR_TINTS[state.column() as usize];
+        for sprite in &mut self.sprites[first..] {
+            sprite.tint = glm::Vec4::new(tint, tint, tint, 1.0);
+            sprite.layer = self.layer + 1;
+        }
+
+        *value != before
+    }
+
+    // A framed panel stretched over the rect, corners kept at the given scale
+    pub fn panel(&mut self, rect: Rect, scale: f32) {
+        let border = PANEL_BORDER;
+        let inner = PANEL_SIZE - 2.0 * border;
+        let screen_border = border * scale;
+        let columns = [
+            (PANEL_X, border, rect.x, screen_border),
+            (
+                PANEL_X + border,
+                inner,
+                rect.x + screen_border,
+                rect.w - 2.0 * screen_border,
+            ),
+            (
+                PANEL_X + border + inner,
+                border,
+                rect.x + rect.w - screen_border,
+                screen_border,
+            ),
+        ];
+        let rows = [
+            (PANEL_Y, border, rect.y, screen_border),
+            (
+                PANEL_Y + border,
+                inner,
+                rect.y + screen_border,
+                rect.h - 2.0 * screen_border,
+            ),
+            (
+                PANEL_Y + border + inner,
+                border,
+                rect.y + rect.h - screen_border,
+                screen_border,
+            ),
+        ];
+
+        for (source_y, source_h, y, h) in rows {
+            for (source_x, source_w, x, w) in columns {
+                let mut sprite = Sprite::new(
+                    self.panel,
+                    Rect::new(source_x, source_y, source_w, source_h),
+                    glm::Vec2::new(x, y),
+                );
+                sprite.scale = glm::Vec2::new(w / source_w, h / source_h);
+                sprite.layer = self.layer;
+                self.sprites.push(sprite);
+            }
+        }
+    }
+
+    // Darkens the whole screen under the widgets
+    pub fn shade(&mut self, darkness: f32) {
+        let mut shade = Sprite::new(self.panel, PLAIN, glm::Vec2::new(0.0, 0.0));
+        shade.scale = glm::Vec2::new(
+            graphics::MAP_WDITH_PIXELS as f32 / PLAIN.w,
+            graphics::MAP_HEIGHT_PIXELS as f32 / PLAIN.h,
+        );
+        shade.tint = glm::Vec4::new(0.0, 0.0, 0.0, darkness);
+        shade.layer = self.layer - 1;
+        self.sprites.push(shade);
+    }
+
+    // Any other sprite, drawn on the panel layer
+    pub fn image(&mut self, mut sprite: Sprite) {
+        sprite.layer = self.layer;
+        self.sprites.push(sprite);
+    }
+}
+
+// Left edge that centres something this wide on the screen
+pub fn centred_x(width: f32) -> f32 {
+    0.5 * (graphics::MAP_WDITH_PIXELS as f32 - width)
+}
+
+// The width and height of a label button or icon at the given scale
+pub fn label_size(scale: f32) -> glm::Vec2 {
+    glm::Vec2::new(LABEL_WIDTH, LABEL_HEIGHT) * scale
+}
+
+pub fn icon_size(scale: f32) -> glm::Vec2 {
+    glm::Vec2::new(ICON_SIZE, ICON_SIZE) * scale
+}
+
+pub fn slider_size(scale: f32) -> glm::Vec2 {
+    glm::Vec2::new(TRACK_WIDTH, TRACK_HEIGHT) * scale
+}
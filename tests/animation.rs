@@ -0,0 +1,290 @@
+use std::path::Path;
+
+use rusty_knight::model::animation::{AnimationSet, Animator, PlayMode, SoundCue};
+use rusty_knight::model::geometry::Rect;
+
+const CLIPS: &str = "
+# Three frames from a sheet two frames wide
+clip walk
+duration 0.1
+sheet walk.png 32 16 2
+frames 1 3
+event 2 footstep
+
+clip swing
+mode once
+duration 0.05
+files swing-{}.png 1 3 24 24
+hold 1 0.2
+event 0 whoosh
+
+clip breathe
+mode ping-pong
+sheet walk.png 32 16 2
+frames 0 3
+";
+
+fn frames_over(set: &AnimationSet, animator: &mut Animator, dt: f32, steps: u32) -> Vec<usize> {
+    (0..steps)
+        .map(|_| {
+            animator.advance(set, dt);
+            animator.frame_index()
+        })
+        .collect()
+}
+
+#[test]
+fn sheets_are_read_in_rows() {
+    let set = AnimationSet::parse(CLIPS);
+    let walk = &set.clips[set.clip("walk").unwrap()];
+
+    assert_eq!(walk.mode, PlayMode::Loop);
+    let sources: Vec<Rect> = walk.frames.iter().map(|frame| frame.source).collect();
+    assert_eq!(
+        sources,
+        [
+            Rect::new(32.0, 0.0, 32.0, 16.0),
+            Rect::new(0.0, 16.0, 32.0, 16.0),
+            Rect::new(32.0, 16.0, 32.0, 16.0),
+        ]
+    );
+}
+
+#[test]
+fn numbered_files_share_images_between_clips() {
+    let set = AnimationSet::parse(CLIPS);
+
+    assert_eq!(
+        set.images,
+        ["walk.png", "swing-1.png", "swing-2.png", "swing-3.png"]
+    );
+    let swing = &set.clips[set.clip("swing").unwrap()];
+    assert_eq!(swing.frames[2].image, 3);
+    assert_eq!(swing.frames[2].source, Rect::new(0.0, 0.0, 24.0, 24.0));
+    assert!((swing.length() - 0.3).abs() < 1e-6);
+}
+
+#[test]
+fn loops_wrap_around() {
+    let set = AnimationSet::parse(CLIPS);
+    let mut animator = Animator::new(&set, "walk");
+
+    assert_eq!(
+        frames_over(&set, &mut animator, 0.05, 8),
+        [0, 1, 1, 2, 2, 0, 0, 1]
+    );
+    assert!(!animator.finished());
+}
+
+#[test]
+fn once_clips_stop_on_the_last_frame() {
+    let set = AnimationSet::parse(CLIPS);
+    let mut animator = Animator::new(&set, "swing");
+
+    animator.advance(&set, 0.1);
+    assert_eq!(animator.frame_index(), 1);
+    assert!(!animator.finished());
+
+    animator.advance(&set, 0.2);
+    assert_eq!(animator.frame_index(), 2);
+    animator.advance(&set, 1.0);
+    assert_eq!(animator.frame_index(), 2);
+    assert!(animator.finished());
+}
+
+#[test]
+fn ping_pong_turns_at_both_ends() {
+    let set = AnimationSet::parse(CLIPS);
+    let mut animator = Animator::new(&set, "breathe");
+
+    assert_eq!(
+        frames_over(&set, &mut animator, 0.1, 7),
+        [1, 2, 1, 0, 1, 2, 1]
+    );
+}
+
+#[test]
+fn events_come_from_the_frames_reached() {
+    let set = AnimationSet::parse(CLIPS);
+    let mut animator = Animator::new(&set, "walk");
+
+    assert!(animator.advance(&set, 0.15).is_empty());
+    assert_eq!(animator.advance(&set, 0.1), ["footstep"]);
+    // A long step can pass the frame more than once
+    assert_eq!(animator.advance(&set, 0.6), ["footstep", "footstep"]);
+
+    // The first frame's events are reported once the clip starts
+    animator.play(&set, "swing");
+    assert_eq!(animator.advance(&set, 0.01), ["whoosh"]);
+    assert!(animator.advance(&set, 0.01).is_empty());
+}
+
+#[test]
+fn playing_the_same_clip_carries_on() {
+    let set = AnimationSet::parse(CLIPS);
+    let mut animator = Animator::new(&set, "walk");
+    animator.advance(&set, 0.15);
+
+    animator.play(&set, "walk");
+    assert_eq!(animator.frame_index(), 1);
+
+    animator.restart(&set, "walk");
+    assert_eq!(animator.frame_index(), 0);
+    assert_eq!(animator.clip_name(&set), "walk");
+}
+
+#[test]
+fn bad_lines_and_empty_clips_are_skipped() {
+    let set = AnimationSet::parse(
+        "
+frames 0 2
+clip nothing
+mode sideways
+clip something
+duration -1
+files frame-{}.png 1 2 8 8
+",
+    );
+
+    assert_eq!(set.clips.len(), 1);
+    assert_eq!(set.clips[0].name, "something");
+    assert_eq!(set.clips[0].frames[0].duration, 0.1);
+}
+
+#[test]
+fn game_animations_point_at_real_images() {
+    for file in [
+        "assets/animations/knight.anim",
+        "assets/animations/skeleton.anim",
+        "assets/animations/nightmare.anim",
+    ] {
+        let set = AnimationSet::load(file);
+        assert!(set.clip("idle").is_some(), "{file} has no idle clip");
+        for image in &set.images {
+            let (width, height) = image::image_dimensions(image).unwrap();
+            for clip in &set.clips {
+                for frame in clip
+                    .frames
+                    .iter()
+                    .filter(|frame| set.images[frame.image] == *image)
+                {
+                    assert!(
+                        frame.source.x + frame.source.w <= width as f32
+                            && frame.source.y + frame.source.h <= height as f32,
+                        "{} in {file} goes past the edge of {image}",
+                        clip.name
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn sounds_are_tied_to_event_names() {
+    let set = AnimationSet::parse(
+        "
+sound footstep steps/stone.wav 0.5 0.1
+clip walk
+sheet walk.png 32 16 2
+frames 0 2
+event 1 footstep
+sound land thud.ogg 1 0
+sound whoosh whoosh.wav loud 0
+",
+    );
+
+    assert_eq!(set.sounds.len(), 2);
+    assert_eq!(
+        set.sounds["footstep"],
+        SoundCue {
+            path: "steps/stone.wav".to_string(),
+            volume: 0.5,
+            pitch_variation: 0.1,
+        }
+    );
+    // Set-wide even when written among a clip's lines
+    assert_eq!(set.sounds["land"].path, "thud.ogg");
+    assert_eq!(set.clips[0].frames.len(), 2);
+}
+
+#[test]
+fn game_sounds_point_at_real_files_and_events() {
+    for file in [
+        "assets/animations/knight.anim",
+        "assets/animations/skeleton.anim",
+        "assets/animations/nightmare.anim",
+    ] {
+        let set = AnimationSet::load(file);
+        assert!(!set.sounds.is_empty(), "{file} has no sounds");
+        for (event, cue) in &set.sounds {
+            assert!(Path::new(&cue.path).exists(), "{file} plays {}", cue.path);
+            let used = set
+                .clips
+                .iter()
+                .flat_map(|clip| &clip.frames)
+                .any(|frame| frame.events.contains(event));
+            assert!(used, "{file} has a sound for {event} but no frame with it");
+        }
+    }
+}
+
+#[test]
+fn boxes_are_placed_on_flipped_frames() {
+    let set = AnimationSet::parse(
+        "
+anchor 16 32
+clip stab
+sheet stab.png 32 32 4
+frames 0 4
+hitbox 1-2 20 10 12 4 5 100 -50
+hurtbox * 10 0 12 32
+",
+    );
+    let stab = &set.clips[0];
+
+    assert_eq!(set.anchor, glm::vec2(16.0, 32.0));
+    assert!(stab.frames[0].hitboxes.is_empty() && stab.frames[3].hitboxes.is_empty());
+    let hitbox = stab.frames[2].hitboxes[0];
+    assert_eq!(hitbox.damage, 5);
+    assert_eq!(hitbox.knockback, glm::vec2(100.0, -50.0));
+    assert!(stab.frames.iter().all(|frame| frame.hurtboxes.len() == 1));
+
+    let frame = &stab.frames[1];
+    let position = glm::vec2(100.0, 50.0);
+    assert_eq!(
+        frame.place(hitbox.rect, position, false),
+        Rect::new(120.0, 60.0, 12.0, 4.0)
+    );
+    assert_eq!(
+        frame.place(hitbox.rect, position, true),
+        Rect::new(100.0, 60.0, 12.0, 4.0)
+    );
+}
+
+#[test]
+fn clips_can_have_their_own_anchor() {
+    let set = AnimationSet::parse(
+        "
+anchor 10 32
+clip stand
+sheet stand.png 32 32 1
+frames 0 1
+clip ball
+anchor 24 8
+sheet stand.png 32 32 1
+frames 0 1
+",
+    );
+    let feet = glm::vec2(100.0, 50.0);
+
+    let mut animator = Animator::new(&set, "stand");
+    assert_eq!(set.clips[0].anchor, None);
+    assert_eq!(animator.origin(&set, feet, false), glm::vec2(90.0, 18.0));
+    // Flipped frames are mirrored around the anchor, not the middle
+    assert_eq!(animator.origin(&set, feet, true), glm::vec2(78.0, 18.0));
+
+    animator.play(&set, "ball");
+    assert_eq!(set.anchor, glm::vec2(10.0, 32.0));
+    assert_eq!(animator.origin(&set, feet, false), glm::vec2(76.0, 42.0));
+}
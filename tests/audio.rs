@@ -0,0 +1,270 @@
+use std::io::Cursor;
+
+use rusty_knight::audio::audio::Audio;
+use rusty_knight::audio::cues;
+use rusty_knight::audio::mixer::{Bus, Mixer, Playback};
+use rusty_knight::audio::sound::Sound;
+use rusty_knight::core::settings::Settings;
+use rusty_knight::model::animation::AnimationSet;
+use rusty_knight::model::level::Level;
+use rusty_knight::model::world::World;
+
+// The same level the whole way through, frames long
+fn flat(level: f32, frames: usize, sample_rate: u32) -> Sound {
+    Sound::from_interleaved(&vec![level; frames], 1, sample_rate)
+}
+
+fn mixed(mixer: &mut Mixer, frames: usize) -> Vec<f32> {
+    let mut output = vec![0.0; 2 * frames];
+    mixer.mix(&mut output);
+    output
+}
+
+// Output frames a one-shot plays for
+fn frames_played(mixer: &mut Mixer, sound: usize, playback: Playback) -> usize {
+    let voice = mixer.play(sound, playback);
+    let mut frames = 0;
+    while mixer.is_playing(voice) {
+        mixed(mixer, 1);
+        frames += 1;
+    }
+    frames
+}
+
+#[test]
+fn wav_files_decode_to_stereo_frames() {
+    let spec = hound::WavSpec {
+        channels: 3,
+        sample_rate: 11025,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut bytes = Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut bytes, spec).unwrap();
+    for sample in [i16::MAX, i16::MIN / 2, 1000, 0, 16384, -1000] {
+        writer.write_sample(sample).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    // The third channel is left out
+    let sound = Sound::from_wav(Cursor::new(bytes.into_inner())).unwrap();
+    assert_eq!(sound.sample_rate, 11025);
+    assert_eq!(sound.frames, [[1.0 - 1.0 / 32768.0, -0.5], [0.0, 0.5]]);
+
+    // Mono plays on both sides
+    let click = Sound::load("assets/sounds/ui_move.wav");
+    assert!(click.frames.iter().all(|[left, right]| left == right));
+    assert!(click.duration() > 0.01 && click.duration() < 0.2);
+    assert!(Sound::from_wav(Cursor::new(b"not a wav".to_vec())).is_err());
+}
+
+#[test]
+fn ogg_files_decode_to_stereo_frames() {
+    // A hand-built stream holding a steady tone of about 500 Hz
+    let tone = Sound::load("tests/audio/tone.ogg");
+    assert_eq!(tone.sample_rate, 8000);
+    assert_eq!(tone.frames.len(), 4096);
+    assert!(tone.frames.iter().all(|[left, right]| left == right));
+
+    let peak = tone
+        .frames
+        .iter()
+        .map(|[left, _]| left.abs())
+        .fold(0.0, f32::max);
+    assert!(peak > 0.3 && peak <= 1.0, "{peak}");
+    let crossings = tone
+        .frames
+        .windows(2)
+        .filter(|pair| (pair[0][0] < 0.0) != (pair[1][0] < 0.0))
+        .count();
+    let frequency = crossings as f32 / 2.0 / tone.duration();
+    assert!((480.0..540.0).contains(&frequency), "{frequency} Hz");
+}
+
+#[test]
+fn voices_are_added_up_through_their_buses() {
+    let mut mixer = Mixer::new(1000);
+    let sound = mixer.add(flat(0.5, 100, 1000));
+
+    mixer.play(
+        sound,
+        Playback {
+            volume: 0.5,
+            ..Default::default()
+        },
+    );
+    assert_eq!(mixed(&mut mixer, 2), [0.25; 4]);
+    mixer.set_bus_volume(Bus::Effects, 0.5);
+    assert_eq!(mixed(&mut mixer, 1), [0.125; 2]);
+    mixer.master_volume = 0.5;
+    assert_eq!(mixed(&mut mixer, 1), [0.0625; 2]);
+
+    // Other buses keep their own volume, and the sum is kept in range
+    mixer.master_volume = 1.0;
+    for _ in 0..3 {
+        mixer.play(
+            sound,
+            Playback {
+                bus: Bus::Ui,
+                ..Default::default()
+            },
+        );
+    }
+    assert_eq!(mixed(&mut mixer, 1), [1.0; 2]);
+    mixer.set_bus_volume(Bus::Ui, 0.0);
+    assert_eq!(mixed(&mut mixer, 1), [0.125; 2]);
+    // Silent voices still run out
+    mixed(&mut mixer, 200);
+    assert_eq!(mixer.voice_count(), 0);
+}
+
+#[test]
+fn one_shots_end_and_music_loops() {
+    let mut mixer = Mixer::new(1000);
+    let short = mixer.add(flat(0.5, 10, 1000));
+    let tune = mixer.add(Sound::from_interleaved(&[0.1, 0.2, 0.3], 1, 1000));
+
+    let shot = mixer.play(short, Playback::default());
+    let output = mixed(&mut mixer, 12);
+    assert_eq!(output[..20], [0.5; 20]);
+    assert_eq!(output[20..], [0.0; 4]);
+    assert!(!mixer.is_playing(shot));
+
+    let music = mixer.play_music(tune, 1.0);
+    let output = mixed(&mut mixer, 7);
+    let left: Vec<f32> = output.iter().step_by(2).copied().collect();
+    assert_eq!(left, [0.1, 0.2, 0.3, 0.1, 0.2, 0.3, 0.1]);
+    // Asking for the same tune carries on with it, another one replaces it
+    assert_eq!(mixer.play_music(tune, 0.5), music);
+    assert_eq!(mixed(&mut mixer, 1), [0.1; 2]);
+    let other = mixer.play_music(short, 1.0);
+    assert!(!mixer.is_playing(music) && mixer.is_playing(other));
+    assert_eq!(mixer.voice_count(), 1);
+    mixer.stop(other);
+    assert_eq!(mixer.voice_count(), 0);
+}
+
+#[test]
+fn pitch_and_sample_rate_change_how_long_sounds_play() {
+    let mut mixer = Mixer::new(1000);
+    let sound = mixer.add(flat(0.5, 100, 500));
+    assert_eq!(frames_played(&mut mixer, sound, Playback::default()), 200);
+    let octave_up = Playback {
+        pitch: 2.0,
+        ..Default::default()
+    };
+    assert_eq!(frames_played(&mut mixer, sound, octave_up), 100);
+
+    // Varied plays spread out either side but stay within the range
+    let varied = Playback {
+        pitch_variation: 0.1,
+        ..Default::default()
+    };
+    let lengths: Vec<usize> = (0..20)
+        .map(|_| frames_played(&mut mixer, sound, varied))
+        .collect();
+    assert!(lengths.iter().all(|frames| (181..=223).contains(frames)));
+    assert!(lengths.iter().any(|frames| *frames < 200));
+    assert!(lengths.iter().any(|frames| *frames > 200));
+
+    // Pitches at or below zero still play through and end
+    for pitch in [0.0, -1.0, f32::NAN] {
+        let stuck = Playback {
+            pitch,
+            ..Default::default()
+        };
+        assert_eq!(frames_played(&mut mixer, sound, stuck), 4000);
+    }
+    let varied_to_zero = Playback {
+        pitch_variation: 1.0,
+        ..Default::default()
+    };
+    for _ in 0..20 {
+        assert!(frames_played(&mut mixer, sound, varied_to_zero) <= 4000);
+    }
+    assert_eq!(mixer.voice_count(), 0);
+}
+
+#[test]
+fn null_audio_keeps_time_and_follows_the_settings() {
+    let mut audio = Audio::null(22050);
+    assert!(audio.is_null());
+    let click = audio.load("assets/sounds/ui_move.wav");
+    assert_eq!(audio.load("assets/sounds/ui_move.wav"), click);
+
+    let voice = audio.play(click, Playback::default());
+    let rendered = audio.render(100);
+    assert_eq!(rendered.len(), 200);
+    assert!(rendered.iter().any(|sample| *sample != 0.0));
+    // Ticks move it along without anyone listening
+    for _ in 0..30 {
+        audio.update(1000.0 / 60.0);
+    }
+    assert!(!audio.is_playing(voice));
+
+    let mut settings = Settings {
+        music: false,
+        volume: 0.3,
+        ..Default::default()
+    };
+    settings.apply(&mut audio);
+    assert_eq!(audio.bus_volume(Bus::Music), 0.0);
+    assert_eq!(audio.bus_volume(Bus::Effects), 1.0);
+    assert_eq!(audio.bus_volume(Bus::Ui), 1.0);
+    assert_eq!(audio.master_volume(), 0.3);
+    settings.sound = false;
+    settings.apply(&mut audio);
+    assert_eq!(audio.bus_volume(Bus::Effects), 0.0);
+    assert_eq!(audio.bus_volume(Bus::Ui), 0.0);
+}
+
+#[test]
+fn animation_events_play_their_sounds_fading_with_distance() {
+    let set = AnimationSet::parse(
+        "
+sound footstep assets/sounds/footstep.wav 0.5 0
+clip walk
+sheet walk.png 32 16 2
+frames 0 2
+event 1 footstep
+",
+    );
+    let mut audio = Audio::null(22050);
+    let events = ["footstep".to_string(), "land".to_string()];
+    cues::play_events(&mut audio, &set, &events, 1.0);
+    assert_eq!(audio.voice_count(), 1);
+    let loud = audio.render(200);
+    audio.stop_bus(Bus::Effects);
+    cues::play_events(&mut audio, &set, &events, 0.5);
+    let quiet = audio.render(200);
+    for (loud, quiet) in loud.iter().zip(&quiet) {
+        assert!((loud * 0.5 - quiet).abs() < 1e-6);
+    }
+    audio.stop_bus(Bus::Effects);
+    cues::play_events(&mut audio, &set, &events, 0.0);
+    assert_eq!(audio.voice_count(), 0);
+
+    let listener = glm::vec2(100.0, 100.0);
+    assert_eq!(cues::falloff(glm::vec2(250.0, 100.0), listener), 1.0);
+    assert_eq!(cues::falloff(glm::vec2(100.0, 440.0), listener), 0.5);
+    assert_eq!(cues::falloff(glm::vec2(1000.0, 100.0), listener), 0.0);
+}
+
+#[test]
+fn the_world_is_heard_from_the_middle_of_the_view() {
+    let mut world = World::new(Level::load(1));
+    let mut audio = Audio::null(22050);
+    let listener = world.camera_2d.position;
+    world.player.body.position = listener;
+    world.player.events = vec!["footstep".to_string(), "swing".to_string()];
+    let skeleton = &mut world.skeletons[0];
+    skeleton.events = vec!["clatter".to_string()];
+    skeleton.body.position = listener + glm::vec2(2000.0, 0.0);
+
+    // The far skeleton can't be heard
+    cues::play_world(&mut audio, &world);
+    assert_eq!(audio.voice_count(), 2);
+    world.skeletons[0].body.position = listener;
+    cues::play_world(&mut audio, &world);
+    assert_eq!(audio.voice_count(), 5);
+}
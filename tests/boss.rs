@@ -0,0 +1,223 @@
+use rusty_knight::model::animation::AnimationSet;
+use rusty_knight::model::boss::{self, Boss};
+use rusty_knight::model::combat::{self, Combatant};
+use rusty_knight::model::fight::{Fight, FightAction, Step};
+use rusty_knight::model::level::{CollisionGrid, TileKind};
+use rusty_knight::model::physics::Body;
+use rusty_knight::model::player::Player;
+use rusty_knight::platform::input::{ACTION_COUNT, ActionState};
+
+const DT: f32 = 1.0 / 60.0;
+
+// 60x20 tiles with a floor along row 15
+fn floor() -> CollisionGrid {
+    let mut grid = CollisionGrid::new(60, 20);
+    for x in 0..60 {
+        for y in 15..20 {
+            grid.set(x, y, TileKind::Solid);
+        }
+    }
+    grid
+}
+
+// Standing on the floor of column 30, with gates 10 tiles either side
+fn boss(script: &str, animations: &AnimationSet, grid: &CollisionGrid) -> Boss {
+    let fight = Fight::parse(&format!("arena 10 6\nsize 64 52\nhealth 100\n{script}"));
+    Boss::new(fight, animations, grid, glm::vec2(30.0 * 16.0 + 8.0, 240.0))
+}
+
+fn standing_at(x: f32) -> Body {
+    Body::new(glm::vec2(x - 10.0, 196.0), glm::vec2(20.0, 44.0))
+}
+
+#[test]
+fn fights_are_read_from_text() {
+    let fight = Fight::parse(
+        "
+health 250
+mouth -40 -50
+arena 12 8
+
+phase calm
+at 2.0 charge 200
+at 0.0 face
+at 1.0 breath fire 100 -200 0.5
+at 1.5 dance
+phase angry below 0.6
+at 0.0 repeat
+phase desperate below 0.25
+",
+    );
+
+    assert_eq!(fight.health, 250);
+    assert_eq!(fight.mouth, glm::vec2(-40.0, -50.0));
+    assert_eq!((fight.arena_width, fight.arena_height), (12, 8));
+    assert_eq!(fight.phases.len(), 3);
+    // Sorted by time, the unknown action dropped
+    assert_eq!(
+        fight.phases[0].steps,
+        vec![
+            Step {
+                time: 0.0,
+                action: FightAction::Face,
+            },
+            Step {
+                time: 1.0,
+                action: FightAction::Breath {
+                    clip: "fire".to_string(),
+                    velocity: glm::vec2(100.0, -200.0),
+                    gravity: 0.5,
+                },
+            },
+            Step {
+                time: 2.0,
+                action: FightAction::Charge(200.0),
+            },
+        ]
+    );
+
+    assert_eq!(fight.phase_for(1.0), 0);
+    assert_eq!(fight.phase_for(0.6), 1);
+    assert_eq!(fight.phase_for(0.3), 1);
+    assert_eq!(fight.phase_for(0.1), 2);
+
+    // Never left without a phase to play
+    assert_eq!(Fight::parse("health 10").phases.len(), 1);
+}
+
+#[test]
+fn arena_locks_behind_the_player_and_opens_on_defeat() {
+    let mut grid = floor();
+    let animations = AnimationSet::load(boss::ANIMATIONS);
+    let mut boss = boss("phase only\nat 0.0 idle", &animations, &grid);
+
+    boss.update(DT, &animations, &mut grid, &standing_at(100.0));
+    assert!(!boss.is_awake() && !boss.arena.is_locked());
+    assert_eq!(grid.get(20, 14), TileKind::Empty);
+
+    boss.update(DT, &animations, &mut grid, &standing_at(360.0));
+    assert!(boss.is_awake() && boss.arena.is_locked());
+    for y in 9..15 {
+        assert!(grid.is_solid(20, y) && grid.is_solid(40, y));
+    }
+    assert!(!grid.is_solid(20, 8));
+
+    boss.fighter.health.damage(100);
+    boss.update(DT, &animations, &mut grid, &standing_at(360.0));
+    assert!(!boss.arena.is_locked());
+    assert_eq!(grid.get(20, 14), TileKind::Empty);
+    assert!(!boss.despawned());
+    // Fades out before it is gone
+    for _ in 0..90 {
+        boss.update(DT, &animations, &mut grid, &standing_at(360.0));
+    }
+    let opacity = boss.pose(&animations, 1.0).opacity;
+    assert!(opacity > 0.0 && opacity < 1.0, "{opacity}");
+    for _ in 0..60 {
+        boss.update(DT, &animations, &mut grid, &standing_at(360.0));
+    }
+    assert!(boss.despawned());
+}
+
+#[test]
+fn charges_stop_at_the_gates() {
+    let mut grid = floor();
+    let animations = AnimationSet::load(boss::ANIMATIONS);
+    let mut boss = boss(
+        "phase only\nat 0.0 face\nat 0.1 charge 300",
+        &animations,
+        &grid,
+    );
+    let player = standing_at(560.0);
+
+    for _ in 0..120 {
+        boss.update(DT, &animations, &mut grid, &player);
+    }
+
+    // Turned to face the player and ran into the right hand gate
+    assert!(!boss.facing_left);
+    assert_eq!(boss.body.velocity.x, 0.0);
+    assert!((boss.body.right() - 40.0 * 16.0).abs() < 0.1);
+    assert_eq!(boss.animator.clip_name(&animations), "idle");
+}
+
+#[test]
+fn breath_lands_and_burns_the_knight() {
+    let mut grid = floor();
+    let animations = AnimationSet::load(boss::ANIMATIONS);
+    let mut boss = boss(
+        "mouth -40 -40\nphase only\nat 0.0 breath breath 120 -100 0.25\nat 5.0 repeat",
+        &animations,
+        &grid,
+    );
+    let mut player = Player::load(glm::vec2(400.0, 240.0));
+    let mut actions = ActionState::new();
+    // Wakes up with the knight inside, then spits once
+    let mut projectiles = boss.update(DT, &animations, &mut grid, &player.body);
+    projectiles.extend(boss.update(DT, &animations, &mut grid, &player.body));
+    assert_eq!(projectiles.len(), 1);
+    assert!(projectiles[0].body.velocity.x < 0.0);
+
+    // Lands in front of the boss, so move the knight to where it burns
+    let mut projectile = projectiles.remove(0);
+    while !projectile.has_landed() {
+        projectile.update(DT, &animations, &grid);
+    }
+    assert!((projectile.body.bottom() - 240.0).abs() < 0.1);
+    assert!(projectile.body.centre().x < boss.body.position.x);
+    player = Player::load(glm::vec2(projectile.body.centre().x, 240.0));
+
+    let mut events = Vec::new();
+    for _ in 0..30 {
+        actions.advance([0.0; ACTION_COUNT]);
+        player.update(DT, &actions, &grid);
+        projectile.update(DT, &animations, &grid);
+        events.extend(combat::exchange_blows(&mut [&mut player, &mut projectile]));
+    }
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        player.fighter.health.current,
+        player.fighter.health.max - 10
+    );
+
+    for _ in 0..120 {
+        projectile.update(DT, &animations, &grid);
+    }
+    assert!(projectile.gone());
+    assert!(projectile.hitboxes().is_empty());
+}
+
+#[test]
+fn phases_change_with_health() {
+    let mut grid = floor();
+    let animations = AnimationSet::load(boss::ANIMATIONS);
+    let mut boss = boss(
+        "phase calm\nat 0.0 idle\nphase angry below 0.5\nat 0.0 face",
+        &animations,
+        &grid,
+    );
+    let player = standing_at(560.0);
+
+    boss.update(DT, &animations, &mut grid, &player);
+    assert_eq!(boss.phase_name(), "calm");
+    boss.fighter.health.damage(50);
+    boss.update(DT, &animations, &mut grid, &player);
+    assert_eq!(boss.phase_name(), "angry");
+    // Its new timeline starts straight away
+    assert!(!boss.facing_left);
+}
+
+#[test]
+fn the_nightmare_fight_has_every_clip_it_needs() {
+    let fight = Fight::load(boss::FIGHT);
+    let animations = AnimationSet::load(boss::ANIMATIONS);
+
+    assert!(fight.phases.len() > 1);
+    for step in fight.phases.iter().flat_map(|phase| &phase.steps) {
+        if let FightAction::Breath { clip, .. } = &step.action {
+            assert!(animations.clip(clip).is_some(), "no clip {clip}");
+            assert!(animations.clip(&format!("{clip}_burst")).is_some());
+        }
+    }
+    assert!(animations.clip("gallop").is_some());
+}
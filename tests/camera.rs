@@ -0,0 +1,110 @@
+use rusty_knight::constants::graphics;
+use rusty_knight::model::game_object::OrthoCamera;
+
+// The view at zoom 1
+const VIEW_W: f32 = graphics::MAP_WDITH_PIXELS as f32;
+const VIEW_H: f32 = graphics::MAP_HEIGHT_PIXELS as f32;
+
+// A level three views wide and two views tall, starting at the origin
+fn bounded() -> OrthoCamera {
+    let mut camera = OrthoCamera::new();
+    camera.set_bounds(glm::vec2(0.0, 0.0), glm::vec2(3.0 * VIEW_W, 2.0 * VIEW_H));
+    camera
+}
+
+#[test]
+fn zoom_changes_how_much_of_the_world_is_seen() {
+    let mut camera = OrthoCamera::new();
+    assert_eq!(camera.view_size(), glm::vec2(VIEW_W, VIEW_H));
+    assert_eq!(camera.top_left(), glm::vec2(0.0, 0.0));
+
+    camera.set_zoom(2.0);
+    assert_eq!(camera.view_size(), glm::vec2(0.5 * VIEW_W, 0.5 * VIEW_H));
+    assert_eq!(camera.top_left(), glm::vec2(0.25 * VIEW_W, 0.25 * VIEW_H));
+    camera.set_zoom(0.5);
+    assert_eq!(camera.view_size(), glm::vec2(2.0 * VIEW_W, 2.0 * VIEW_H));
+    // Zero or negative zoom is kept just above zero
+    camera.set_zoom(0.0);
+    assert_eq!(camera.zoom, 0.01);
+}
+
+#[test]
+fn following_stops_at_each_edge_of_the_bounds() {
+    let mut camera = bounded();
+    let half = glm::vec2(0.5 * VIEW_W, 0.5 * VIEW_H);
+    let far = glm::vec2(3.0 * VIEW_W, 2.0 * VIEW_H);
+
+    camera.follow(glm::vec2(-500.0, VIEW_H), 16.0);
+    assert_eq!(camera.position, glm::vec2(half.x, VIEW_H));
+    camera.follow(glm::vec2(far.x + 500.0, VIEW_H), 16.0);
+    assert_eq!(camera.position, glm::vec2(far.x - half.x, VIEW_H));
+    camera.follow(glm::vec2(VIEW_W, -500.0), 16.0);
+    assert_eq!(camera.position, glm::vec2(VIEW_W, half.y));
+    camera.follow(glm::vec2(VIEW_W, far.y + 500.0), 16.0);
+    assert_eq!(camera.position, glm::vec2(VIEW_W, far.y - half.y));
+
+    // Inside the bounds it goes where it is told
+    camera.follow(glm::vec2(VIEW_W, VIEW_H), 16.0);
+    assert_eq!(camera.position, glm::vec2(VIEW_W, VIEW_H));
+}
+
+#[test]
+fn easing_moves_part_of_the_way_and_stays_inside() {
+    let mut camera = bounded();
+    camera.follow(glm::vec2(VIEW_W, VIEW_H), 16.0);
+    camera.follow_speed = 5.0;
+    camera.follow(glm::vec2(VIEW_W + 1000.0, VIEW_H), 100.0);
+    assert_eq!(camera.position, glm::vec2(VIEW_W + 500.0, VIEW_H));
+    camera.follow(glm::vec2(-1000.0, VIEW_H), 1000.0);
+    assert_eq!(camera.position, glm::vec2(0.5 * VIEW_W, VIEW_H));
+}
+
+#[test]
+fn setting_bounds_or_zoom_pulls_the_view_back_inside() {
+    let mut camera = OrthoCamera::new();
+    camera.position = glm::vec2(-1000.0, 5000.0);
+    camera.set_bounds(glm::vec2(0.0, 0.0), glm::vec2(3.0 * VIEW_W, 2.0 * VIEW_H));
+    assert_eq!(camera.position, glm::vec2(0.5 * VIEW_W, 1.5 * VIEW_H));
+
+    // Zooming out shows more, so the centre has to move further in
+    camera.set_zoom(0.75);
+    let half = camera.view_size() * 0.5;
+    assert_eq!(camera.position, glm::vec2(half.x, 2.0 * VIEW_H - half.y));
+
+    // Moving by hand isn't checked until asked
+    camera.position = glm::vec2(10.0 * VIEW_W, -VIEW_H);
+    camera.clamp_to_bounds();
+    assert_eq!(camera.position, glm::vec2(3.0 * VIEW_W - half.x, half.y));
+    camera.clear_bounds();
+    camera.position = glm::vec2(-VIEW_W, -VIEW_H);
+    camera.clamp_to_bounds();
+    assert_eq!(camera.position, glm::vec2(-VIEW_W, -VIEW_H));
+}
+
+#[test]
+fn levels_smaller_than_the_view_are_centred() {
+    let mut camera = OrthoCamera::new();
+    // Narrower than the view but taller
+    camera.set_bounds(
+        glm::vec2(100.0, 0.0),
+        glm::vec2(100.0 + 0.5 * VIEW_W, 3.0 * VIEW_H),
+    );
+    camera.follow(glm::vec2(0.0, 0.0), 16.0);
+    assert_eq!(
+        camera.position,
+        glm::vec2(100.0 + 0.25 * VIEW_W, 0.5 * VIEW_H)
+    );
+    camera.follow(glm::vec2(5000.0, 2.0 * VIEW_H), 16.0);
+    assert_eq!(
+        camera.position,
+        glm::vec2(100.0 + 0.25 * VIEW_W, 2.0 * VIEW_H)
+    );
+
+    // Zooming in far enough lets it scroll again
+    camera.set_zoom(4.0);
+    camera.follow(glm::vec2(0.0, 0.0), 16.0);
+    assert_eq!(
+        camera.position,
+        glm::vec2(100.0 + 0.125 * VIEW_W, 0.125 * VIEW_H)
+    );
+}
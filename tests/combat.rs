@@ -0,0 +1,196 @@
+use rusty_knight::model::combat::{self, Combatant, DamageEvent, Fighter, Health, Hitbox, Team};
+use rusty_knight::model::geometry::Rect;
+use rusty_knight::model::level::{CollisionGrid, TileKind};
+use rusty_knight::model::player::Player;
+use rusty_knight::platform::input::{ACTION_COUNT, Action, ActionState};
+
+const DT: f32 = 1.0 / 60.0;
+
+// Stands still with whatever boxes the test gives it
+struct Dummy {
+    fighter: Fighter,
+    hitboxes: Vec<Hitbox>,
+    hurtbox: Rect,
+    hits: Vec<DamageEvent>,
+}
+
+impl Dummy {
+    fn new(team: Team, x: f32) -> Self {
+        Dummy {
+            fighter: Fighter::new(team, Health::new(30, 0.5)),
+            hitboxes: Vec::new(),
+            hurtbox: Rect::new(x, 0.0, 20.0, 40.0),
+            hits: Vec::new(),
+        }
+    }
+
+    // Swings at everything from x to x + 30
+    fn swing(&mut self, x: f32, damage: i32) {
+        self.hitboxes = vec![Hitbox {
+            rect: Rect::new(x, 10.0, 30.0, 10.0),
+            damage,
+            knockback: glm::vec2(100.0, -50.0),
+        }];
+    }
+}
+
+impl Combatant for Dummy {
+    fn fighter(&self) -> &Fighter {
+        &self.fighter
+    }
+
+    fn fighter_mut(&mut self) -> &mut Fighter {
+        &mut self.fighter
+    }
+
+    fn hitboxes(&self) -> Vec<Hitbox> {
+        self.hitboxes.clone()
+    }
+
+    fn hurtboxes(&self) -> Vec<Rect> {
+        vec![self.hurtbox]
+    }
+
+    fn take_hit(&mut self, hit: &DamageEvent) {
+        self.hits.push(*hit);
+    }
+}
+
+fn exchange(a: &mut Dummy, b: &mut Dummy) -> Vec<DamageEvent> {
+    combat::exchange_blows(&mut [a, b])
+}
+
+#[test]
+fn hits_knock_targets_away() {
+    let mut knight = Dummy::new(Team::Knight, 0.0);
+    let mut skeleton = Dummy::new(Team::Monsters, 40.0);
+    knight.swing(15.0, 10);
+
+    let events = exchange(&mut knight, &mut skeleton);
+
+    assert_eq!(events.len(), 1);
+    assert_eq!((events[0].attacker, events[0].target), (0, 1));
+    assert_eq!(events[0].knockback, glm::vec2(100.0, -50.0));
+    assert_eq!(events[0].position, glm::vec2(42.5, 15.0));
+    assert_eq!(skeleton.fighter.health.current, 20);
+    assert_eq!(skeleton.hits, events);
+
+    // Swinging the other way knocks the other way
+    let mut skeleton = Dummy::new(Team::Monsters, 40.0);
+    let mut knight = Dummy::new(Team::Knight, 60.0);
+    knight.swing(45.0, 10);
+    let events = exchange(&mut knight, &mut skeleton);
+    assert_eq!(events[0].knockback.x, -100.0);
+}
+
+#[test]
+fn teams_do_not_hurt_themselves() {
+    let mut skeleton = Dummy::new(Team::Monsters, 0.0);
+    let mut other = Dummy::new(Team::Monsters, 20.0);
+    skeleton.swing(10.0, 10);
+
+    assert!(exchange(&mut skeleton, &mut other).is_empty());
+    assert_eq!(other.fighter.health.current, 30);
+}
+
+#[test]
+fn one_swing_hits_once() {
+    let mut knight = Dummy::new(Team::Knight, 0.0);
+    let mut skeleton = Dummy::new(Team::Monsters, 40.0);
+    knight.swing(15.0, 10);
+
+    exchange(&mut knight, &mut skeleton);
+    // Still swinging after the target has recovered
+    skeleton.fighter.health.tick(1.0);
+    assert!(exchange(&mut knight, &mut skeleton).is_empty());
+
+    // A new swing lands again
+    knight.hitboxes.clear();
+    exchange(&mut knight, &mut skeleton);
+    knight.swing(15.0, 10);
+    assert_eq!(exchange(&mut knight, &mut skeleton).len(), 1);
+    assert_eq!(skeleton.fighter.health.current, 10);
+}
+
+#[test]
+fn hits_give_invulnerability() {
+    let mut skeleton = Dummy::new(Team::Monsters, 40.0);
+    let mut first = Dummy::new(Team::Knight, 0.0);
+    let mut second = Dummy::new(Team::Knight, 0.0);
+    first.swing(15.0, 10);
+    second.swing(15.0, 10);
+
+    let events = combat::exchange_blows(&mut [&mut first, &mut second, &mut skeleton]);
+    assert_eq!(events.len(), 1);
+    assert!(skeleton.fighter.health.is_invulnerable());
+
+    skeleton.fighter.health.tick(0.5);
+    assert!(skeleton.fighter.health.can_be_hurt());
+    let events = combat::exchange_blows(&mut [&mut first, &mut second, &mut skeleton]);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].attacker, 1);
+}
+
+#[test]
+fn health_stays_in_range() {
+    let mut health = Health::new(30, 0.0);
+    health.damage(50);
+    assert_eq!(health.current, 0);
+    assert!(health.is_dead() && !health.can_be_hurt());
+
+    // The dead cannot be healed
+    health.heal(10);
+    assert_eq!(health.current, 0);
+
+    let mut health = Health::new(30, 0.0);
+    health.damage(5);
+    health.heal(10);
+    assert_eq!(health.current, 30);
+}
+
+#[test]
+fn the_dead_do_not_fight() {
+    let mut knight = Dummy::new(Team::Knight, 0.0);
+    let mut skeleton = Dummy::new(Team::Monsters, 10.0);
+    knight.swing(15.0, 10);
+    skeleton.swing(-10.0, 10);
+    skeleton.fighter.health.damage(100);
+
+    assert!(exchange(&mut knight, &mut skeleton).is_empty());
+    assert_eq!(knight.fighter.health.current, 30);
+}
+
+#[test]
+fn knight_attacks_hit_in_front_only() {
+    let mut grid = CollisionGrid::new(30, 20);
+    for x in 0..30 {
+        grid.set(x, 15, TileKind::Solid);
+    }
+    let mut player = Player::load(glm::vec2(200.0, 240.0));
+    let mut actions = ActionState::new();
+    let mut front = Dummy::new(Team::Monsters, 215.0);
+    let mut behind = Dummy::new(Team::Monsters, 165.0);
+    front.hurtbox.y = 200.0;
+    behind.hurtbox.y = 200.0;
+
+    let mut events = Vec::new();
+    for i in 0..40 {
+        let mut values = [0.0; ACTION_COUNT];
+        if i == 10 {
+            values[Action::Attack as usize] = 1.0;
+        }
+        actions.advance(values);
+        player.update(DT, &actions, &grid);
+        events.extend(combat::exchange_blows(&mut [
+            &mut player,
+            &mut front,
+            &mut behind,
+        ]));
+    }
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].target, 1);
+    assert!(events[0].knockback.x > 0.0);
+    assert_eq!(front.fighter.health.current, 20);
+    assert_eq!(behind.fighter.health.current, 30);
+}
@@ -0,0 +1,103 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use rusty_knight::renderer::renderer::State;
+
+pub const WIDTH: i32 = 320;
+pub const HEIGHT: i32 = 240;
+
+// Largest difference allowed on any colour channel before a pixel counts as changed
+pub const CHANNEL_TOLERANCE: u8 = 8;
+// Fraction of changed pixels tolerated, covers rasterisation differences between adapters
+pub const MISMATCH_FRACTION: f32 = 0.002;
+
+// Set to regenerate the checked-in references from the current renderer
+const BLESS_VARIABLE: &str = "RUSTY_KNIGHT_BLESS";
+
+pub struct ImageDiff {
+    pub mismatched_pixels: usize,
+    pub diff: image::RgbaImage,
+}
+
+pub fn new_state() -> Option<State> {
+    match pollster::block_on(State::new_headless((WIDTH, HEIGHT))) {
+        Ok(state) => Some(state),
+        Err(e) => {
+            eprintln!("Skipping golden image test, {e}");
+            None
+        }
+    }
+}
+
+pub fn compare(actual: &image::RgbaImage, reference: &image::RgbaImage) -> ImageDiff {
+    let mut diff = image::RgbaImage::new(actual.width(), actual.height());
+    let mut mismatched_pixels: usize = 0;
+
+    for (x, y, pixel) in actual.enumerate_pixels() {
+        let expected = reference.get_pixel(x, y);
+        let difference = pixel
+            .0
+            .iter()
+            .zip(expected.0.iter())
+            .map(|(a, b)| a.abs_diff(*b))
+            .max()
+            .unwrap();
+
+        // Changed pixels in red, unchanged ones as a faded copy of the reference
+        if difference > CHANNEL_TOLERANCE {
+            mismatched_pixels += 1;
+            diff.put_pixel(x, y, image::Rgba([255, 0, 0, 255]));
+        } else {
+            let faded = expected.0.map(|c| c / 4);
+            diff.put_pixel(x, y, image::Rgba([faded[0], faded[1], faded[2], 255]));
+        }
+    }
+
+    ImageDiff {
+        mismatched_pixels,
+        diff,
+    }
+}
+
+pub fn check(name: &str, actual: &image::RgbaImage) {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let reference_path = manifest_dir.join("tests/golden").join(format!("{name}.png"));
+
+    if env::var_os(BLESS_VARIABLE).is_some() {
+        actual.save(&reference_path).unwrap();
+        println!("Blessed {}", reference_path.display());
+        return;
+    }
+
+    let reference = match image::open(&reference_path) {
+        Ok(reference) => reference.to_rgba8(),
+        Err(e) => panic!(
+            "Cannot read reference {}: {e}, run with {BLESS_VARIABLE}=1 to create it",
+            reference_path.display()
+        ),
+    };
+    assert_eq!(
+        actual.dimensions(),
+        reference.dimensions(),
+        "{name}: frame size differs from the reference"
+    );
+
+    let result = compare(actual, &reference);
+    let allowed = (MISMATCH_FRACTION * (actual.width() * actual.height()) as f32) as usize;
+    if result.mismatched_pixels > allowed {
+        let output_dir = manifest_dir.join("target/golden");
+        fs::create_dir_all(&output_dir).unwrap();
+        let actual_path = output_dir.join(format!("{name}_actual.png"));
+        let diff_path = output_dir.join(format!("{name}_diff.png"));
+        actual.save(&actual_path).unwrap();
+        result.diff.save(&diff_path).unwrap();
+
+        panic!(
+            "{name}: {} pixels differ from the reference (allowed {allowed}), see {} and {}",
+            result.mismatched_pixels,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
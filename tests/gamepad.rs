@@ -0,0 +1,150 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use glfw::{GamepadAxis, GamepadButton};
+use rusty_knight::platform::gamepad::{GamepadSnapshot, GamepadSource, STICK_DEADZONE};
+use rusty_knight::platform::input::{Action, Input, InputMap};
+
+// Pads the test can plug in, pull out and press buttons on between ticks
+#[derive(Clone, Default)]
+struct FakePads {
+    pads: Rc<RefCell<Vec<(usize, GamepadSnapshot)>>>,
+}
+
+impl FakePads {
+    fn plug_in(&self, slot: usize) {
+        self.pads
+            .borrow_mut()
+            .push((slot, GamepadSnapshot::default()));
+    }
+
+    fn unplug(&self, slot: usize) {
+        self.pads.borrow_mut().retain(|(s, _)| *s != slot);
+    }
+
+    fn update(&self, slot: usize, change: impl Fn(&mut GamepadSnapshot)) {
+        for (s, pad) in self.pads.borrow_mut().iter_mut() {
+            if *s == slot {
+                change(pad);
+            }
+        }
+    }
+}
+
+impl GamepadSource for FakePads {
+    fn connected(&self) -> Vec<usize> {
+        self.pads.borrow().iter().map(|(s, _)| *s).collect()
+    }
+
+    fn name(&self, slot: usize) -> String {
+        format!("Fake pad {slot}")
+    }
+
+    fn snapshot(&self, slot: usize) -> Option<GamepadSnapshot> {
+        self.pads
+            .borrow()
+            .iter()
+            .find(|(s, _)| *s == slot)
+            .map(|(_, pad)| *pad)
+    }
+}
+
+fn input_with(pads: &FakePads) -> Input {
+    let mut input = Input::new(InputMap::default());
+    input.set_gamepad_source(Box::new(pads.clone()));
+    input
+}
+
+#[test]
+fn buttons_drive_actions_with_edges() {
+    let pads = FakePads::default();
+    pads.plug_in(0);
+    let mut input = input_with(&pads);
+
+    pads.update(0, |pad| pad.buttons[GamepadButton::ButtonA as usize] = true);
+    assert!(input.tick().pressed(Action::Jump));
+    assert!(input.tick().held(Action::Jump));
+    assert!(!input.tick().pressed(Action::Jump));
+
+    pads.update(0, |pad| {
+        pad.buttons[GamepadButton::ButtonA as usize] = false
+    });
+    assert!(input.tick().released(Action::Jump));
+}
+
+#[test]
+fn sticks_ignore_the_deadzone() {
+    let pads = FakePads::default();
+    pads.plug_in(0);
+    let mut input = input_with(&pads);
+
+    pads.update(0, |pad| {
+        pad.axes[GamepadAxis::AxisLeftX as usize] = 0.9 * STICK_DEADZONE;
+    });
+    let actions = input.tick();
+    assert_eq!(actions.axis(Action::MoveLeft, Action::MoveRight), 0.0);
+
+    // Drift on one axis stays inside the deadzone while the other is pushed
+    pads.update(0, |pad| {
+        pad.axes[GamepadAxis::AxisLeftX as usize] = -1.0;
+        pad.axes[GamepadAxis::AxisLeftY as usize] = 0.01;
+    });
+    let actions = input.tick();
+    assert!(actions.axis(Action::MoveLeft, Action::MoveRight) < -0.99);
+    assert!(actions.held(Action::MoveLeft));
+    assert!(!actions.held(Action::MoveDown));
+}
+
+#[test]
+fn partly_pushed_sticks_are_analog() {
+    let pads = FakePads::default();
+    pads.plug_in(0);
+    let mut input = input_with(&pads);
+
+    pads.update(0, |pad| pad.axes[GamepadAxis::AxisLeftY as usize] = -0.5);
+    let actions = input.tick();
+    let up = actions.value(Action::MoveUp);
+    assert!(up > 0.0 && up < 0.5);
+    assert!(!actions.held(Action::MoveUp));
+}
+
+#[test]
+fn triggers_press_past_halfway() {
+    let pads = FakePads::default();
+    pads.plug_in(0);
+    let mut input = input_with(&pads);
+
+    assert!(!input.tick().held(Action::Attack));
+
+    pads.update(0, |pad| {
+        pad.axes[GamepadAxis::AxisRightTrigger as usize] = 0.5
+    });
+    assert!(input.tick().pressed(Action::Attack));
+}
+
+#[test]
+fn pads_can_be_plugged_in_and_out() {
+    let pads = FakePads::default();
+    let mut input = input_with(&pads);
+    input.tick();
+    assert!(input.gamepads().connected().is_empty());
+
+    pads.plug_in(2);
+    pads.update(2, |pad| {
+        pad.buttons[GamepadButton::ButtonStart as usize] = true
+    });
+    assert!(input.tick().pressed(Action::Pause));
+    assert_eq!(input.gamepads().connected(), &[2]);
+
+    pads.unplug(2);
+    assert!(input.tick().released(Action::Pause));
+    assert!(input.gamepads().connected().is_empty());
+}
+
+#[test]
+fn pad_bindings_survive_the_config_file() {
+    let map = InputMap::default();
+    let loaded = InputMap::from_config(&map.to_config());
+
+    assert_eq!(loaded.to_config(), map.to_config());
+}
@@ -0,0 +1,114 @@
+use rusty_knight::model::boss::Boss;
+use rusty_knight::model::combat::DamageEvent;
+use rusty_knight::model::fight::Fight;
+use rusty_knight::model::level::Level;
+use rusty_knight::model::physics::Body;
+use rusty_knight::model::world::World;
+use rusty_knight::ui::hud::{self, Hud};
+
+const DT: f32 = 1.0 / 60.0;
+
+fn hit(damage: i32, position: glm::Vec2) -> DamageEvent {
+    DamageEvent {
+        attacker: 1,
+        target: 0,
+        damage,
+        knockback: glm::vec2(0.0, 0.0),
+        position,
+    }
+}
+
+#[test]
+fn effects_are_picked_by_how_big_the_change_is() {
+    assert_eq!(hud::damage_effect(8), "minimum_damage");
+    assert_eq!(hud::damage_effect(12), "medium_damage");
+    assert_eq!(hud::damage_effect(19), "medium_damage");
+    assert_eq!(hud::damage_effect(20), "critical_damage");
+    assert_eq!(hud::healing_effect(5), "regeneration");
+    assert_eq!(hud::healing_effect(40), "healing");
+
+    // Every effect the HUD picks is in its animation file
+    let world = World::new(Level::load(1));
+    let hud = Hud::new(&world);
+    for clip in [
+        "minimum_damage",
+        "medium_damage",
+        "critical_damage",
+        "healing",
+        "regeneration",
+    ] {
+        assert!(hud.animations.clip(clip).is_some(), "{clip}");
+    }
+}
+
+#[test]
+fn hits_and_heals_leave_effects_where_they_happened() {
+    let mut world = World::new(Level::load(1));
+    let mut hud = Hud::new(&world);
+    assert_eq!(hud.player_shown, 1.0);
+
+    world.damage_events = vec![hit(20, glm::vec2(100.0, 50.0)), hit(8, glm::vec2(0.0, 0.0))];
+    world.player.fighter.health.damage(28);
+    hud.update(DT, &world);
+    assert_eq!(hud.effects.len(), 2);
+    assert_eq!(hud.effects[0].position, glm::vec2(100.0, 50.0));
+    world.damage_events.clear();
+
+    // The bar drains towards the real health rather than jumping there
+    let fraction =
+        world.player.fighter.health.current as f32 / world.player.fighter.health.max as f32;
+    assert!(hud.player_shown < 1.0 && hud.player_shown > fraction);
+    for _ in 0..120 {
+        hud.update(DT, &world);
+    }
+    assert_eq!(hud.player_shown, fraction);
+    // and the effects float up and go away once played
+    assert!(hud.effects.is_empty());
+
+    world.player.fighter.health.heal(25);
+    hud.update(DT, &world);
+    assert_eq!(hud.effects.len(), 1);
+    let body = &world.player.body;
+    assert!(hud.effects[0].position.y <= body.position.y);
+    assert!((hud.effects[0].position.x - body.centre().x).abs() < 1e-3);
+    hud.update(DT, &world);
+    assert_eq!(hud.effects.len(), 1);
+}
+
+#[test]
+fn the_boss_bar_shows_only_during_the_fight() {
+    let mut world = World::new(Level::load(1));
+    let feet = world.player.body.position + glm::vec2(10.0, 44.0);
+    let boss = Boss::new(
+        Fight::parse("arena 10 6\nsize 64 52\nhealth 100\nphase only\nat 0.0 idle"),
+        &world.boss_animations,
+        &world.level.collision,
+        feet,
+    );
+    world.boss = Some(boss);
+    let mut hud = Hud::new(&world);
+    hud.update(DT, &world);
+    assert_eq!(hud.boss_shown, None);
+
+    let body = Body::new(world.player.body.position, world.player.body.size);
+    let boss = world.boss.as_mut().unwrap();
+    boss.update(
+        DT,
+        &world.boss_animations,
+        &mut world.level.collision,
+        &body,
+    );
+    assert!(boss.is_awake());
+    boss.fighter.health.damage(40);
+    hud.update(DT, &world);
+    // Fills up from empty before settling on what the boss has left
+    assert!(hud.boss_shown.unwrap() < 0.6);
+    for _ in 0..120 {
+        hud.update(DT, &world);
+    }
+    assert_eq!(hud.boss_shown, Some(0.6));
+
+    world.boss = None;
+    hud.update(DT, &world);
+    assert_eq!(hud.boss_shown, None);
+}
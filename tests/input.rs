@@ -0,0 +1,149 @@
+use glfw::{Key, Modifiers, MouseButton, WindowEvent};
+use rusty_knight::platform::input::{ACTION_COUNT, Action, ActionState, Binding, Input, InputMap};
+
+fn key(key: Key, action: glfw::Action) -> WindowEvent {
+    WindowEvent::Key(key, 0, action, Modifiers::empty())
+}
+
+// Action values with only the given ones held
+fn holding(actions: &[Action]) -> [f32; ACTION_COUNT] {
+    let mut values = [0.0; ACTION_COUNT];
+    for action in actions {
+        values[*action as usize] = 1.0;
+    }
+    values
+}
+
+#[test]
+fn config_lines_bind_and_unbind_actions() {
+    let map = InputMap::from_config(
+        "
+# comments and blank lines are skipped
+bind Jump W
+bind Jump MouseRight
+unbind Roll
+",
+    );
+    assert_eq!(
+        map.bindings_for(Action::Jump),
+        [Binding::Key(Key::W), Binding::Mouse(MouseButton::Button2)]
+    );
+    assert!(map.bindings_for(Action::Roll).is_empty());
+    // Actions the file leaves out keep their defaults
+    assert_eq!(
+        map.bindings_for(Action::Pause),
+        InputMap::default().bindings_for(Action::Pause)
+    );
+
+    // An unbound action stays unbound after a save
+    let saved = InputMap::from_config(&map.to_config());
+    assert!(saved.bindings_for(Action::Roll).is_empty());
+    assert_eq!(
+        saved.bindings_for(Action::Jump),
+        map.bindings_for(Action::Jump)
+    );
+}
+
+#[test]
+fn extra_spaces_between_words_are_fine() {
+    let map = InputMap::from_config("bind  Jump   Space\n  unbind    Roll  ");
+    assert_eq!(map.bindings_for(Action::Jump), [Binding::Key(Key::Space)]);
+    assert!(map.bindings_for(Action::Roll).is_empty());
+}
+
+#[test]
+fn lines_it_cannot_read_are_skipped() {
+    let map = InputMap::from_config(
+        "
+bind Jump NoSuchKey
+bind Fly Space
+bind Jump
+press Jump Space
+bind Attack L
+",
+    );
+    // Jump was only mentioned on lines that were thrown away, so it keeps its default
+    assert_eq!(
+        map.bindings_for(Action::Jump),
+        InputMap::default().bindings_for(Action::Jump)
+    );
+    assert_eq!(map.bindings_for(Action::Attack), [Binding::Key(Key::L)]);
+    assert!(
+        map.actions_for(Binding::Key(Key::Space))
+            .contains(&Action::Jump)
+    );
+}
+
+#[test]
+fn rebinding_replaces_the_binding_on_the_same_device() {
+    let defaults = InputMap::default();
+    let mut map = defaults.clone();
+    let without = |action: Action, dropped: fn(&Binding) -> bool| -> Vec<Binding> {
+        let mut bindings = defaults.bindings_for(action);
+        bindings.retain(|binding| !dropped(binding));
+        bindings
+    };
+
+    // K was Roll's, it moves over to Jump and Space is let go, the pad keeps its buttons
+    map.rebind(Action::Jump, Binding::Key(Key::K));
+    let mut jump = without(Action::Jump, |binding| matches!(binding, Binding::Key(_)));
+    jump.push(Binding::Key(Key::K));
+    assert_eq!(map.bindings_for(Action::Jump), jump);
+    assert_eq!(map.actions_for(Binding::Key(Key::K)), [Action::Jump]);
+    assert!(map.actions_for(Binding::Key(Key::Space)).is_empty());
+    assert_eq!(
+        map.bindings_for(Action::Roll),
+        without(Action::Roll, |binding| *binding == Binding::Key(Key::K))
+    );
+
+    // Binding twice doesn't add it twice
+    map.bind(Action::Jump, Binding::Key(Key::K));
+    assert_eq!(map.actions_for(Binding::Key(Key::K)), [Action::Jump]);
+}
+
+#[test]
+fn presses_and_releases_last_one_advance() {
+    let mut state = ActionState::new();
+    state.advance(holding(&[Action::Jump]));
+    assert!(state.pressed(Action::Jump) && state.held(Action::Jump));
+    state.advance(holding(&[Action::Jump]));
+    assert!(!state.pressed(Action::Jump) && state.held(Action::Jump));
+    state.advance(holding(&[]));
+    assert!(state.released(Action::Jump) && !state.held(Action::Jump));
+    state.advance(holding(&[]));
+    assert!(!state.released(Action::Jump));
+
+    // Analog values count as held past the threshold, axes cancel out
+    let mut values = holding(&[Action::MoveRight]);
+    values[Action::MoveLeft as usize] = 0.25;
+    state.advance(values);
+    assert!(!state.held(Action::MoveLeft) && state.held(Action::MoveRight));
+    assert_eq!(state.axis(Action::MoveLeft, Action::MoveRight), 0.75);
+}
+
+#[test]
+fn taps_between_ticks_are_seen_by_one_tick() {
+    let mut input = Input::new(InputMap::default());
+    input.handle_event(&key(Key::Space, glfw::Action::Press));
+    input.handle_event(&key(Key::Space, glfw::Action::Release));
+    assert!(input.tick().pressed(Action::Jump));
+    assert!(input.tick().released(Action::Jump));
+
+    // Held keys stay held, mouse buttons too
+    input.handle_event(&key(Key::A, glfw::Action::Press));
+    input.handle_event(&WindowEvent::MouseButton(
+        MouseButton::Button1,
+        glfw::Action::Press,
+        Modifiers::empty(),
+    ));
+    assert!(input.tick().pressed(Action::MoveLeft));
+    let state = input.tick();
+    assert!(state.held(Action::MoveLeft) && state.held(Action::Attack));
+    assert!(!state.pressed(Action::MoveLeft));
+
+    // Flushed taps never reach a tick
+    input.handle_event(&key(Key::Escape, glfw::Action::Press));
+    input.handle_event(&key(Key::Escape, glfw::Action::Release));
+    input.flush();
+    assert!(!input.tick().pressed(Action::Pause));
+}
@@ -0,0 +1,68 @@
+use rusty_knight::model::geometry::Rect;
+use rusty_knight::renderer::renderer::{letterbox, window_to_logical};
+
+const LOGICAL: (u32, u32) = (320, 180);
+
+#[test]
+fn exact_multiples_fill_the_window() {
+    assert_eq!(
+        letterbox((320, 180), LOGICAL),
+        Rect::new(0.0, 0.0, 320.0, 180.0)
+    );
+    assert_eq!(
+        letterbox((1280, 720), LOGICAL),
+        Rect::new(0.0, 0.0, 1280.0, 720.0)
+    );
+}
+
+#[test]
+fn other_sizes_scale_by_whole_numbers_between_bars() {
+    // 3x fits, with bars left and right and a bit over top and bottom
+    assert_eq!(
+        letterbox((1100, 600), LOGICAL),
+        Rect::new(70.0, 30.0, 960.0, 540.0)
+    );
+    // A tall window only gets bars top and bottom
+    assert_eq!(
+        letterbox((640, 1000), LOGICAL),
+        Rect::new(0.0, 320.0, 640.0, 360.0)
+    );
+    // Odd leftovers are rounded down
+    assert_eq!(
+        letterbox((641, 361), LOGICAL),
+        Rect::new(0.0, 0.0, 640.0, 360.0)
+    );
+}
+
+#[test]
+fn small_windows_shrink_to_fit() {
+    assert_eq!(
+        letterbox((160, 90), LOGICAL),
+        Rect::new(0.0, 0.0, 160.0, 90.0)
+    );
+    assert_eq!(
+        letterbox((160, 200), LOGICAL),
+        Rect::new(0.0, 55.0, 160.0, 90.0)
+    );
+}
+
+#[test]
+fn window_points_map_to_logical_pixels_except_on_the_bars() {
+    let window = (1100, 600);
+    let to_logical = |x, y| window_to_logical(window, LOGICAL, x, y);
+    assert_eq!(to_logical(70.0, 30.0), Some(glm::vec2(0.0, 0.0)));
+    assert_eq!(to_logical(550.0, 300.0), Some(glm::vec2(160.0, 90.0)));
+    assert_eq!(to_logical(1027.0, 567.0), Some(glm::vec2(319.0, 179.0)));
+
+    // The bars and the far edges of the image
+    assert_eq!(to_logical(69.0, 300.0), None);
+    assert_eq!(to_logical(1030.0, 300.0), None);
+    assert_eq!(to_logical(550.0, 29.0), None);
+    assert_eq!(to_logical(550.0, 570.0), None);
+
+    // Shrunk windows are scaled up again
+    assert_eq!(
+        window_to_logical((160, 90), LOGICAL, 80.0, 45.0),
+        Some(glm::vec2(160.0, 90.0))
+    );
+}
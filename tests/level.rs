@@ -0,0 +1,165 @@
+use rusty_knight::constants::{gameplay, graphics};
+use rusty_knight::model::level::{CollisionGrid, Level, TileKind, classify};
+use rusty_knight::model::world::World;
+use rusty_knight::platform::input::ActionState;
+
+const WHITE: [u8; 3] = [255, 255, 255];
+const BLUE: [u8; 3] = [0, 0, 255];
+
+// Every colour the masks are painted with, in a row
+const PAINTED: [([u8; 3], TileKind); 11] = [
+    ([255, 255, 255], TileKind::Empty),
+    ([0, 0, 255], TileKind::Solid),
+    ([0, 0, 0], TileKind::Solid),
+    ([0, 255, 255], TileKind::OneWay),
+    ([255, 128, 0], TileKind::SlopeRight),
+    ([128, 0, 255], TileKind::SlopeLeft),
+    ([255, 0, 255], TileKind::Hazard),
+    ([255, 255, 0], TileKind::Spawn),
+    ([0, 255, 0], TileKind::EnemySpawn),
+    ([255, 0, 0], TileKind::BossSpawn),
+    ([128, 128, 128], TileKind::Exit),
+];
+
+fn mask(width: u32, height: u32, background: [u8; 3]) -> image::RgbaImage {
+    let [r, g, b] = background;
+    image::RgbaImage::from_pixel(width, height, image::Rgba([r, g, b, 255]))
+}
+
+fn paint(mask: &mut image::RgbaImage, x: u32, y: u32, [r, g, b]: [u8; 3]) {
+    mask.put_pixel(x, y, image::Rgba([r, g, b, 255]));
+}
+
+#[test]
+fn painted_colours_are_matched_to_the_nearest_tile() {
+    for (colour, kind) in PAINTED {
+        assert_eq!(classify(colour), kind, "{colour:?}");
+    }
+    // Hand painted masks are a little off
+    assert_eq!(classify([10, 5, 240]), TileKind::Solid);
+    assert_eq!(classify([240, 250, 20]), TileKind::Spawn);
+    assert_eq!(classify([135, 120, 130]), TileKind::Exit);
+}
+
+#[test]
+fn one_pixel_per_tile_masks_map_straight_to_tiles() {
+    let mut one_to_one = mask(PAINTED.len() as u32, 1, WHITE);
+    for (x, (colour, _)) in PAINTED.iter().enumerate() {
+        paint(&mut one_to_one, x as u32, 0, *colour);
+    }
+
+    let grid = CollisionGrid::from_mask(&one_to_one, PAINTED.len() as u32, 1);
+    for (x, (_, kind)) in PAINTED.iter().enumerate() {
+        assert_eq!(grid.get(x as i32, 0), *kind);
+    }
+}
+
+#[test]
+fn blocks_of_pixels_go_by_majority_and_markers_win() {
+    // Two tiles by two, four pixels a side each
+    let mut blocks = mask(8, 8, WHITE);
+    // Mostly solid with a stray empty corner
+    for y in 0..4 {
+        for x in 0..4 {
+            paint(&mut blocks, x, y, BLUE);
+        }
+    }
+    paint(&mut blocks, 0, 0, WHITE);
+    // Mostly empty with a few solid pixels
+    paint(&mut blocks, 4, 0, BLUE);
+    paint(&mut blocks, 5, 0, BLUE);
+    // One spawn pixel in an otherwise solid block
+    for y in 4..8 {
+        for x in 0..4 {
+            paint(&mut blocks, x, y, BLUE);
+        }
+    }
+    paint(&mut blocks, 2, 6, [255, 255, 0]);
+    // A single enemy marker in the empty one
+    paint(&mut blocks, 7, 7, [0, 255, 0]);
+
+    let grid = CollisionGrid::from_mask(&blocks, 2, 2);
+    assert_eq!(grid.get(0, 0), TileKind::Solid);
+    assert_eq!(grid.get(1, 0), TileKind::Empty);
+    assert_eq!(grid.get(0, 1), TileKind::Spawn);
+    assert_eq!(grid.get(1, 1), TileKind::EnemySpawn);
+}
+
+#[test]
+fn markers_become_spawn_points_in_the_middle_of_their_tiles() {
+    let tile = graphics::TILE_PIXELS;
+    let design = image::RgbaImage::new(6 * tile, 4 * tile);
+    let mut markers = mask(6, 4, WHITE);
+    paint(&mut markers, 1, 2, [255, 255, 0]);
+    paint(&mut markers, 3, 2, [0, 255, 0]);
+    paint(&mut markers, 4, 1, [0, 255, 0]);
+    paint(&mut markers, 5, 2, [255, 0, 0]);
+
+    let level = Level::from_images(7, design, &markers);
+    assert_eq!(level.collision.width, 6);
+    assert_eq!(level.collision.height, 4);
+    let centre = |x: u32, y: u32| {
+        glm::vec2(
+            (x as f32 + 0.5) * tile as f32,
+            (y as f32 + 0.5) * tile as f32,
+        )
+    };
+    assert_eq!(level.spawn_point(), centre(1, 2));
+    assert_eq!(level.enemy_spawns(), [centre(4, 1), centre(3, 2)]);
+    assert_eq!(level.boss_spawn(), Some(centre(5, 2)));
+
+    // Without markers the knight starts in the middle and there is no boss
+    let empty = Level::from_images(
+        7,
+        image::RgbaImage::new(6 * tile, 4 * tile),
+        &mask(6, 4, WHITE),
+    );
+    assert_eq!(empty.spawn_point(), glm::vec2(3.0, 2.0) * tile as f32);
+    assert!(empty.enemy_spawns().is_empty());
+    assert_eq!(empty.boss_spawn(), None);
+}
+
+#[test]
+fn hazards_hurt_the_knight_once_per_recovery() {
+    let tile = graphics::TILE_PIXELS as f32;
+    // A floor along the bottom with a hazard sitting on it to the right of the spawn
+    let mut markers = mask(12, 8, WHITE);
+    for x in 0..12 {
+        paint(&mut markers, x, 7, BLUE);
+    }
+    paint(&mut markers, 2, 6, [255, 255, 0]);
+    paint(&mut markers, 6, 6, [255, 0, 255]);
+    let level = Level::from_images(
+        7,
+        image::RgbaImage::new(12 * graphics::TILE_PIXELS, 8 * graphics::TILE_PIXELS),
+        &markers,
+    );
+    let mut world = World::new(level);
+    let actions = ActionState::new();
+    let dt = 1000.0 / 60.0;
+
+    // Standing on the floor away from it nothing happens
+    for _ in 0..30 {
+        world.update(dt, &actions);
+        assert!(world.damage_events.is_empty());
+    }
+    let full = world.player.fighter.health.max;
+    assert_eq!(world.player.fighter.health.current, full);
+
+    // Half in the hazard from its right, knocked up and further right
+    world.player.body.position.x = 7.0 * tile - 0.5 * world.player.body.size.x;
+    world.update(dt, &actions);
+    assert_eq!(world.damage_events.len(), 1);
+    assert_eq!(world.damage_events[0].target, 0);
+    assert_eq!(
+        world.player.fighter.health.current,
+        full - gameplay::HAZARD_DAMAGE
+    );
+    assert!(world.player.body.velocity.x > 0.0);
+    assert!(world.player.body.velocity.y < 0.0);
+
+    // Still touching it while recovering doesn't hurt again
+    world.player.body.position.x = 7.0 * tile - 0.5 * world.player.body.size.x;
+    world.update(dt, &actions);
+    assert!(world.damage_events.is_empty());
+}
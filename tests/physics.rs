@@ -0,0 +1,169 @@
+use rusty_knight::model::level::{CollisionGrid, TileKind};
+use rusty_knight::model::physics::{self, Body};
+
+const DT: f32 = 1.0 / 60.0;
+
+// 20x12 tiles with a floor along row 10 and walls down both sides
+fn walled_room() -> CollisionGrid {
+    let mut grid = CollisionGrid::new(20, 12);
+    for x in 0..20 {
+        grid.set(x, 10, TileKind::Solid);
+        grid.set(x, 11, TileKind::Solid);
+    }
+    for y in 0..10 {
+        grid.set(0, y, TileKind::Solid);
+        grid.set(19, y, TileKind::Solid);
+    }
+    grid
+}
+
+fn run(body: &mut Body, grid: &CollisionGrid, steps: u32) {
+    for _ in 0..steps {
+        physics::step(body, grid, DT);
+    }
+}
+
+#[test]
+fn falls_and_lands_on_the_floor() {
+    let grid = walled_room();
+    let mut body = Body::new(glm::vec2(64.0, 16.0), glm::vec2(16.0, 32.0));
+
+    run(&mut body, &grid, 120);
+
+    assert!(body.contacts.ground);
+    assert_eq!(body.bottom(), 160.0);
+    assert_eq!(body.velocity.y, 0.0);
+}
+
+#[test]
+fn fast_bodies_do_not_tunnel() {
+    let mut grid = CollisionGrid::new(20, 40);
+    for x in 0..20 {
+        grid.set(x, 30, TileKind::Solid);
+    }
+    let mut body = Body::new(glm::vec2(64.0, 0.0), glm::vec2(8.0, 8.0));
+    body.gravity_scale = 0.0;
+    body.velocity.y = 900.0;
+
+    // A single second long step crosses the floor in one go
+    physics::step(&mut body, &grid, 1.0);
+
+    assert!(body.contacts.ground);
+    assert_eq!(body.bottom(), 480.0);
+}
+
+#[test]
+fn walls_stop_horizontal_movement() {
+    let grid = walled_room();
+    let mut body = Body::new(glm::vec2(64.0, 128.0), glm::vec2(16.0, 32.0));
+
+    for _ in 0..240 {
+        body.velocity.x = 400.0;
+        physics::step(&mut body, &grid, DT);
+    }
+    assert!(body.contacts.wall_right);
+    assert_eq!(body.right(), 19.0 * 16.0);
+
+    for _ in 0..240 {
+        body.velocity.x = -400.0;
+        physics::step(&mut body, &grid, DT);
+    }
+    assert!(body.contacts.wall_left);
+    assert_eq!(body.position.x, 16.0);
+}
+
+#[test]
+fn ceilings_stop_jumps() {
+    let mut grid = walled_room();
+    for x in 0..20 {
+        grid.set(x, 4, TileKind::Solid);
+    }
+    let mut body = Body::new(glm::vec2(64.0, 128.0), glm::vec2(16.0, 32.0));
+    body.velocity.y = -4000.0;
+
+    physics::step(&mut body, &grid, DT);
+
+    assert!(body.contacts.ceiling);
+    assert_eq!(body.position.y, 80.0);
+    assert_eq!(body.velocity.y, 0.0);
+}
+
+#[test]
+fn one_way_platforms_only_block_from_above() {
+    let mut grid = walled_room();
+    for x in 3..8 {
+        grid.set(x, 7, TileKind::OneWay);
+    }
+    let mut body = Body::new(glm::vec2(64.0, 128.0), glm::vec2(16.0, 32.0));
+
+    // Jumps up through the platform
+    body.velocity.y = -500.0;
+    for _ in 0..20 {
+        physics::step(&mut body, &grid, DT);
+        assert!(!body.contacts.ceiling);
+    }
+    assert!(body.bottom() < 112.0);
+
+    // And lands on it on the way back down
+    run(&mut body, &grid, 60);
+    assert!(body.contacts.ground);
+    assert_eq!(body.bottom(), 112.0);
+
+    // Dropping through takes it back to the floor
+    body.drop_through = true;
+    run(&mut body, &grid, 60);
+    assert_eq!(body.bottom(), 160.0);
+}
+
+#[test]
+fn walks_up_and_down_slopes() {
+    // Floor on row 10, a ramp up to a ledge on row 7
+    let mut grid = walled_room();
+    for (i, x) in (5..8).enumerate() {
+        grid.set(x, 9 - i as i32, TileKind::SlopeRight);
+        for y in 10 - i as i32..10 {
+            grid.set(x, y, TileKind::Solid);
+        }
+    }
+    for x in 8..19 {
+        for y in 7..10 {
+            grid.set(x, y, TileKind::Solid);
+        }
+    }
+
+    let mut body = Body::new(glm::vec2(32.0, 128.0), glm::vec2(16.0, 32.0));
+    run(&mut body, &grid, 10);
+    for _ in 0..90 {
+        body.velocity.x = 120.0;
+        physics::step(&mut body, &grid, DT);
+    }
+    assert!(body.contacts.ground);
+    assert!(!body.contacts.wall_right);
+    assert_eq!(body.bottom(), 112.0);
+
+    // Walking back down stays on the ground the whole way
+    for _ in 0..90 {
+        body.velocity.x = -120.0;
+        physics::step(&mut body, &grid, DT);
+        assert!(body.contacts.ground);
+    }
+    assert_eq!(body.bottom(), 160.0);
+}
+
+#[test]
+fn same_inputs_give_the_same_result() {
+    let grid = walled_room();
+    let simulate = || {
+        let mut body = Body::new(glm::vec2(40.0, 20.0), glm::vec2(16.0, 32.0));
+        for i in 0..300 {
+            body.velocity.x = if i % 90 < 45 { 150.0 } else { -110.0 };
+            if i % 70 == 0 && body.contacts.ground {
+                body.velocity.y = -500.0;
+            }
+            physics::step(&mut body, &grid, DT);
+        }
+        (body.position.x.to_bits(), body.position.y.to_bits())
+    };
+
+    assert_eq!(simulate(), simulate());
+}
@@ -0,0 +1,266 @@
+use rusty_knight::model::combat::{Combatant, DamageEvent};
+use rusty_knight::model::level::{CollisionGrid, TileKind};
+use rusty_knight::model::player::Player;
+use rusty_knight::model::state_machine::{Condition, Effect, StateMachine};
+use rusty_knight::platform::input::{ACTION_COUNT, Action, ActionState};
+
+const DT: f32 = 1.0 / 60.0;
+
+// 30x20 tiles with a floor along row 15 and walls down both sides
+fn room() -> CollisionGrid {
+    let mut grid = CollisionGrid::new(30, 20);
+    for x in 0..30 {
+        for y in 15..20 {
+            grid.set(x, y, TileKind::Solid);
+        }
+    }
+    for y in 0..15 {
+        grid.set(0, y, TileKind::Solid);
+        grid.set(29, y, TileKind::Solid);
+    }
+    grid
+}
+
+struct Run {
+    player: Player,
+    grid: CollisionGrid,
+    actions: ActionState,
+}
+
+impl Run {
+    fn new(grid: CollisionGrid, feet: glm::Vec2) -> Self {
+        let mut run = Run {
+            player: Player::load(feet),
+            grid,
+            actions: ActionState::new(),
+        };
+        run.ticks(30, &[]);
+        run
+    }
+
+    fn ticks(&mut self, count: u32, held: &[Action]) {
+        let mut values = [0.0; ACTION_COUNT];
+        for action in held {
+            values[*action as usize] = 1.0;
+        }
+        for _ in 0..count {
+            self.actions.advance(values);
+            self.player.update(DT, &self.actions, &self.grid);
+        }
+    }
+
+    fn state(&self) -> &str {
+        self.player.state_name()
+    }
+}
+
+#[test]
+fn transitions_are_read_from_text() {
+    let machine = StateMachine::parse(
+        "
+any
+to dead when dead
+reenter walk when after:9
+
+state idle
+clip standing
+run 12
+to walk when moving !held:Roll do cut_jump
+to nowhere when moving
+wobble 3
+
+state walk
+to idle when after:0.5
+
+state dead
+",
+    );
+
+    let idle = &machine.states[0];
+    assert_eq!(idle.clip, "standing");
+    assert_eq!(idle.run, 12.0);
+    assert_eq!(idle.transitions.len(), 1);
+    let transition = &idle.transitions[0];
+    assert_eq!(transition.target, machine.state("walk").unwrap());
+    assert_eq!(
+        transition.conditions,
+        [
+            (Condition::Moving, true),
+            (Condition::Held(Action::Roll), false)
+        ]
+    );
+    assert_eq!(transition.effects, [Effect::CutJump]);
+
+    assert_eq!(machine.states[1].clip, "walk");
+    assert_eq!(
+        machine.states[1].transitions[0].conditions[0].0,
+        Condition::After(0.5)
+    );
+    assert_eq!(machine.any[0].target, machine.state("dead").unwrap());
+    assert!(!machine.any[0].reenter);
+    assert!(machine.any[1].reenter);
+}
+
+#[test]
+fn runs_and_stops() {
+    let mut run = Run::new(room(), glm::vec2(100.0, 240.0));
+    assert_eq!(run.state(), "idle");
+    assert!(run.player.body.contacts.ground);
+
+    run.ticks(30, &[Action::MoveLeft]);
+    assert_eq!(run.state(), "run");
+    assert!(run.player.facing_left);
+    assert!(run.player.body.centre().x < 100.0);
+
+    run.ticks(1, &[]);
+    assert_eq!(run.state(), "idle");
+}
+
+#[test]
+fn letting_go_of_jump_early_gives_a_lower_jump() {
+    let peak = |hold: u32| {
+        let mut run = Run::new(room(), glm::vec2(100.0, 240.0));
+        let mut highest = run.player.body.bottom();
+        for i in 0..60 {
+            run.ticks(1, if i < hold { &[Action::Jump] } else { &[] });
+            highest = highest.min(run.player.body.bottom());
+        }
+        assert_eq!(run.state(), "idle");
+        highest
+    };
+
+    assert!(peak(2) > peak(30) + 16.0);
+}
+
+#[test]
+fn attacks_chain_when_pressed_again() {
+    let mut run = Run::new(room(), glm::vec2(100.0, 240.0));
+    run.ticks(1, &[Action::Attack]);
+    assert_eq!(run.state(), "attack_1");
+
+    run.ticks(1, &[]);
+    run.ticks(1, &[Action::Attack]);
+    run.ticks(60, &[]);
+    assert_eq!(run.state(), "attack_2");
+
+    run.ticks(60, &[]);
+    assert_eq!(run.state(), "idle");
+}
+
+#[test]
+fn stays_crouched_under_a_low_ceiling() {
+    let mut grid = room();
+    // Roof three tiles up from x = 160
+    for x in 10..20 {
+        grid.set(x, 12, TileKind::Solid);
+    }
+    let mut run = Run::new(grid, glm::vec2(120.0, 240.0));
+
+    run.ticks(1, &[Action::MoveDown]);
+    assert_eq!(run.state(), "crouch");
+    assert_eq!(run.player.body.bottom(), 240.0);
+    run.ticks(1, &[]);
+    assert_eq!(run.state(), "idle");
+
+    // Rolls in under the roof and cannot stand up there
+    run.ticks(1, &[Action::Roll]);
+    run.ticks(60, &[]);
+    assert_eq!(run.state(), "crouch");
+    assert_eq!(run.player.body.bottom(), 240.0);
+    assert!(run.player.body.position.y >= 208.0);
+    assert!(run.player.body.position.x > 160.0);
+}
+
+#[test]
+fn grabs_and_climbs_ledges() {
+    let mut grid = room();
+    // A block six tiles high against the right of the room
+    for x in 20..29 {
+        for y in 9..15 {
+            grid.set(x, y, TileKind::Solid);
+        }
+    }
+    let mut run = Run::new(grid, glm::vec2(280.0, 240.0));
+
+    run.ticks(20, &[Action::MoveRight, Action::Jump]);
+    run.ticks(30, &[Action::MoveRight]);
+    assert_eq!(run.state(), "hang");
+    assert_eq!(run.player.body.position.y, 9.0 * 16.0 + 12.0);
+    assert_eq!(run.player.body.right(), 320.0);
+
+    run.ticks(1, &[Action::MoveUp]);
+    run.ticks(60, &[]);
+    assert_eq!(run.state(), "idle");
+    assert_eq!(run.player.body.bottom(), 144.0);
+    assert!(run.player.body.position.x >= 320.0);
+}
+
+#[test]
+fn hurt_and_death_interrupt_anything() {
+    let mut run = Run::new(room(), glm::vec2(100.0, 240.0));
+    run.ticks(1, &[Action::Attack]);
+
+    // Hit from behind, turns round and gets knocked forwards
+    let mut hit = DamageEvent {
+        attacker: 1,
+        target: 0,
+        damage: 10,
+        knockback: glm::vec2(150.0, -100.0),
+        position: run.player.body.centre(),
+    };
+    run.player.fighter.health.damage(hit.damage);
+    run.player.take_hit(&hit);
+    run.ticks(1, &[]);
+    assert_eq!(run.state(), "hurt");
+    assert!(run.player.facing_left);
+    assert!(run.player.body.velocity.x > 0.0);
+    // Drawn turned round and faded while it recovers
+    let pose = run.player.pose(1.0);
+    assert!(pose.flip_x);
+    assert_eq!(pose.opacity, 0.5);
+
+    // Slides to a stop
+    run.ticks(30, &[]);
+    assert_eq!(run.player.body.velocity.x, 0.0);
+
+    hit.damage = 1000;
+    run.player.fighter.health.damage(hit.damage);
+    run.player.take_hit(&hit);
+    run.ticks(120, &[Action::MoveRight, Action::Jump]);
+    assert_eq!(run.state(), "death");
+}
+
+#[test]
+fn a_second_hit_starts_hurt_over() {
+    let mut run = Run::new(room(), glm::vec2(300.0, 240.0));
+    let hit = |run: &mut Run, knockback: glm::Vec2| {
+        let hit = DamageEvent {
+            attacker: 1,
+            target: 0,
+            damage: 1,
+            knockback,
+            position: run.player.body.centre(),
+        };
+        run.player.fighter.health.damage(hit.damage);
+        run.player.take_hit(&hit);
+    };
+
+    hit(&mut run, glm::vec2(150.0, -100.0));
+    run.ticks(12, &[]);
+    assert_eq!(run.state(), "hurt");
+    assert!(run.player.animator.frame_index() > 0);
+
+    // Hit again from the other side before the clip is over
+    hit(&mut run, glm::vec2(-150.0, -100.0));
+    run.ticks(1, &[]);
+    assert_eq!(run.state(), "hurt");
+    assert_eq!(run.player.animator.frame_index(), 0);
+    assert!(!run.player.facing_left);
+    assert!(run.player.body.velocity.x < 0.0);
+
+    // The whole clip plays again from the second hit
+    run.ticks(12, &[]);
+    assert_eq!(run.state(), "hurt");
+    run.ticks(60, &[]);
+    assert_eq!(run.state(), "idle");
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use std::path::Path;
+
+use common::golden;
+use rusty_knight::model::game_object::{Camera, Object, OrthoCamera};
+use rusty_knight::model::geometry::Rect;
+use rusty_knight::renderer::sprite_batch::Sprite;
+
+// Looks straight down the z axis at the origin, where the scenes are placed
+fn top_down_camera() -> Camera {
+    let mut camera = Camera::new();
+    camera.position = glm::Vec3::new(0.0, 0.0, 0.0);
+    camera.camera_spin(0.0, -89.0);
+    camera
+}
+
+#[test]
+fn quad_scene() {
+    let Some(mut state) = golden::new_state() else {
+        return;
+    };
+
+    let quads = vec![Object {
+        position: glm::Vec3::new(0.0, 0.0, -1.5),
+        angle: 0.0,
+    }];
+
+    state.request_capture();
+    state
+        .render(&quads, &[], &top_down_camera(), &OrthoCamera::new(), 1.0)
+        .unwrap();
+    golden::check("quad", &state.take_capture().unwrap());
+}
+
+#[test]
+fn triangle_scene() {
+    let Some(mut state) = golden::new_state() else {
+        return;
+    };
+
+    let tris = vec![Object {
+        position: glm::Vec3::new(0.0, 0.0, -1.5),
+        angle: 30.0,
+    }];
+
+    state.request_capture();
+    state
+        .render(&[], &tris, &top_down_camera(), &OrthoCamera::new(), 1.0)
+        .unwrap();
+    golden::check("triangle", &state.take_capture().unwrap());
+}
+
+#[test]
+fn instances_past_the_initial_capacity() {
+    let Some(mut state) = golden::new_state() else {
+        return;
+    };
+
+    // Rows of 13 just inside the far plane, the second grid half a place over
+    let grid = |count: i32, offset: f32, depth: f32| -> Vec<Object> {
+        (0..count)
+            .map(|i| Object {
+                position: glm::Vec3::new(
+                    1.1 * ((i % 13) as f32 - 6.0 + offset),
+                    1.1 * ((i / 13) as f32 - 4.5 + offset),
+                    depth,
+                ),
+                angle: 0.0,
+            })
+            .collect()
+    };
+    let camera = top_down_camera();
+
+    // A few first, then far more than the 64 the instance buffer starts with,
+    // so it has to grow after it has already been used
+    state
+        .render(&grid(4, 0.0, -9.5), &[], &camera, &OrthoCamera::new(), 1.0)
+        .unwrap();
+
+    let quads = grid(130, 0.0, -9.5);
+    let tris = grid(100, 0.5, -9.0);
+    state.request_capture();
+    state
+        .render(&quads, &tris, &camera, &OrthoCamera::new(), 1.0)
+        .unwrap();
+    golden::check("instances", &state.take_capture().unwrap());
+}
+
+#[test]
+fn sprite_scene() {
+    let Some(mut state) = golden::new_state() else {
+        return;
+    };
+
+    let run = state.load_sprite_texture("assets/sprites/rusty_knight/Run.png");
+    let idle = state.load_sprite_texture("assets/sprites/rusty_knight/Idle.png");
+    let frame = Rect::new(0.0, 0.0, 128.0, 64.0);
+
+    let mut flipped = Sprite::new(run, frame, glm::Vec2::new(400.0, 100.0));
+    flipped.flip_x = true;
+    flipped.scale = glm::Vec2::new(3.0, 3.0);
+
+    let mut tinted = Sprite::new(idle, frame, glm::Vec2::new(200.0, 300.0));
+    tinted.scale = glm::Vec2::new(4.0, 4.0);
+    tinted.tint = glm::Vec4::new(1.0, 0.3, 0.3, 1.0);
+    tinted.layer = 1;
+
+    // Submitted last but on a lower layer, so it ends up behind the tinted one
+    let mut background = Sprite::new(run, frame, glm::Vec2::new(150.0, 250.0));
+    background.scale = glm::Vec2::new(5.0, 5.0);
+    background.layer = -1;
+
+    state.draw_sprite(flipped);
+    state.draw_sprite(tinted);
+    state.draw_sprite(background);
+
+    state.request_capture();
+    state
+        .render(&[], &[], &Camera::new(), &OrthoCamera::new(), 1.0)
+        .unwrap();
+    golden::check("sprites", &state.take_capture().unwrap());
+}
+
+#[test]
+fn model_scene() {
+    let Some(mut state) = golden::new_state() else {
+        return;
+    };
+
+    // A small fixture rather than the game's own models, which aren't checked in
+    let c0 = glm::Vec4::new(1.0, 0.0, 0.0, 0.0);
+    let c1 = glm::Vec4::new(0.0, 1.0, 0.0, 0.0);
+    let c2 = glm::Vec4::new(0.0, 0.0, 1.0, 0.0);
+    let c3 = glm::Vec4::new(0.0, 0.0, 0.0, 1.0);
+    let identity = glm::Matrix4::new(c0, c1, c2, c3);
+    state.load_model(Path::new("tests/golden/pyramid.obj"), &identity);
+
+    state.request_capture();
+    state
+        .render(&[], &[], &top_down_camera(), &OrthoCamera::new(), 1.0)
+        .unwrap();
+    golden::check("pyramid_model", &state.take_capture().unwrap());
+}
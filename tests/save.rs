@@ -0,0 +1,164 @@
+use std::fs;
+use std::path::PathBuf;
+
+use rusty_knight::core::save::{SaveGame, SaveSlots, VERSION};
+use rusty_knight::core::settings::Settings;
+use rusty_knight::model::level::Level;
+use rusty_knight::model::world::World;
+use rusty_knight::platform::input::ActionState;
+
+const DT: f32 = 1000.0 / 60.0;
+
+// An empty directory of its own for each test
+fn slots(name: &str) -> SaveSlots {
+    let dir: PathBuf = std::env::temp_dir()
+        .join(format!("rusty_knight_saves_{}", std::process::id()))
+        .join(name);
+    let _ = fs::remove_dir_all(&dir);
+    SaveSlots::new(dir)
+}
+
+fn game() -> SaveGame {
+    SaveGame {
+        level: 3,
+        checkpoint: Some(glm::vec2(912.5, 640.0)),
+        health: 45,
+        abilities: vec!["double_jump".to_string(), "wall_slide".to_string()],
+        settings: Settings {
+            music: false,
+            sound: true,
+            volume: 0.7,
+        },
+    }
+}
+
+#[test]
+fn saves_are_read_back_from_their_text() {
+    let game = game();
+    let text = game.to_text();
+    assert!(text.contains(&format!("version {VERSION}\n")));
+    assert_eq!(SaveGame::from_text(&text).unwrap(), game);
+
+    // Fields left out or unreadable keep their new game values
+    let game = SaveGame::from_text(
+        "
+# Rusty Knight save
+version 2
+level 2
+health lots
+ability double_jump
+ability double_jump
+volume 4
+teleport 3
+",
+    )
+    .unwrap();
+    assert_eq!(game.level, 2);
+    assert_eq!(game.checkpoint, None);
+    assert_eq!(game.health, SaveGame::default().health);
+    assert_eq!(game.abilities, ["double_jump"]);
+    assert_eq!(game.settings.volume, 1.0);
+    assert!(game.settings.music);
+}
+
+#[test]
+fn numbers_that_are_not_finite_are_skipped() {
+    let game = SaveGame::from_text(
+        "
+version 2
+volume NaN
+checkpoint inf 640
+",
+    )
+    .unwrap();
+    assert_eq!(game.settings.volume, SaveGame::default().settings.volume);
+    assert_eq!(game.checkpoint, None);
+}
+
+#[test]
+fn older_versions_are_brought_up_to_date_and_newer_ones_refused() {
+    // Version 1 had the checkpoint's tile, which is 16 pixels square
+    let game = SaveGame::from_text("version 1\nlevel 3\ncheckpoint 57 39\nhealth 60\n").unwrap();
+    assert_eq!(game.checkpoint, Some(glm::vec2(920.0, 640.0)));
+    assert_eq!(game.health, 60);
+    assert!(game.to_text().contains(&format!("version {VERSION}\n")));
+
+    assert!(SaveGame::from_text("level 3\nversion 2\n").is_err());
+    assert!(SaveGame::from_text("version two\nlevel 3\n").is_err());
+    assert!(SaveGame::from_text(&format!("version {}\nlevel 3\n", VERSION + 1)).is_err());
+    assert!(SaveGame::from_text("").is_err());
+}
+
+#[test]
+fn slots_are_kept_apart_and_replaced_whole() {
+    let mut slots = slots("slots");
+    assert_eq!(slots.load(0), Ok(None));
+
+    let game = game();
+    slots.write(1, &game).unwrap();
+    assert_eq!(slots.load(1), Ok(Some(game.clone())));
+    assert_eq!(slots.load(0), Ok(None));
+
+    // A save cut off before it was renamed into place is never read
+    let partial = slots.path(1).with_extension("sav.tmp");
+    fs::write(&partial, "version 2\nlev").unwrap();
+    assert_eq!(slots.load(1), Ok(Some(game.clone())));
+    let mut next = game.clone();
+    next.level = 2;
+    next.abilities.clear();
+    slots.write(1, &next).unwrap();
+    assert_eq!(slots.load(1), Ok(Some(next)));
+    assert!(!partial.exists());
+
+    fs::write(slots.path(2), "nonsense").unwrap();
+    assert!(slots.load(2).is_err());
+    // Past the last level counts as damaged rather than loading a level
+    // that isn't there
+    fs::write(slots.path(2), "version 2\nlevel 99\n").unwrap();
+    assert!(slots.load(2).is_err());
+
+    // Settings only go into a slot that already has a save
+    slots.current = 0;
+    let quiet = Settings {
+        volume: 0.2,
+        ..Default::default()
+    };
+    slots.save_settings(&quiet);
+    assert_eq!(slots.load(0), Ok(None));
+    slots.current = 1;
+    slots.save_settings(&quiet);
+    assert_eq!(slots.load(1).unwrap().unwrap().settings, quiet);
+    assert_eq!(slots.load(1).unwrap().unwrap().level, 2);
+    fs::remove_dir_all(&slots.dir).unwrap();
+}
+
+#[test]
+fn the_boss_arena_is_a_checkpoint_to_come_back_to() {
+    let mut world = World::new(Level::load(3));
+    let actions = ActionState::new();
+    assert_eq!(world.checkpoint, None);
+    world.update(DT, &actions);
+    assert!(!world.checkpoint_reached);
+
+    world.player.body.position.x = 900.0;
+    world.update(DT, &actions);
+    assert!(world.checkpoint_reached);
+    let checkpoint = world.checkpoint.unwrap();
+    let body = &world.player.body;
+    assert_eq!(checkpoint, glm::vec2(body.centre().x, body.bottom()));
+    world.update(DT, &actions);
+    assert!(!world.checkpoint_reached);
+    assert_eq!(world.checkpoint, Some(checkpoint));
+
+    // Saved and loaded, the knight starts there and doesn't save it again
+    world.player.fighter.health.damage(30);
+    let game = SaveGame::capture(&world, &Settings::default());
+    assert_eq!(game.level, 3);
+    assert_eq!(game.health, world.player.fighter.health.current);
+    let mut resumed = World::resume(Level::load(game.level), game.checkpoint);
+    let body = &resumed.player.body;
+    assert_eq!(glm::vec2(body.centre().x, body.bottom()), checkpoint);
+    resumed.update(DT, &actions);
+    assert!(resumed.boss.as_ref().unwrap().arena.is_locked());
+    assert!(!resumed.checkpoint_reached);
+}
@@ -0,0 +1,367 @@
+// Only the headless state is needed here
+#[allow(dead_code)]
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use common::golden;
+use rusty_knight::audio::audio::{Audio, NULL_SAMPLE_RATE};
+use rusty_knight::core::controls_menu::ControlsMenu;
+use rusty_knight::core::main_menu::MainMenu;
+use rusty_knight::core::playing::Playing;
+use rusty_knight::core::save::{SaveGame, SaveSlots};
+use rusty_knight::core::scene::{Context, Scene, SceneStack, Transition};
+use rusty_knight::core::settings::Settings;
+use rusty_knight::platform::input::{ACTION_COUNT, Action, ActionState};
+use rusty_knight::renderer::renderer::State;
+
+const DT: f32 = 1000.0 / 60.0;
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+// Records what is done to it and hands back whatever transition it is given next
+struct Dummy {
+    name: &'static str,
+    overlay: bool,
+    log: Log,
+    next: Rc<RefCell<Option<Transition>>>,
+}
+
+impl Scene for Dummy {
+    fn update(&mut self, _dt: f32, _actions: &ActionState, _context: &mut Context) -> Transition {
+        self.log.borrow_mut().push(format!("update {}", self.name));
+        self.next.borrow_mut().take().unwrap_or(Transition::Stay)
+    }
+
+    fn draw(&self, _state: &mut State, _alpha: f32) {
+        self.log.borrow_mut().push(format!("draw {}", self.name));
+    }
+
+    fn is_overlay(&self) -> bool {
+        self.overlay
+    }
+}
+
+// An empty directory of its own for each test
+fn slots(name: &str) -> SaveSlots {
+    let dir = std::env::temp_dir()
+        .join(format!("rusty_knight_scenes_{}", std::process::id()))
+        .join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    SaveSlots::new(dir)
+}
+
+fn holding(actions: &mut ActionState, held: &[Action]) {
+    let mut values = [0.0; ACTION_COUNT];
+    for action in held {
+        values[*action as usize] = 1.0;
+    }
+    actions.advance(values);
+}
+
+// Presses and lets go of the actions over two ticks of the stack
+fn tap(
+    scenes: &mut SceneStack,
+    actions: &mut ActionState,
+    context: &mut Context,
+    tapped: &[Action],
+) {
+    holding(actions, tapped);
+    scenes.update(DT, actions, context);
+    holding(actions, &[]);
+    scenes.update(DT, actions, context);
+}
+
+fn wait(scenes: &mut SceneStack, actions: &mut ActionState, context: &mut Context, ticks: u32) {
+    for _ in 0..ticks {
+        holding(actions, &[]);
+        scenes.update(DT, actions, context);
+    }
+}
+
+#[test]
+fn only_the_top_scene_updates_and_overlays_show_what_is_beneath() {
+    let Some(mut state) = golden::new_state() else {
+        return;
+    };
+    let mut controls_menu = ControlsMenu::new(&mut state);
+    let mut settings = Settings::default();
+    let mut audio = Audio::null(NULL_SAMPLE_RATE);
+    let mut saves = slots("stack");
+    let log: Log = Rc::default();
+    let next = Rc::new(RefCell::new(None));
+    let dummy = |name, overlay| {
+        Box::new(Dummy {
+            name,
+            overlay,
+            log: log.clone(),
+            next: next.clone(),
+        })
+    };
+    let actions = ActionState::new();
+
+    let mut scenes = SceneStack::new(dummy("game", false));
+    scenes.apply(Transition::Push(dummy("pause", true)));
+    {
+        let mut context = Context {
+            state: &mut state,
+            controls_menu: &mut controls_menu,
+            settings: &mut settings,
+            audio: &mut audio,
+            saves: &mut saves,
+        };
+        scenes.update(DT, &actions, &mut context);
+    }
+    scenes.draw(&mut state, 0.0);
+    assert_eq!(*log.borrow(), ["update pause", "draw game", "draw pause"]);
+
+    // A full screen scene hides everything underneath
+    log.borrow_mut().clear();
+    scenes.apply(Transition::Push(dummy("options", false)));
+    scenes.draw(&mut state, 0.0);
+    assert_eq!(*log.borrow(), ["draw options"]);
+
+    // The scene's own transition is applied after its tick
+    log.borrow_mut().clear();
+    *next.borrow_mut() = Some(Transition::Pop);
+    let mut context = Context {
+        state: &mut state,
+        controls_menu: &mut controls_menu,
+        settings: &mut settings,
+        audio: &mut audio,
+        saves: &mut saves,
+    };
+    scenes.update(DT, &actions, &mut context);
+    assert_eq!(scenes.len(), 2);
+    assert!(scenes.top().unwrap().is_overlay());
+
+    *next.borrow_mut() = Some(Transition::Replace(dummy("defeat", true)));
+    scenes.update(DT, &actions, &mut context);
+    assert_eq!(scenes.len(), 2);
+    *next.borrow_mut() = Some(Transition::Restart(dummy("menu", false)));
+    scenes.update(DT, &actions, &mut context);
+    assert_eq!(scenes.len(), 1);
+    assert!(scenes.world().is_none());
+    *next.borrow_mut() = Some(Transition::Quit);
+    scenes.update(DT, &actions, &mut context);
+    assert!(scenes.is_empty());
+    assert_eq!(
+        *log.borrow(),
+        [
+            "update options",
+            "update pause",
+            "update defeat",
+            "update menu"
+        ]
+    );
+}
+
+#[test]
+fn pausing_freezes_the_level_until_resumed() {
+    let Some(mut state) = golden::new_state() else {
+        return;
+    };
+    let mut controls_menu = ControlsMenu::new(&mut state);
+    let mut settings = Settings::default();
+    let mut audio = Audio::null(NULL_SAMPLE_RATE);
+    let mut saves = slots("pause");
+    let mut context = Context {
+        state: &mut state,
+        controls_menu: &mut controls_menu,
+        settings: &mut settings,
+        audio: &mut audio,
+        saves: &mut saves,
+    };
+    let playing = Playing::new(context.state, 1);
+    let mut scenes = SceneStack::new(Box::new(playing));
+    let mut actions = ActionState::new();
+    wait(&mut scenes, &mut actions, &mut context, 30);
+
+    tap(&mut scenes, &mut actions, &mut context, &[Action::Pause]);
+    assert_eq!(scenes.len(), 2);
+    assert!(scenes.top().unwrap().is_overlay());
+    // The level stays on screen but stops moving
+    let frozen = scenes.world().unwrap().player.body.position;
+    for _ in 0..30 {
+        holding(&mut actions, &[Action::MoveRight]);
+        scenes.update(DT, &actions, &mut context);
+    }
+    assert_eq!(scenes.world().unwrap().player.body.position, frozen);
+
+    // Right took the focus from resume over to the home button, the volume
+    // is just above resume
+    tap(&mut scenes, &mut actions, &mut context, &[Action::MoveLeft]);
+    tap(&mut scenes, &mut actions, &mut context, &[Action::MoveUp]);
+    tap(&mut scenes, &mut actions, &mut context, &[Action::MoveLeft]);
+    assert!((context.settings.volume - 0.9).abs() < 1e-4);
+    tap(&mut scenes, &mut actions, &mut context, &[Action::MoveDown]);
+    tap(&mut scenes, &mut actions, &mut context, &[Action::Jump]);
+    assert_eq!(scenes.len(), 1);
+
+    // Quitting leaves the level for the main menu
+    tap(&mut scenes, &mut actions, &mut context, &[Action::Pause]);
+    tap(&mut scenes, &mut actions, &mut context, &[Action::MoveDown]);
+    tap(&mut scenes, &mut actions, &mut context, &[Action::Jump]);
+    assert_eq!(scenes.len(), 1);
+    assert!(scenes.world().is_none());
+}
+
+#[test]
+fn dying_offers_a_retry_and_the_exit_leads_to_the_next_level() {
+    let Some(mut state) = golden::new_state() else {
+        return;
+    };
+    let mut controls_menu = ControlsMenu::new(&mut state);
+    let mut settings = Settings::default();
+    let mut audio = Audio::null(NULL_SAMPLE_RATE);
+    let mut saves = slots("outcome");
+    let mut context = Context {
+        state: &mut state,
+        controls_menu: &mut controls_menu,
+        settings: &mut settings,
+        audio: &mut audio,
+        saves: &mut saves,
+    };
+    let mut actions = ActionState::new();
+
+    let mut playing = Playing::new(context.state, 1);
+    playing.world.player.fighter.health.damage(1000);
+    let mut scenes = SceneStack::new(Box::new(playing));
+    // Falls over before the defeat banner shows. It waits for the whole death
+    // clip, three frames of 0.15s and one held for 1s, so 87 ticks after the
+    // tick the knight starts falling
+    wait(&mut scenes, &mut actions, &mut context, 1);
+    assert_eq!(scenes.len(), 1);
+    wait(&mut scenes, &mut actions, &mut context, 86);
+    assert_eq!(scenes.len(), 1);
+    wait(&mut scenes, &mut actions, &mut context, 1);
+    assert_eq!(scenes.len(), 2);
+
+    // Retrying starts the same level over at full health, once the buttons show
+    tap(&mut scenes, &mut actions, &mut context, &[Action::Attack]);
+    assert_eq!(scenes.len(), 2);
+    wait(&mut scenes, &mut actions, &mut context, 60);
+    tap(&mut scenes, &mut actions, &mut context, &[Action::Attack]);
+    assert_eq!(scenes.len(), 1);
+    let world = scenes.world().unwrap();
+    assert_eq!(world.level.number, 1);
+    assert!(!world.player.fighter.health.is_dead());
+
+    let mut playing = Playing::new(context.state, 1);
+    let exit = playing.world.level.collision.tile_position(78, 40);
+    playing.world.player.body.position = exit - glm::vec2(0.0, 28.0);
+    let mut scenes = SceneStack::new(Box::new(playing));
+    wait(&mut scenes, &mut actions, &mut context, 61);
+    assert_eq!(scenes.len(), 2);
+    tap(&mut scenes, &mut actions, &mut context, &[Action::Jump]);
+    assert_eq!(scenes.len(), 1);
+    assert_eq!(scenes.world().unwrap().level.number, 2);
+    // Going on saves the start of the next level
+    let saved = context.saves.load(0).unwrap().unwrap();
+    assert_eq!((saved.level, saved.checkpoint), (2, None));
+}
+
+#[test]
+fn the_main_menu_plays_the_picked_slot_and_retries_from_its_checkpoint() {
+    let Some(mut state) = golden::new_state() else {
+        return;
+    };
+    let mut controls_menu = ControlsMenu::new(&mut state);
+    let mut settings = Settings::default();
+    let mut audio = Audio::null(NULL_SAMPLE_RATE);
+    let mut saves = slots("menu");
+    let checkpoint = glm::vec2(912.0, 640.0);
+    let game = SaveGame {
+        level: 3,
+        checkpoint: Some(checkpoint),
+        health: 40,
+        settings: Settings {
+            volume: 0.5,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    saves.write(1, &game).unwrap();
+    let mut context = Context {
+        state: &mut state,
+        controls_menu: &mut controls_menu,
+        settings: &mut settings,
+        audio: &mut audio,
+        saves: &mut saves,
+    };
+    let mut actions = ActionState::new();
+
+    // Play has the focus, the slot row is above it
+    let menu = MainMenu::new(context.state);
+    let mut scenes = SceneStack::new(Box::new(menu));
+    wait(&mut scenes, &mut actions, &mut context, 1);
+    tap(&mut scenes, &mut actions, &mut context, &[Action::MoveUp]);
+    tap(
+        &mut scenes,
+        &mut actions,
+        &mut context,
+        &[Action::MoveRight],
+    );
+    assert_eq!(context.saves.current, 1);
+    tap(&mut scenes, &mut actions, &mut context, &[Action::MoveDown]);
+    tap(&mut scenes, &mut actions, &mut context, &[Action::Jump]);
+    let world = scenes.world().unwrap();
+    assert_eq!(world.level.number, 3);
+    assert_eq!(world.player.fighter.health.current, 40);
+    let body = &world.player.body;
+    assert_eq!(glm::vec2(body.centre().x, body.bottom()), checkpoint);
+    assert_eq!(context.settings.volume, 0.5);
+
+    // Dying brings the knight back to the checkpoint with the saved health
+    let mut playing = Playing::resume(context.state, &game);
+    playing.world.player.fighter.health.damage(1000);
+    let mut scenes = SceneStack::new(Box::new(playing));
+    while scenes.len() == 1 {
+        wait(&mut scenes, &mut actions, &mut context, 1);
+    }
+    wait(&mut scenes, &mut actions, &mut context, 60);
+    tap(&mut scenes, &mut actions, &mut context, &[Action::Attack]);
+    let world = scenes.world().unwrap();
+    assert_eq!(world.player.fighter.health.current, 40);
+    assert_eq!(world.checkpoint, Some(checkpoint));
+
+    // An empty slot starts a new game and saves it straight away
+    let menu = MainMenu::new(context.state);
+    let mut scenes = SceneStack::new(Box::new(menu));
+    wait(&mut scenes, &mut actions, &mut context, 1);
+    tap(&mut scenes, &mut actions, &mut context, &[Action::MoveUp]);
+    tap(
+        &mut scenes,
+        &mut actions,
+        &mut context,
+        &[Action::MoveRight],
+    );
+    tap(&mut scenes, &mut actions, &mut context, &[Action::MoveDown]);
+    tap(&mut scenes, &mut actions, &mut context, &[Action::Jump]);
+    assert_eq!(scenes.world().unwrap().level.number, 1);
+    assert_eq!(context.saves.current, 2);
+    assert_eq!(context.saves.load(2).unwrap().unwrap().level, 1);
+
+    // A slot it can't read, here from a newer game, is kept and not played
+    let newer = "version 99\nlevel 1\n";
+    std::fs::write(context.saves.path(0), newer).unwrap();
+    let menu = MainMenu::new(context.state);
+    let mut scenes = SceneStack::new(Box::new(menu));
+    wait(&mut scenes, &mut actions, &mut context, 1);
+    tap(&mut scenes, &mut actions, &mut context, &[Action::MoveUp]);
+    tap(
+        &mut scenes,
+        &mut actions,
+        &mut context,
+        &[Action::MoveRight],
+    );
+    assert_eq!(context.saves.current, 0);
+    tap(&mut scenes, &mut actions, &mut context, &[Action::MoveDown]);
+    tap(&mut scenes, &mut actions, &mut context, &[Action::Jump]);
+    assert!(scenes.world().is_none());
+    assert_eq!(
+        std::fs::read_to_string(context.saves.path(0)).unwrap(),
+        newer
+    );
+}
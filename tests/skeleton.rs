@@ -0,0 +1,178 @@
+use rusty_knight::model::animation::AnimationSet;
+use rusty_knight::model::combat::{self, Combatant, DamageEvent};
+use rusty_knight::model::level::{CollisionGrid, TileKind};
+use rusty_knight::model::physics::Body;
+use rusty_knight::model::player::Player;
+use rusty_knight::model::skeleton::{self, Behaviour, Skeleton};
+use rusty_knight::platform::input::{ACTION_COUNT, ActionState};
+
+const DT: f32 = 1.0 / 60.0;
+
+// 40x20 tiles with a floor along row 15
+fn floor() -> CollisionGrid {
+    let mut grid = CollisionGrid::new(40, 20);
+    for x in 0..40 {
+        for y in 15..20 {
+            grid.set(x, y, TileKind::Solid);
+        }
+    }
+    grid
+}
+
+// Somewhere for the player to stand, feet on the floor
+fn standing_at(x: f32) -> Body {
+    Body::new(glm::vec2(x - 10.0, 196.0), glm::vec2(20.0, 44.0))
+}
+
+fn ticks(
+    skeleton: &mut Skeleton,
+    animations: &AnimationSet,
+    grid: &CollisionGrid,
+    player: &Body,
+    count: u32,
+) {
+    for _ in 0..count {
+        skeleton.update(DT, animations, grid, player);
+    }
+}
+
+#[test]
+fn patrols_between_ledges_and_walls() {
+    // A platform along row 10 from column 5 to 15, with a wall at its right end
+    let mut grid = CollisionGrid::new(40, 20);
+    for x in 5..=15 {
+        grid.set(x, 10, TileKind::Solid);
+    }
+    grid.set(15, 9, TileKind::Solid);
+    let animations = AnimationSet::load(skeleton::ANIMATIONS);
+    let mut skeleton = Skeleton::new(&animations, glm::vec2(160.0, 160.0));
+    let player = standing_at(600.0);
+
+    let mut seen = (f32::MAX, f32::MIN);
+    let mut turned_left = false;
+    for _ in 0..(15.0 / DT) as u32 {
+        ticks(&mut skeleton, &animations, &grid, &player, 1);
+        seen.0 = seen.0.min(skeleton.body.position.x);
+        seen.1 = seen.1.max(skeleton.body.right());
+        turned_left |= skeleton.facing_left;
+        assert_eq!(skeleton.behaviour(), Behaviour::Patrol);
+    }
+
+    assert!(skeleton.body.contacts.ground);
+    assert!(turned_left);
+    // Walked from the wall to the far edge without falling off
+    assert!(seen.0 < 5.0 * 16.0 + 4.0, "left end {}", seen.0);
+    assert_eq!(seen.1, 15.0 * 16.0);
+    assert_eq!(skeleton.body.bottom(), 160.0);
+}
+
+#[test]
+fn notices_the_player_in_front_when_nothing_is_in_the_way() {
+    let animations = AnimationSet::load(skeleton::ANIMATIONS);
+    let notices = |grid: &CollisionGrid, player: &Body| {
+        let mut skeleton = Skeleton::new(&animations, glm::vec2(100.0, 240.0));
+        ticks(&mut skeleton, &animations, grid, player, 2);
+        skeleton.behaviour() == Behaviour::Chase
+    };
+
+    let grid = floor();
+    assert!(notices(&grid, &standing_at(220.0)));
+    // Too far away, or behind its back
+    assert!(!notices(&grid, &standing_at(400.0)));
+    assert!(!notices(&grid, &standing_at(40.0)));
+
+    // A wall in between blocks the view
+    let mut walled = floor();
+    for y in 8..15 {
+        walled.set(10, y, TileKind::Solid);
+    }
+    assert!(!notices(&walled, &standing_at(220.0)));
+    assert!(walled.line_of_sight(glm::vec2(100.0, 200.0), glm::vec2(150.0, 200.0)));
+    assert!(!walled.line_of_sight(glm::vec2(100.0, 200.0), glm::vec2(220.0, 200.0)));
+}
+
+#[test]
+fn chases_and_gives_up_once_out_of_sight() {
+    let grid = floor();
+    let animations = AnimationSet::load(skeleton::ANIMATIONS);
+    let mut skeleton = Skeleton::new(&animations, glm::vec2(100.0, 240.0));
+
+    ticks(&mut skeleton, &animations, &grid, &standing_at(220.0), 30);
+    assert_eq!(skeleton.behaviour(), Behaviour::Chase);
+    assert!(skeleton.body.centre().x > 130.0);
+
+    // Runs the other way once the player gets past
+    ticks(&mut skeleton, &animations, &grid, &standing_at(20.0), 30);
+    assert!(skeleton.facing_left);
+    assert_eq!(skeleton.behaviour(), Behaviour::Chase);
+
+    // Far away it keeps looking for a while, then goes back to patrolling
+    let gone = standing_at(620.0);
+    ticks(&mut skeleton, &animations, &grid, &gone, 60);
+    assert_eq!(skeleton.behaviour(), Behaviour::Chase);
+    ticks(&mut skeleton, &animations, &grid, &gone, 90);
+    assert_eq!(skeleton.behaviour(), Behaviour::Patrol);
+}
+
+#[test]
+fn attacks_take_turns_and_hurt_the_knight() {
+    let grid = floor();
+    let animations = AnimationSet::load(skeleton::ANIMATIONS);
+    // Walking left towards the knight, so the flipped frames are placed too
+    let mut skeleton = Skeleton::new(&animations, glm::vec2(300.0, 240.0));
+    skeleton.facing_left = true;
+    let mut player = Player::load(glm::vec2(200.0, 240.0));
+    let mut actions = ActionState::new();
+
+    let mut clips = Vec::new();
+    let mut events = Vec::new();
+    for _ in 0..(6.0 / DT) as u32 {
+        actions.advance([0.0; ACTION_COUNT]);
+        player.update(DT, &actions, &grid);
+        skeleton.update(DT, &animations, &grid, &player.body);
+        let clip = skeleton.animator.clip_name(&animations).to_string();
+        if clip.starts_with("attack") && clips.last() != Some(&clip) {
+            clips.push(clip);
+        }
+        events.extend(combat::exchange_blows(&mut [&mut player, &mut skeleton]));
+    }
+
+    assert_eq!(&clips[..3], ["attack_a", "attack_b", "attack_a"]);
+    assert!(!events.is_empty());
+    assert!(events.iter().all(|e| (e.attacker, e.target) == (1, 0)));
+    assert!(events.iter().all(|e| e.knockback.x < 0.0));
+    assert!(player.fighter.health.current < player.fighter.health.max);
+}
+
+#[test]
+fn hits_stagger_and_death_despawns() {
+    let grid = floor();
+    let animations = AnimationSet::load(skeleton::ANIMATIONS);
+    let mut skeleton = Skeleton::new(&animations, glm::vec2(100.0, 240.0));
+    let player = standing_at(600.0);
+    ticks(&mut skeleton, &animations, &grid, &player, 10);
+
+    let hit = DamageEvent {
+        attacker: 0,
+        target: 1,
+        damage: 10,
+        knockback: glm::vec2(-150.0, -100.0),
+        position: skeleton.body.centre(),
+    };
+    skeleton.fighter.health.damage(hit.damage);
+    skeleton.take_hit(&hit);
+    ticks(&mut skeleton, &animations, &grid, &player, 1);
+    assert_eq!(skeleton.behaviour(), Behaviour::Hit);
+    // Turned to face whoever hit it, which is worth chasing even unseen
+    assert!(!skeleton.facing_left);
+    ticks(&mut skeleton, &animations, &grid, &player, 60);
+    assert_eq!(skeleton.behaviour(), Behaviour::Chase);
+
+    skeleton.fighter.health.damage(100);
+    skeleton.take_hit(&hit);
+    ticks(&mut skeleton, &animations, &grid, &player, 1);
+    assert_eq!(skeleton.behaviour(), Behaviour::Dead);
+    assert!(!skeleton.despawned());
+    ticks(&mut skeleton, &animations, &grid, &player, 120);
+    assert!(skeleton.despawned());
+}
@@ -0,0 +1,126 @@
+use std::path::Path;
+
+use rusty_knight::renderer::text::{Align, Font, TextStyle};
+
+// Glyphs 4 pixels wide that advance by 5, with A and V tucked together
+const FONT: &str = r#"
+info face="Test Font" size=8 unicode=1
+common lineHeight=10 base=8 scaleW=64 scaleH=64 pages=1
+page id=0 file="test atlas.png"
+chars count=4
+char id=32 x=0  y=0 width=0 height=0 xoffset=0 yoffset=0 xadvance=3 page=0 chnl=15
+char id=65 x=0  y=0 width=4 height=8 xoffset=0 yoffset=0 xadvance=5 page=0 chnl=15
+char id=86 x=8  y=0 width=4 height=8 xoffset=0 yoffset=0 xadvance=5 page=0 chnl=15
+char id=63 x=16 y=0 width=4 height=8 xoffset=1 yoffset=2 xadvance=5 page=0 chnl=15
+char id=66 x=24 y=0 width=4 height=8 xoffset=0 xadvance=5
+char id=67 x=32 y=0 width=4 height=8 xoffset=0 yoffset=0 xadvance=5 page=1 chnl=15
+kernings count=1
+kerning first=65 second=86 amount=-2
+"#;
+
+fn font() -> Font {
+    let mut font = Font::parse(FONT, Path::new("assets/fonts"));
+    // Stands in for the loaded atlas, the sprites only carry the handle
+    font.textures = vec![7];
+    font
+}
+
+#[test]
+fn fonts_are_read_from_bmfont_text() {
+    let font = font();
+    assert_eq!(font.line_height, 10.0);
+    assert_eq!(font.base, 8.0);
+    assert_eq!(
+        Path::new(&font.pages[0]),
+        Path::new("assets/fonts/test atlas.png")
+    );
+    // The char line without a yoffset is dropped, as is C on a page that isn't there
+    assert_eq!(font.glyphs.len(), 4);
+    assert!(!font.glyphs.contains_key(&'C'));
+    assert_eq!(font.kernings[&('A', 'V')], -2.0);
+
+    let shipped = Font::load("assets/fonts/knight.fnt");
+    assert!(Path::new(&shipped.pages[0]).exists());
+    for character in ' '..='~' {
+        assert!(shipped.glyphs.contains_key(&character), "{character:?}");
+    }
+}
+
+#[test]
+fn lines_are_measured_with_kerning_and_missing_glyphs_replaced() {
+    let font = font();
+    assert_eq!(font.line_width("AV"), 8.0);
+    assert_eq!(font.line_width("VA"), 10.0);
+    // Unknown characters are drawn as question marks
+    assert_eq!(font.line_width("A!"), 10.0);
+    assert_eq!(font.glyph('!'), font.glyph('?'));
+
+    let style = TextStyle {
+        scale: 2.0,
+        ..Default::default()
+    };
+    assert_eq!(font.measure("AVA\nA", &style), glm::vec2(26.0, 40.0));
+}
+
+#[test]
+fn words_wrap_to_the_width() {
+    let font = font();
+    let mut style = TextStyle {
+        wrap_width: Some(25.0),
+        ..Default::default()
+    };
+    // "AA AA" is 23 wide, adding another word would make it 41
+    assert_eq!(font.lines("AA AA AAA", &style), ["AA AA", "AAA"]);
+    // Too long for any line, so it goes on one by itself
+    assert_eq!(font.lines("A AAAAAAAA A", &style), ["A", "AAAAAAAA", "A"]);
+    assert_eq!(font.lines("A\n\nA  A", &style), ["A", "", "A A"]);
+
+    style.scale = 2.0;
+    assert_eq!(font.lines("AA AA", &style), ["AA", "AA"]);
+    style.wrap_width = None;
+    assert_eq!(font.lines("AA AA AAA AAAA", &style), ["AA AA AAA AAAA"]);
+}
+
+#[test]
+fn glyphs_become_sprites_lined_up_by_alignment() {
+    let font = font();
+    let mut style = TextStyle {
+        scale: 2.0,
+        colour: glm::vec4(1.0, 0.5, 0.0, 1.0),
+        layer: 4,
+        ..Default::default()
+    };
+    let position = glm::vec2(100.0, 50.0);
+
+    // Spaces take room but draw nothing, kerning and offsets are scaled
+    let sprites = font.sprites("AV ?", position, &style);
+    assert_eq!(sprites.len(), 3);
+    let positions: Vec<glm::Vec2> = sprites.iter().map(|sprite| sprite.position).collect();
+    assert_eq!(
+        positions,
+        [
+            glm::vec2(100.0, 50.0),
+            glm::vec2(106.0, 50.0),
+            glm::vec2(124.0, 54.0)
+        ]
+    );
+    assert_eq!(sprites[1].source.x, 8.0);
+    assert!(sprites.iter().all(|sprite| sprite.texture == 7
+        && sprite.scale == glm::vec2(2.0, 2.0)
+        && sprite.tint == style.colour
+        && sprite.layer == 4));
+
+    // Lines of 10 and 20 pixels, one under the other
+    style.align = Align::Centre;
+    let sprites = font.sprites("A\nAA", position, &style);
+    assert_eq!(sprites[0].position, glm::vec2(95.0, 50.0));
+    assert_eq!(sprites[1].position, glm::vec2(90.0, 70.0));
+    style.align = Align::Right;
+    let sprites = font.sprites("A\nAA", position, &style);
+    assert_eq!(sprites[0].position, glm::vec2(90.0, 50.0));
+    assert_eq!(sprites[1].position, glm::vec2(80.0, 70.0));
+
+    // Nothing is drawn before the atlas is loaded
+    let unloaded = Font::parse(FONT, Path::new("assets/fonts"));
+    assert!(unloaded.sprites("AV ?", position, &style).is_empty());
+}
@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use rusty_knight::core::timestep::FixedTimestep;
+
+// 100 ticks a second, so a tick is exactly 10ms
+fn new_timestep(max_steps: u32) -> FixedTimestep {
+    FixedTimestep::new(100, max_steps)
+}
+
+fn ms(milliseconds: u64) -> Duration {
+    Duration::from_millis(milliseconds)
+}
+
+#[test]
+fn short_frames_add_up_to_ticks() {
+    let mut timestep = new_timestep(5);
+    assert_eq!(timestep.step_milliseconds(), 10.0);
+    assert_eq!(timestep.advance(ms(4)), 0);
+    assert_eq!(timestep.advance(ms(4)), 0);
+    assert_eq!(timestep.advance(ms(4)), 1);
+    assert!((timestep.alpha() - 0.2).abs() < 1e-5);
+}
+
+#[test]
+fn a_long_frame_runs_several_ticks() {
+    let mut timestep = new_timestep(5);
+    assert_eq!(timestep.advance(ms(35)), 3);
+    assert!((timestep.alpha() - 0.5).abs() < 1e-5);
+    // The half tick left over counts towards the next frame
+    assert_eq!(timestep.advance(ms(5)), 1);
+    assert!(timestep.alpha().abs() < 1e-5);
+}
+
+#[test]
+fn stalls_run_at_most_max_steps_and_drop_the_rest() {
+    let mut timestep = new_timestep(5);
+    assert_eq!(timestep.advance(ms(2003)), 5);
+    // Only the part of a tick is kept, not the 195 ticks that were skipped
+    assert!((timestep.alpha() - 0.3).abs() < 1e-5);
+    assert_eq!(timestep.advance(ms(0)), 0);
+    assert_eq!(timestep.advance(ms(7)), 1);
+
+    // A cap of 0 still runs a tick
+    let mut lowest = new_timestep(0);
+    assert_eq!(lowest.advance(ms(30)), 1);
+    assert!(lowest.alpha().abs() < 1e-5);
+}
+
+#[test]
+fn alpha_stays_below_one() {
+    let mut timestep = new_timestep(3);
+    for frame in [0, 1, 9, 10, 11, 16, 17, 25, 29, 30, 31, 99, 250, 1000] {
+        timestep.advance(ms(frame));
+        let alpha = timestep.alpha();
+        assert!((0.0..1.0).contains(&alpha), "{alpha} after {frame}ms");
+    }
+}
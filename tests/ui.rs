@@ -0,0 +1,222 @@
+// Only the headless state is needed here
+#[allow(dead_code)]
+mod common;
+
+use common::golden;
+use rusty_knight::platform::input::{ACTION_COUNT, Action, ActionState};
+use rusty_knight::ui::widgets::{Icon, Label, Ui};
+
+fn holding(actions: &mut ActionState, held: &[Action]) {
+    let mut values = [0.0; ACTION_COUNT];
+    for action in held {
+        values[*action as usize] = 1.0;
+    }
+    actions.advance(values);
+}
+
+struct Menu {
+    music: bool,
+    volume: f32,
+}
+
+// A button, a toggle, a slider and an icon button, answering which were used
+fn declare(ui: &mut Ui, actions: &ActionState, menu: &mut Menu) -> [bool; 4] {
+    ui.begin(actions);
+    ui.shade(0.5);
+    [
+        ui.button(Label::Play, glm::vec2(0.0, 0.0)),
+        ui.toggle(&mut menu.music, glm::vec2(0.0, 100.0)),
+        ui.slider(&mut menu.volume, glm::vec2(0.0, 200.0)),
+        ui.icon_button(Icon::Home, glm::vec2(0.0, 300.0)),
+    ]
+}
+
+#[test]
+fn focus_moves_in_the_order_widgets_are_declared() {
+    let Some(mut state) = golden::new_state() else {
+        return;
+    };
+    let mut ui = Ui::new(&mut state);
+    let mut actions = ActionState::new();
+    let mut menu = Menu {
+        music: true,
+        volume: 0.5,
+    };
+    let mut step = |ui: &mut Ui, held: &[Action]| {
+        holding(&mut actions, held);
+        declare(ui, &actions, &mut menu)
+    };
+
+    step(&mut ui, &[]);
+    assert_eq!(ui.focus(), 0);
+    // Wraps around from the top
+    step(&mut ui, &[Action::MoveUp]);
+    assert_eq!(ui.focus(), 3);
+    step(&mut ui, &[]);
+    step(&mut ui, &[Action::MoveDown]);
+    assert_eq!(ui.focus(), 0);
+
+    // Sideways moves too, until it reaches the slider
+    step(&mut ui, &[]);
+    step(&mut ui, &[Action::MoveRight]);
+    step(&mut ui, &[]);
+    step(&mut ui, &[Action::MoveRight]);
+    assert_eq!(ui.focus(), 2);
+    step(&mut ui, &[]);
+    step(&mut ui, &[Action::MoveRight]);
+    assert_eq!(ui.focus(), 2);
+    step(&mut ui, &[]);
+    step(&mut ui, &[Action::MoveDown]);
+    assert_eq!(ui.focus(), 3);
+}
+
+#[test]
+fn widgets_go_off_when_confirm_is_let_go() {
+    let Some(mut state) = golden::new_state() else {
+        return;
+    };
+    let mut ui = Ui::new(&mut state);
+    let mut actions = ActionState::new();
+    let mut menu = Menu {
+        music: true,
+        volume: 0.5,
+    };
+
+    // Held since before the menu showed up, so it does nothing
+    holding(&mut actions, &[Action::Jump]);
+    holding(&mut actions, &[Action::Jump]);
+    assert_eq!(declare(&mut ui, &actions, &mut menu), [false; 4]);
+    holding(&mut actions, &[]);
+    assert_eq!(declare(&mut ui, &actions, &mut menu), [false; 4]);
+
+    holding(&mut actions, &[Action::Attack]);
+    assert_eq!(declare(&mut ui, &actions, &mut menu), [false; 4]);
+    holding(&mut actions, &[]);
+    assert_eq!(
+        declare(&mut ui, &actions, &mut menu),
+        [true, false, false, false]
+    );
+
+    // Moving away while held lets the press go
+    ui.set_focus(1);
+    holding(&mut actions, &[Action::Jump]);
+    declare(&mut ui, &actions, &mut menu);
+    holding(&mut actions, &[Action::Jump, Action::MoveDown]);
+    declare(&mut ui, &actions, &mut menu);
+    holding(&mut actions, &[]);
+    assert_eq!(declare(&mut ui, &actions, &mut menu), [false; 4]);
+    assert!(menu.music);
+
+    ui.set_focus(1);
+    holding(&mut actions, &[Action::Jump]);
+    declare(&mut ui, &actions, &mut menu);
+    holding(&mut actions, &[]);
+    assert_eq!(
+        declare(&mut ui, &actions, &mut menu),
+        [false, true, false, false]
+    );
+    assert!(!menu.music);
+}
+
+#[test]
+fn a_press_goes_off_once_however_long_it_is_held() {
+    let Some(mut state) = golden::new_state() else {
+        return;
+    };
+    let mut ui = Ui::new(&mut state);
+    let mut actions = ActionState::new();
+    let mut menu = Menu {
+        music: true,
+        volume: 0.5,
+    };
+
+    // Pressed on the very first tick the menu is up
+    holding(&mut actions, &[Action::Attack]);
+    assert_eq!(declare(&mut ui, &actions, &mut menu), [false; 4]);
+    for _ in 0..10 {
+        holding(&mut actions, &[Action::Attack]);
+        assert_eq!(declare(&mut ui, &actions, &mut menu), [false; 4]);
+    }
+    holding(&mut actions, &[]);
+    assert_eq!(
+        declare(&mut ui, &actions, &mut menu),
+        [true, false, false, false]
+    );
+    holding(&mut actions, &[]);
+    assert_eq!(declare(&mut ui, &actions, &mut menu), [false; 4]);
+
+    // Swapping one confirm for the other while held is still the same press
+    holding(&mut actions, &[Action::Jump]);
+    declare(&mut ui, &actions, &mut menu);
+    holding(&mut actions, &[Action::Jump, Action::Attack]);
+    declare(&mut ui, &actions, &mut menu);
+    holding(&mut actions, &[Action::Attack]);
+    assert_eq!(declare(&mut ui, &actions, &mut menu), [false; 4]);
+    holding(&mut actions, &[]);
+    assert_eq!(
+        declare(&mut ui, &actions, &mut menu),
+        [true, false, false, false]
+    );
+}
+
+#[test]
+fn sliders_step_and_stay_in_range() {
+    let Some(mut state) = golden::new_state() else {
+        return;
+    };
+    let mut ui = Ui::new(&mut state);
+    let mut actions = ActionState::new();
+    let mut menu = Menu {
+        music: true,
+        volume: 0.75,
+    };
+
+    // Left alone it keeps whatever value it was given
+    declare(&mut ui, &actions, &mut menu);
+    ui.set_focus(2);
+    holding(&mut actions, &[]);
+    assert_eq!(declare(&mut ui, &actions, &mut menu), [false; 4]);
+    assert_eq!(menu.volume, 0.75);
+
+    holding(&mut actions, &[Action::MoveRight]);
+    assert_eq!(
+        declare(&mut ui, &actions, &mut menu),
+        [false, false, true, false]
+    );
+    assert!((menu.volume - 0.9).abs() < 1e-4);
+    for _ in 0..5 {
+        holding(&mut actions, &[]);
+        declare(&mut ui, &actions, &mut menu);
+        holding(&mut actions, &[Action::MoveRight]);
+        declare(&mut ui, &actions, &mut menu);
+    }
+    assert_eq!(menu.volume, 1.0);
+    for _ in 0..15 {
+        holding(&mut actions, &[]);
+        declare(&mut ui, &actions, &mut menu);
+        holding(&mut actions, &[Action::MoveLeft]);
+        declare(&mut ui, &actions, &mut menu);
+    }
+    assert_eq!(menu.volume, 0.0);
+}
+
+#[test]
+fn focus_stays_on_a_widget_when_some_go_away() {
+    let Some(mut state) = golden::new_state() else {
+        return;
+    };
+    let mut ui = Ui::new(&mut state);
+    let actions = ActionState::new();
+    let mut menu = Menu {
+        music: true,
+        volume: 0.5,
+    };
+
+    declare(&mut ui, &actions, &mut menu);
+    ui.set_focus(3);
+    ui.begin(&actions);
+    ui.button(Label::Play, glm::vec2(0.0, 0.0));
+    ui.button(Label::Quit, glm::vec2(0.0, 100.0));
+    ui.begin(&actions);
+    assert_eq!(ui.focus(), 1);
+}